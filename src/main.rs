@@ -376,7 +376,7 @@ fn detect_language_code(ui_language: &str, template_path: Option<&str>, csv_path
                 // Manche Dateien nutzen formate wie "de_de" oder "en_us"
                 if p.contains('_') {
                     let tokens: Vec<&str> = p.split('_').collect();
-                    if let Some(lang_tok) = tokens.get(0) {
+                    if let Some(lang_tok) = tokens.first() {
                         if let Some(n2) = norm_token(lang_tok) { return n2; }
                     }
                 }
@@ -432,24 +432,385 @@ fn get_configured_output_dir_with_debug(use_custom: bool, custom_path: &str, gro
 /// Hauptkonfiguration für Bestellschein-Generierung
 /// 
 /// Diese Struktur enthält alle Einstellungen für:
-/// - QR-Code-Positionen und -Größen  
-/// - Vertreternummer-Positionen und Schriftarten
+/// - die Overlay-Elemente (QR-Codes, Texte, Bilder, Strichcodes, Rechtecke, Linien)
+/// - Link-Vorlage und Formular-Optionen
 /// 
 /// # Beispiel
 /// ```
 /// let config = Config {
-///     qr_codes: vec![QrCodeConfig { x: 50.0, y: 50.0, size: 18.0, pages: vec![1], all_pages: false }],
-///     vertreter: vec![VertreterConfig { x: 77.0, y: 80.0, size: 12.0, pages: vec![1], all_pages: false, 
-///                                      font_name: "Arial".to_string(), font_style: "Normal".to_string(), 
-///                                      font_path: "".to_string() }],
+///     elements: vec![
+///         Element::new(ElementKind::Qr(QrCodeConfig { x: 50.0, y: 50.0, size: 18.0, pages: vec![1], all_pages: false, ..Default::default() })),
+///         Element::new(ElementKind::Text(VertreterConfig { x: 77.0, y: 80.0, size: 12.0, ..Default::default() })),
+///     ],
+///     ..Default::default()
 /// };
 /// ```
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "ConfigFile")]
 pub struct Config {
-    /// Liste der QR-Code-Konfigurationen
-    pub qr_codes: Vec<QrCodeConfig>,
-    /// Liste der Vertreternummer-Konfigurationen  
-    pub vertreter: Vec<VertreterConfig>,
+    /// Alle Overlay-Elemente; gezeichnet wird nach `z_order`, bei Gleichstand in Listenreihenfolge
+    pub elements: Vec<Element>,
+    /// Link-Vorlage für QR-Codes, z.B. `https://shop.natugena.de/{lang}/?ref={kundennr}&utm_source={campaign}`.
+    /// Platzhalter: `{kundennr}`, `{lang}`, `{lang_code}`, `{group}`, `{campaign}`, `{date}`
+    /// sowie jede CSV-Spalte über ihren Namen aus der Kopfzeile. Leer = nur CSV-Links verwenden.
+    #[serde(default)]
+    pub link_template: String,
+    /// Wenn true, wird die Link-Vorlage auch verwendet, wenn die CSV einen Link enthält
+    #[serde(default)]
+    pub force_link_template: bool,
+    /// Formularfelder der Vorlage nach dem Ausfüllen in den Seiteninhalt übernehmen
+    /// (Felder sind danach nicht mehr editierbar)
+    #[serde(default)]
+    pub flatten_form: bool,
+    /// Seiten der Ausgabe nach dem Platzieren, z.B. "2-3" oder "1,1" (weglassen, umsortieren,
+    /// doppeln); leer = alle Seiten der Vorlage
+    #[serde(default)]
+    pub output_pages: String,
+    /// Titel, Autor usw. der erzeugten PDFs (Info-Dictionary und XMP)
+    #[serde(default)]
+    pub metadata: MetadataConfig,
+    /// Optionale Verschlüsselung der Ausgaben mit Berechtigungen
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+}
+
+/// Config-Datei beim Einlesen über serde: neue Elementliste oder die früheren getrennten Listen
+/// (`qr_codes`, `vertreter`, `barcodes`, `images`), die in Elemente übernommen werden
+#[derive(Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    elements: Vec<Element>,
+    #[serde(default)]
+    qr_codes: Vec<QrCodeConfig>,
+    #[serde(default)]
+    vertreter: Vec<VertreterConfig>,
+    #[serde(default)]
+    barcodes: Vec<BarcodeConfig>,
+    #[serde(default)]
+    images: Vec<ImageConfig>,
+    #[serde(default)]
+    link_template: String,
+    #[serde(default)]
+    force_link_template: bool,
+    #[serde(default)]
+    flatten_form: bool,
+    #[serde(default)]
+    output_pages: String,
+    #[serde(default)]
+    metadata: MetadataConfig,
+    #[serde(default)]
+    encryption: EncryptionConfig,
+}
+
+impl From<ConfigFile> for Config {
+    fn from(file: ConfigFile) -> Self {
+        let mut elements = file.elements;
+        elements.extend(elements_from_lists(file.images, file.qr_codes, file.barcodes, file.vertreter));
+        Config {
+            elements,
+            link_template: file.link_template,
+            force_link_template: file.force_link_template,
+            flatten_form: file.flatten_form,
+            output_pages: file.output_pages,
+            metadata: file.metadata,
+            encryption: file.encryption,
+        }
+    }
+}
+
+// Getrennte Listen des früheren Config-Formats in Elemente übernehmen. Die Reihenfolge entspricht
+// der bisherigen Zeichenreihenfolge: Bilder, QR-Codes, Strichcodes, Vertreternummern.
+fn elements_from_lists(images: Vec<ImageConfig>, qr_codes: Vec<QrCodeConfig>, barcodes: Vec<BarcodeConfig>, vertreter: Vec<VertreterConfig>) -> Vec<Element> {
+    images.into_iter().map(ElementKind::Image)
+        .chain(qr_codes.into_iter().map(ElementKind::Qr))
+        .chain(barcodes.into_iter().map(ElementKind::Barcode))
+        .chain(vertreter.into_iter().map(ElementKind::Text))
+        .map(Element::new)
+        .collect()
+}
+
+/// Dokument-Metadaten der erzeugten PDFs. Alle Felder sind Muster mit den Platzhaltern der
+/// Link-Vorlage plus `{template}` (Dateiname der Vorlage ohne Endung); leer = Eintrag entfällt.
+/// Vertreternummer, Gruppe, Sprache, Vorlagen-Prüfsumme und Generator werden immer gesetzt.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MetadataConfig {
+    #[serde(default = "default_metadata_title")]
+    pub title: String,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default = "default_metadata_keywords")]
+    pub keywords: String,
+    /// Weitere eigene Schlüssel (Name → Muster), z.B. `Kampagne = "{campaign}"`
+    #[serde(default)]
+    pub custom: std::collections::BTreeMap<String, String>,
+}
+
+fn default_metadata_title() -> String {
+    "{template} {vertreternummer}".to_string()
+}
+
+fn default_metadata_keywords() -> String {
+    "{vertreternummer}, {group}, {lang}".to_string()
+}
+
+/// Verschlüsselung der erzeugten PDFs (AES-256, Standard-Sicherheitshandler Revision 6).
+/// Ohne Benutzer-Passwort öffnet sich die Datei ohne Abfrage, die Berechtigungen gelten trotzdem;
+/// nur mit dem Besitzer-Passwort lassen sie sich aufheben.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub owner_password: String,
+    /// Passwort zum Öffnen (leer = ohne Passwort lesbar)
+    #[serde(default)]
+    pub user_password: String,
+    #[serde(default = "default_permission")]
+    pub allow_print: bool,
+    /// Seiten ändern, einfügen, drehen usw.
+    #[serde(default)]
+    pub allow_modify: bool,
+    /// Text und Grafiken kopieren
+    #[serde(default = "default_permission")]
+    pub allow_copy: bool,
+    /// Kommentare hinzufügen
+    #[serde(default)]
+    pub allow_annotate: bool,
+    /// Formularfelder ausfüllen
+    #[serde(default)]
+    pub allow_fill_forms: bool,
+}
+
+fn default_permission() -> bool { true }
+
+impl Default for EncryptionConfig {
+    fn default() -> Self {
+        EncryptionConfig {
+            enabled: false,
+            owner_password: String::new(),
+            user_password: String::new(),
+            allow_print: true,
+            allow_modify: false,
+            allow_copy: true,
+            allow_annotate: false,
+            allow_fill_forms: false,
+        }
+    }
+}
+
+impl EncryptionConfig {
+    /// Wert für /P (Bits 7, 8 und 13-32 sind immer gesetzt, Bit 10 Barrierefreiheit ebenfalls)
+    fn permission_flags(&self) -> i32 {
+        let mut p: u32 = 0xFFFF_F0C0 | 0x200;
+        if self.allow_print { p |= 0x4 | 0x800; }
+        if self.allow_modify { p |= 0x8 | 0x400; }
+        if self.allow_copy { p |= 0x10; }
+        if self.allow_annotate { p |= 0x20; }
+        if self.allow_fill_forms { p |= 0x100; }
+        p as i32
+    }
+}
+
+impl Default for MetadataConfig {
+    fn default() -> Self {
+        MetadataConfig {
+            title: default_metadata_title(),
+            subject: String::new(),
+            author: String::new(),
+            keywords: default_metadata_keywords(),
+            custom: std::collections::BTreeMap::new(),
+        }
+    }
+}
+
+/// Ein Overlay-Element mit den für alle Arten gemeinsamen Eigenschaften. Position und Seiten
+/// stehen in der jeweiligen Art (`x`, `y`, `pages`, `all_pages`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Element {
+    /// Anzeigename in der UI (leer = Art und Nummer, z.B. "QR-Code 1")
+    #[serde(default)]
+    pub name: String,
+    /// Ausgeblendete Elemente bleiben in der Config, werden aber nicht erzeugt
+    #[serde(default = "default_element_visible")]
+    pub visible: bool,
+    /// Zeichenreihenfolge: Elemente mit höherem Wert liegen über denen mit niedrigerem
+    #[serde(default)]
+    pub z_order: i32,
+    #[serde(flatten)]
+    pub kind: ElementKind,
+}
+
+fn default_element_visible() -> bool { true }
+
+/// Art eines Overlay-Elements mit ihren eigenen Einstellungen
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ElementKind {
+    Qr(QrCodeConfig),
+    /// Text wie die Vertreternummer (Format mit Platzhaltern)
+    Text(VertreterConfig),
+    Image(ImageConfig),
+    Barcode(BarcodeConfig),
+    Rect(RectConfig),
+    Line(LineConfig),
+}
+
+impl Element {
+    pub fn new(kind: ElementKind) -> Self {
+        Element { name: String::new(), visible: true, z_order: 0, kind }
+    }
+
+    /// Ob das Element auf der Seite `page` erscheint
+    pub fn on_page(&self, page: u32) -> bool {
+        let (all_pages, pages) = match &self.kind {
+            ElementKind::Qr(e) => (e.all_pages, &e.pages),
+            ElementKind::Text(e) => (e.all_pages, &e.pages),
+            ElementKind::Image(e) => (e.all_pages, &e.pages),
+            ElementKind::Barcode(e) => (e.all_pages, &e.pages),
+            ElementKind::Rect(e) => (e.all_pages, &e.pages),
+            ElementKind::Line(e) => (e.all_pages, &e.pages),
+        };
+        all_pages || pages.contains(&page)
+    }
+}
+
+impl ElementKind {
+    /// Schlüssel für Config-Dateien (`type = "..."`)
+    pub fn key(&self) -> &'static str {
+        match self {
+            ElementKind::Qr(_) => "qr",
+            ElementKind::Text(_) => "text",
+            ElementKind::Image(_) => "image",
+            ElementKind::Barcode(_) => "barcode",
+            ElementKind::Rect(_) => "rect",
+            ElementKind::Line(_) => "line",
+        }
+    }
+
+    /// Anzeigename in der UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            ElementKind::Qr(_) => "QR-Code",
+            ElementKind::Text(_) => "Vertreternummer",
+            ElementKind::Image(_) => "Bild",
+            ElementKind::Barcode(_) => "Strichcode",
+            ElementKind::Rect(_) => "Rechteck",
+            ElementKind::Line(_) => "Linie",
+        }
+    }
+}
+
+// Typisierter Zugriff auf die Elemente einer Art (in Listenreihenfolge). Alle pro Zeile erzeugten
+// Inhalte (QR-Inhalte, Texte, Strichcodes, Bildpfade) sind parallel zu dieser Reihenfolge.
+macro_rules! element_accessors {
+    ($($name:ident, $name_mut:ident, $variant:ident, $ty:ty;)*) => {
+        impl Config {
+            $(
+                #[allow(dead_code)] // nicht jede Art wird auch lesend abgefragt
+                pub fn $name(&self) -> impl Iterator<Item = &$ty> + '_ {
+                    self.elements.iter().filter_map(|e| match &e.kind { ElementKind::$variant(c) => Some(c), _ => None })
+                }
+
+                pub fn $name_mut(&mut self) -> impl Iterator<Item = &mut $ty> + '_ {
+                    self.elements.iter_mut().filter_map(|e| match &mut e.kind { ElementKind::$variant(c) => Some(c), _ => None })
+                }
+            )*
+        }
+    };
+}
+
+element_accessors! {
+    qr_codes, qr_codes_mut, Qr, QrCodeConfig;
+    vertreter, vertreter_mut, Text, VertreterConfig;
+    images, images_mut, Image, ImageConfig;
+    barcodes, barcodes_mut, Barcode, BarcodeConfig;
+    rects, rects_mut, Rect, RectConfig;
+    lines, lines_mut, Line, LineConfig;
+}
+
+impl Config {
+    /// Letztes Element der Art `key` (siehe `ElementKind::key`) entfernen, für die "- entfernen"-Knöpfe
+    pub fn remove_last_of(&mut self, key: &str) {
+        if let Some(pos) = self.elements.iter().rposition(|e| e.kind.key() == key) {
+            self.elements.remove(pos);
+        }
+    }
+
+    /// Indizes der Elemente in Zeichenreihenfolge (stabil: gleiche `z_order` in Listenreihenfolge)
+    pub fn draw_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.elements.len()).collect();
+        order.sort_by_key(|&i| self.elements[i].z_order);
+        order
+    }
+}
+
+/// Konfiguration für ein Rechteck (Rahmen und/oder Füllung), z.B. zum Abdecken alter Angaben
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RectConfig {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// Füllfarbe (None = ungefüllt)
+    #[serde(default)]
+    pub fill: Option<[u8; 3]>,
+    /// Rahmenfarbe (None = kein Rahmen)
+    #[serde(default)]
+    pub stroke: Option<[u8; 3]>,
+    #[serde(default = "default_stroke_width")]
+    pub stroke_width: f32,
+    pub pages: Vec<u32>,
+    pub all_pages: bool,
+}
+
+fn default_stroke_width() -> f32 { 1.0 }
+
+impl Default for RectConfig {
+    fn default() -> Self {
+        Self {
+            x: 50.0,
+            y: 50.0,
+            width: 120.0,
+            height: 40.0,
+            fill: None,
+            stroke: Some([0, 0, 0]),
+            stroke_width: default_stroke_width(),
+            pages: vec![1],
+            all_pages: false,
+        }
+    }
+}
+
+/// Konfiguration für eine Linie von (x, y) nach (x2, y2)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LineConfig {
+    pub x: f32,
+    pub y: f32,
+    pub x2: f32,
+    pub y2: f32,
+    #[serde(default)]
+    pub color: [u8; 3],
+    #[serde(default = "default_stroke_width")]
+    pub stroke_width: f32,
+    pub pages: Vec<u32>,
+    pub all_pages: bool,
+}
+
+impl Default for LineConfig {
+    fn default() -> Self {
+        Self {
+            x: 50.0,
+            y: 50.0,
+            x2: 200.0,
+            y2: 50.0,
+            color: [0, 0, 0],
+            stroke_width: default_stroke_width(),
+            pages: vec![1],
+            all_pages: false,
+        }
+    }
 }
 
 /// Konfiguration für QR-Code-Platzierung
@@ -463,6 +824,368 @@ pub struct QrCodeConfig {
     pub size: f32,
     pub pages: Vec<u32>,      // Seiten für diesen QR-Code
     pub all_pages: bool,      // Wenn true, ignoriere pages und verwende alle Seiten
+    /// Logo in der Mitte des QR-Codes (PNG, JPEG oder PDF; leer = kein Logo).
+    /// Relative Pfade beziehen sich auf das Programmverzeichnis.
+    #[serde(default)]
+    pub logo_path: String,
+    /// Kantenlänge des Logos relativ zur QR-Code-Größe (0.05–0.30)
+    #[serde(default = "default_logo_size")]
+    pub logo_size: f32,
+    /// Inhalt des QR-Codes (Link, Kontakt, E-Mail oder Überweisung)
+    #[serde(default)]
+    pub payload: QrPayloadType,
+    /// Felder für Nicht-Link-Inhalte (siehe `QrPayloadType`), Werte dürfen Platzhalter
+    /// wie `{kundennr}` oder CSV-Spaltennamen enthalten
+    #[serde(default)]
+    pub payload_fields: std::collections::BTreeMap<String, String>,
+    /// Drehung gegen den Uhrzeigersinn in Grad um den Mittelpunkt des Codes
+    #[serde(default)]
+    pub rotation: f32,
+    /// Beschriftung unter dem Code (leer = keine), dreht mit dem Code mit
+    #[serde(default)]
+    pub caption: String,
+    #[serde(default = "default_caption_font")]
+    pub caption_font: String,
+    #[serde(default = "default_caption_size")]
+    pub caption_size: f32,
+    /// Abstand zwischen Code-Unterkante und Oberkante der Beschriftung in Punkten
+    #[serde(default = "default_caption_offset")]
+    pub caption_offset: f32,
+    /// Woher der Link dieses Elements kommt (nur für `QrPayloadType::Link`)
+    #[serde(default)]
+    pub link_source: QrLinkSource,
+    /// CSV-Spalte für `QrLinkSource::Column`
+    #[serde(default)]
+    pub link_column: String,
+    /// Eigene Link-Vorlage für `QrLinkSource::Template` (leer = Link-Vorlage der Config)
+    #[serde(default)]
+    pub link_template: String,
+    /// Name eines Formularfeldes der Vorlage (z.B. `QR`); wenn gesetzt, wird der Code in das
+    /// Feldrechteck gesetzt und x/y/size/Seiten werden ignoriert
+    #[serde(default)]
+    pub form_field: String,
+}
+
+/// Linkquelle eines QR-Elements
+///
+/// `Auto` entspricht dem bisherigen Verhalten: Sprachspalte nach UI-Sprache, Link-Vorlage
+/// der Config als Ersatz. Mit `German`/`English` lassen sich auf zweisprachigen Formularen
+/// beide Shops nebeneinander verlinken.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QrLinkSource {
+    #[default]
+    Auto,
+    German,
+    English,
+    Column,
+    Template,
+}
+
+impl QrLinkSource {
+    pub const ALL: [QrLinkSource; 5] = [QrLinkSource::Auto, QrLinkSource::German, QrLinkSource::English, QrLinkSource::Column, QrLinkSource::Template];
+
+    /// Schlüssel für Config-Dateien
+    pub fn key(&self) -> &'static str {
+        match self {
+            QrLinkSource::Auto => "auto",
+            QrLinkSource::German => "german",
+            QrLinkSource::English => "english",
+            QrLinkSource::Column => "column",
+            QrLinkSource::Template => "template",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        QrLinkSource::ALL.iter().copied().find(|t| t.key().eq_ignore_ascii_case(key.trim()))
+    }
+
+    /// Anzeigename in der UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            QrLinkSource::Auto => "Automatisch (UI-Sprache)",
+            QrLinkSource::German => "Deutsche Spalte",
+            QrLinkSource::English => "Englische Spalte",
+            QrLinkSource::Column => "Eigene CSV-Spalte",
+            QrLinkSource::Template => "Link-Vorlage",
+        }
+    }
+}
+
+fn default_logo_size() -> f32 { 0.2 }
+fn default_caption_font() -> String { "Helvetica".to_string() }
+fn default_caption_size() -> f32 { 7.0 }
+fn default_caption_offset() -> f32 { 2.0 }
+
+/// Art des QR-Inhalts
+///
+/// Verwendete Felder in `QrCodeConfig::payload_fields`:
+/// - `VCard`/`MeCard`: name, first_name, last_name, org, title, phone, mobile, email, url, note
+/// - `Mailto`: to, cc, subject, body
+/// - `Epc` (GiroCode): name, iban, bic, amount, purpose, reference (strukturierte
+///   RF-Referenz nach ISO 11649), text (freier Verwendungszweck)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QrPayloadType {
+    #[default]
+    Link,
+    VCard,
+    MeCard,
+    Mailto,
+    Epc,
+}
+
+impl QrPayloadType {
+    pub const ALL: [QrPayloadType; 5] = [QrPayloadType::Link, QrPayloadType::VCard, QrPayloadType::MeCard, QrPayloadType::Mailto, QrPayloadType::Epc];
+
+    /// Schlüssel für Config-Dateien
+    pub fn key(&self) -> &'static str {
+        match self {
+            QrPayloadType::Link => "link",
+            QrPayloadType::VCard => "vcard",
+            QrPayloadType::MeCard => "mecard",
+            QrPayloadType::Mailto => "mailto",
+            QrPayloadType::Epc => "epc",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        QrPayloadType::ALL.iter().copied().find(|t| t.key().eq_ignore_ascii_case(key.trim()))
+    }
+
+    /// Anzeigename in der UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            QrPayloadType::Link => "Link",
+            QrPayloadType::VCard => "Kontakt (vCard)",
+            QrPayloadType::MeCard => "Kontakt (MeCard)",
+            QrPayloadType::Mailto => "E-Mail (mailto)",
+            QrPayloadType::Epc => "Überweisung (EPC/GiroCode)",
+        }
+    }
+
+    /// Felder, die für diesen Typ in der UI angeboten werden
+    pub fn field_names(&self) -> &'static [&'static str] {
+        match self {
+            QrPayloadType::Link => &[],
+            QrPayloadType::VCard | QrPayloadType::MeCard => &["name", "first_name", "last_name", "org", "title", "phone", "mobile", "email", "url", "note"],
+            QrPayloadType::Mailto => &["to", "cc", "subject", "body"],
+            QrPayloadType::Epc => &["name", "iban", "bic", "amount", "purpose", "reference", "text"],
+        }
+    }
+
+    /// Sinnvolle Startwerte beim Umstellen des Typs in der UI
+    pub fn default_fields(&self) -> std::collections::BTreeMap<String, String> {
+        let pairs: &[(&str, &str)] = match self {
+            QrPayloadType::Link => &[],
+            QrPayloadType::VCard | QrPayloadType::MeCard => &[("name", "{name}"), ("org", "NatuGena GmbH"), ("phone", "{telefon}"), ("email", "{email}")],
+            QrPayloadType::Mailto => &[("to", "bestellung@natugena.de"), ("subject", "Bestellung Vertreter {kundennr}")],
+            QrPayloadType::Epc => &[("name", "NatuGena GmbH"), ("iban", ""), ("text", "Vorkasse {kundennr}")],
+        };
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+}
+
+impl Default for QrCodeConfig {
+    fn default() -> Self {
+        Self {
+            x: 50.0,
+            y: 50.0,
+            size: 18.0,
+            pages: vec![1],
+            all_pages: false,
+            logo_path: String::new(),
+            logo_size: default_logo_size(),
+            payload: QrPayloadType::Link,
+            payload_fields: std::collections::BTreeMap::new(),
+            rotation: 0.0,
+            caption: String::new(),
+            caption_font: default_caption_font(),
+            caption_size: default_caption_size(),
+            caption_offset: default_caption_offset(),
+            link_source: QrLinkSource::Auto,
+            link_column: String::new(),
+            link_template: String::new(),
+            form_field: String::new(),
+        }
+    }
+}
+
+/// Konfiguration für Strichcode-Platzierung
+///
+/// Position (linke untere Ecke) und Seiten wie bei `QrCodeConfig`. `size` ist die Breite,
+/// `bar_height` die Höhe der Striche; DataMatrix ist quadratisch mit Kantenlänge `size`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BarcodeConfig {
+    pub x: f32,
+    pub y: f32,
+    pub size: f32,
+    #[serde(default = "default_bar_height")]
+    pub bar_height: f32,
+    #[serde(default)]
+    pub symbology: BarcodeSymbology,
+    /// Inhalt mit Platzhaltern wie in Link-Vorlagen (Standard: `{kundennr}`)
+    #[serde(default = "default_barcode_content")]
+    pub content: String,
+    /// Klartextzeile unter dem Code
+    #[serde(default)]
+    pub show_text: bool,
+    #[serde(default = "default_barcode_text_size")]
+    pub text_size: f32,
+    pub pages: Vec<u32>,
+    pub all_pages: bool,
+}
+
+fn default_bar_height() -> f32 { 30.0 }
+fn default_barcode_content() -> String { "{kundennr}".to_string() }
+fn default_barcode_text_size() -> f32 { 8.0 }
+
+impl Default for BarcodeConfig {
+    fn default() -> Self {
+        Self {
+            x: 50.0,
+            y: 100.0,
+            size: 120.0,
+            bar_height: default_bar_height(),
+            symbology: BarcodeSymbology::Code128,
+            content: default_barcode_content(),
+            show_text: true,
+            text_size: default_barcode_text_size(),
+            pages: vec![1],
+            all_pages: false,
+        }
+    }
+}
+
+/// Konfiguration für Bild-Elemente (Logo, Unterschrift, Stempel)
+///
+/// `x`/`y` ist die linke untere Ecke des Rahmens `width` x `height`. Der Pfad darf Platzhalter
+/// wie Link-Vorlagen enthalten (z.B. `Data/Unterschriften/{Vertreter}.png`); ergibt er für eine
+/// Zeile einen leeren Pfad, entfällt das Bild dort.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImageConfig {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub path: String,
+    /// Umgang mit abweichendem Seitenverhältnis
+    #[serde(default)]
+    pub fit: ImageFit,
+    pub pages: Vec<u32>,
+    pub all_pages: bool,
+}
+
+impl Default for ImageConfig {
+    fn default() -> Self {
+        Self {
+            x: 50.0,
+            y: 50.0,
+            width: 120.0,
+            height: 60.0,
+            path: String::new(),
+            fit: ImageFit::Contain,
+            pages: vec![1],
+            all_pages: false,
+        }
+    }
+}
+
+/// Einpassen eines Bildes in seinen Rahmen
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFit {
+    /// Seitenverhältnis beibehalten, ganz sichtbar und im Rahmen zentriert
+    #[default]
+    Contain,
+    /// Seitenverhältnis beibehalten, Rahmen ganz füllen und Überstand abschneiden
+    Cover,
+    /// Auf den Rahmen strecken
+    Stretch,
+}
+
+impl ImageFit {
+    pub const ALL: [ImageFit; 3] = [ImageFit::Contain, ImageFit::Cover, ImageFit::Stretch];
+
+    /// Schlüssel für Config-Dateien
+    pub fn key(&self) -> &'static str {
+        match self {
+            ImageFit::Contain => "contain",
+            ImageFit::Cover => "cover",
+            ImageFit::Stretch => "stretch",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|fit| fit.key() == key)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImageFit::Contain => "Einpassen",
+            ImageFit::Cover => "Füllen (beschneiden)",
+            ImageFit::Stretch => "Strecken",
+        }
+    }
+
+    /// Platzierung (x, y, Breite, Höhe) eines Bildes mit natürlicher Größe `natural` im Rahmen
+    /// `frame` (x, y, Breite, Höhe); bei `Cover` ragt das Ergebnis über den Rahmen hinaus
+    fn place(&self, frame: (f32, f32, f32, f32), natural: (f32, f32)) -> (f32, f32, f32, f32) {
+        let (x, y, w, h) = frame;
+        let (nw, nh) = (natural.0.max(0.001), natural.1.max(0.001));
+        let scale = match self {
+            ImageFit::Stretch => return frame,
+            ImageFit::Contain => (w / nw).min(h / nh),
+            ImageFit::Cover => (w / nw).max(h / nh),
+        };
+        let (iw, ih) = (nw * scale, nh * scale);
+        (x + (w - iw) / 2.0, y + (h - ih) / 2.0, iw, ih)
+    }
+}
+
+/// Strichcode-Symbologie
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BarcodeSymbology {
+    #[default]
+    Code128,
+    /// Nur für 12- oder 13-stellige Ziffernfolgen (Prüfziffer wird ergänzt bzw. geprüft)
+    Ean13,
+    DataMatrix,
+    Pdf417,
+}
+
+impl BarcodeSymbology {
+    pub const ALL: [BarcodeSymbology; 4] = [BarcodeSymbology::Code128, BarcodeSymbology::Ean13, BarcodeSymbology::DataMatrix, BarcodeSymbology::Pdf417];
+
+    /// Schlüssel für Config-Dateien
+    pub fn key(&self) -> &'static str {
+        match self {
+            BarcodeSymbology::Code128 => "code128",
+            BarcodeSymbology::Ean13 => "ean13",
+            BarcodeSymbology::DataMatrix => "datamatrix",
+            BarcodeSymbology::Pdf417 => "pdf417",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        BarcodeSymbology::ALL.iter().copied().find(|t| t.key().eq_ignore_ascii_case(key.trim()))
+    }
+
+    /// Anzeigename in der UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            BarcodeSymbology::Code128 => "Code 128",
+            BarcodeSymbology::Ean13 => "EAN-13",
+            BarcodeSymbology::DataMatrix => "DataMatrix",
+            BarcodeSymbology::Pdf417 => "PDF417",
+        }
+    }
+
+    /// Quadratische 2D-Codes ignorieren `bar_height`
+    pub fn is_square(&self) -> bool {
+        matches!(self, BarcodeSymbology::DataMatrix)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -475,16 +1198,154 @@ pub struct VertreterConfig {
     pub font_name: String,    // Name der Schriftart (z.B. "Arial", "Times New Roman")
     pub font_size: f32,       // Schriftgröße für die Vertreternummer
     pub font_style: String,   // Style: "Normal", "Bold", "Italic", "BoldItalic"
+    /// Horizontale Ausrichtung relativ zu x (links, zentriert, rechtsbündig)
+    #[serde(default)]
+    pub align: TextAlign,
+    /// Worauf sich y bezieht (Grundlinie, Oberkante, Mitte)
+    #[serde(default)]
+    pub anchor: TextAnchor,
+    /// Drehung gegen den Uhrzeigersinn in Grad um den Ankerpunkt (x, y)
+    #[serde(default)]
+    pub rotation: f32,
+    /// Textfarbe als RGB
+    #[serde(default)]
+    pub color: [u8; 3],
+    /// Zusätzlicher Abstand nach jedem Zeichen in Punkten (`Tc`, z.B. für Kästchenfelder)
+    #[serde(default)]
+    pub char_spacing: f32,
+    /// Zusätzlicher Abstand nach jedem Leerzeichen in Punkten (`Tw`)
+    #[serde(default)]
+    pub word_spacing: f32,
+    /// Horizontale Skalierung in Prozent (`Tz`, 100 = unverändert)
+    #[serde(default = "default_horizontal_scaling")]
+    pub horizontal_scaling: f32,
+    /// Ausgegebener Text mit Platzhaltern wie `{kundennr}` oder CSV-Spaltennamen,
+    /// z.B. `Vertreter-Nr.: {kundennr}` (die Configs sind je Sprache getrennt)
+    #[serde(default = "default_vertreter_format")]
+    pub format: String,
+    /// Name eines Formularfeldes der Vorlage (z.B. `Vertreternummer`); wenn gesetzt, wird der
+    /// Text als Feldwert mit Erscheinungsbild eingetragen statt an x/y gezeichnet
+    #[serde(default)]
+    pub form_field: String,
+}
+
+fn default_horizontal_scaling() -> f32 { 100.0 }
+fn default_vertreter_format() -> String { "{kundennr}".to_string() }
+
+impl Default for VertreterConfig {
+    fn default() -> Self {
+        Self {
+            x: 77.0,
+            y: 80.0,
+            size: 12.0,
+            pages: vec![1],
+            all_pages: false,
+            font_name: "Arial".to_string(),
+            font_size: 12.0,
+            font_style: "Normal".to_string(),
+            align: TextAlign::Left,
+            anchor: TextAnchor::Baseline,
+            rotation: 0.0,
+            color: [0, 0, 0],
+            char_spacing: 0.0,
+            word_spacing: 0.0,
+            horizontal_scaling: default_horizontal_scaling(),
+            format: default_vertreter_format(),
+            form_field: String::new(),
+        }
+    }
+}
+
+/// Horizontale Textausrichtung
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl TextAlign {
+    pub const ALL: [TextAlign; 3] = [TextAlign::Left, TextAlign::Center, TextAlign::Right];
+
+    /// Schlüssel für Config-Dateien
+    pub fn key(&self) -> &'static str {
+        match self {
+            TextAlign::Left => "left",
+            TextAlign::Center => "center",
+            TextAlign::Right => "right",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        TextAlign::ALL.iter().copied().find(|t| t.key().eq_ignore_ascii_case(key.trim()))
+    }
+
+    /// Anzeigename in der UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            TextAlign::Left => "Links",
+            TextAlign::Center => "Zentriert",
+            TextAlign::Right => "Rechts",
+        }
+    }
+}
+
+/// Vertikaler Bezugspunkt eines Textes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextAnchor {
+    #[default]
+    Baseline,
+    Top,
+    Middle,
+}
+
+impl TextAnchor {
+    pub const ALL: [TextAnchor; 3] = [TextAnchor::Baseline, TextAnchor::Top, TextAnchor::Middle];
+
+    /// Schlüssel für Config-Dateien
+    pub fn key(&self) -> &'static str {
+        match self {
+            TextAnchor::Baseline => "baseline",
+            TextAnchor::Top => "top",
+            TextAnchor::Middle => "middle",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        TextAnchor::ALL.iter().copied().find(|t| t.key().eq_ignore_ascii_case(key.trim()))
+    }
+
+    /// Anzeigename in der UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            TextAnchor::Baseline => "Grundlinie",
+            TextAnchor::Top => "Oberkante",
+            TextAnchor::Middle => "Mitte",
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self { 
-            qr_codes: vec![QrCodeConfig { x: 50.0, y: 50.0, size: 18.0, pages: vec![1], all_pages: false }],
-            vertreter: vec![
-                VertreterConfig { x: 77.0, y: 80.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string() },
-                VertreterConfig { x: 100.0, y: 650.0, size: 10.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 10.0, font_style: "Normal".to_string() }
-            ],
+            elements: elements_from_lists(
+                Vec::new(),
+                vec![QrCodeConfig { x: 50.0, y: 50.0, size: 18.0, pages: vec![1], all_pages: false, ..Default::default() }],
+                Vec::new(),
+                vec![
+                    VertreterConfig { x: 77.0, y: 80.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string(), ..Default::default() },
+                    VertreterConfig { x: 100.0, y: 650.0, size: 10.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 10.0, font_style: "Normal".to_string(), ..Default::default() }
+                ],
+            ),
+            link_template: String::new(),
+            force_link_template: false,
+            flatten_form: false,
+            output_pages: String::new(),
+            metadata: MetadataConfig::default(),
+            encryption: EncryptionConfig::default(),
         }
     }
 }
@@ -498,20 +1359,30 @@ fn get_group_default_config(group: &str, is_messe: bool) -> Config {
             if is_messe {
                 // Apo Messe - andere Positionen
                 Config {
-                    qr_codes: vec![QrCodeConfig { x: 80.0, y: 70.0, size: 22.0, pages: vec![1, 2], all_pages: false }],
-                    vertreter: vec![
-                        VertreterConfig { x: 120.0, y: 100.0, size: 14.0, pages: vec![1, 2], all_pages: false, font_name: "Arial".to_string(), font_size: 14.0, font_style: "Normal".to_string() },
-                        VertreterConfig { x: 150.0, y: 700.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string() }
-                    ],
+                    elements: elements_from_lists(
+                        Vec::new(),
+                        vec![QrCodeConfig { x: 80.0, y: 70.0, size: 22.0, pages: vec![1, 2], all_pages: false, ..Default::default() }],
+                        Vec::new(),
+                        vec![
+                            VertreterConfig { x: 120.0, y: 100.0, size: 14.0, pages: vec![1, 2], all_pages: false, font_name: "Arial".to_string(), font_size: 14.0, font_style: "Normal".to_string(), ..Default::default() },
+                            VertreterConfig { x: 150.0, y: 700.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string(), ..Default::default() }
+                        ],
+                    ),
+                    ..Default::default()
                 }
             } else {
                 // Apo Normal - optimiert für Apotheken-Formulare
                 Config {
-                    qr_codes: vec![QrCodeConfig { x: 75.0, y: 60.0, size: 20.0, pages: vec![1], all_pages: false }],
-                    vertreter: vec![
-                        VertreterConfig { x: 100.0, y: 90.0, size: 14.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 14.0, font_style: "Normal".to_string() },
-                        VertreterConfig { x: 130.0, y: 680.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string() }
-                    ],
+                    elements: elements_from_lists(
+                        Vec::new(),
+                        vec![QrCodeConfig { x: 75.0, y: 60.0, size: 20.0, pages: vec![1], all_pages: false, ..Default::default() }],
+                        Vec::new(),
+                        vec![
+                            VertreterConfig { x: 100.0, y: 90.0, size: 14.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 14.0, font_style: "Normal".to_string(), ..Default::default() },
+                            VertreterConfig { x: 130.0, y: 680.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string(), ..Default::default() }
+                        ],
+                    ),
+                    ..Default::default()
                 }
             }
         },
@@ -519,20 +1390,30 @@ fn get_group_default_config(group: &str, is_messe: bool) -> Config {
             if is_messe {
                 // Endkunde Messe - angepasst für Messestände
                 Config {
-                    qr_codes: vec![QrCodeConfig { x: 60.0, y: 80.0, size: 24.0, pages: vec![1], all_pages: false }],
-                    vertreter: vec![
-                        VertreterConfig { x: 90.0, y: 120.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string() },
-                        VertreterConfig { x: 120.0, y: 720.0, size: 10.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 10.0, font_style: "Normal".to_string() }
-                    ],
+                    elements: elements_from_lists(
+                        Vec::new(),
+                        vec![QrCodeConfig { x: 60.0, y: 80.0, size: 24.0, pages: vec![1], all_pages: false, ..Default::default() }],
+                        Vec::new(),
+                        vec![
+                            VertreterConfig { x: 90.0, y: 120.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string(), ..Default::default() },
+                            VertreterConfig { x: 120.0, y: 720.0, size: 10.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 10.0, font_style: "Normal".to_string(), ..Default::default() }
+                        ],
+                    ),
+                    ..Default::default()
                 }
             } else {
                 // Endkunde Normal - Standard-Layout
                 Config {
-                    qr_codes: vec![QrCodeConfig { x: 50.0, y: 50.0, size: 18.0, pages: vec![1], all_pages: false }],
-                    vertreter: vec![
-                        VertreterConfig { x: 77.0, y: 80.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string() },
-                        VertreterConfig { x: 100.0, y: 650.0, size: 10.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 10.0, font_style: "Normal".to_string() }
-                    ],
+                    elements: elements_from_lists(
+                        Vec::new(),
+                        vec![QrCodeConfig { x: 50.0, y: 50.0, size: 18.0, pages: vec![1], all_pages: false, ..Default::default() }],
+                        Vec::new(),
+                        vec![
+                            VertreterConfig { x: 77.0, y: 80.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string(), ..Default::default() },
+                            VertreterConfig { x: 100.0, y: 650.0, size: 10.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 10.0, font_style: "Normal".to_string(), ..Default::default() }
+                        ],
+                    ),
+                    ..Default::default()
                 }
             }
         },
@@ -666,177 +1547,634 @@ fn load_app_settings() -> bool {
     false // Standard: Light Mode
 }
 
-// Hilfsfunktion: Parsen eines TOML-Strings in ein Config-Objekt
-fn parse_toml_to_config(toml: &str) -> Config {
-    // Startwerte - werden überschrieben wenn in der Datei gefunden
-    let mut qr_codes = Vec::new();
-    let mut vertreter = Vec::new();
-    let mut pages = Vec::new();
-
-    let mut in_qr_array = false;
-    let mut in_vertreter_array = false;
-    let mut _in_positions_section = false;
-    let mut _in_pages_section = false;
-
-    for l in toml.lines() {
-        let l = l.trim();
-
-        // Section-Header erkennen
-        if l == "[positions]" {
-            _in_positions_section = true;
-            _in_pages_section = false;
-            continue;
-        } else if l == "[pages]" {
-            _in_positions_section = false;
-            _in_pages_section = true;
-            continue;
-        } else if l.starts_with('[') {
-            // Andere Section
-            _in_positions_section = false;
-            _in_pages_section = false;
+// Inline-Tabelle einer Config-Zeile (`{ x = 1, font_name = "A, B" },`) in ihre Felder zerlegen.
+// Kommas innerhalb von Anführungszeichen oder eckigen Klammern trennen keine Felder; `\"` in
+// einem String beendet ihn nicht (siehe toml_quote).
+fn split_inline_table(line: &str) -> Vec<String> {
+    let inner = line.trim().trim_end_matches(',').trim();
+    let inner = inner.strip_prefix('{').unwrap_or(inner);
+    let inner = inner.strip_suffix('}').unwrap_or(inner);
+
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut bracket_depth = 0;
+    for c in inner.chars() {
+        if escaped {
+            escaped = false;
+            current.push(c);
             continue;
         }
+        match c {
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '[' if !in_quotes => bracket_depth += 1,
+            ']' if !in_quotes && bracket_depth > 0 => bracket_depth -= 1,
+            ',' if !in_quotes && bracket_depth == 0 => {
+                parts.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if !current.trim().is_empty() {
+        parts.push(current.trim().to_string());
+    }
+    parts
+}
 
-        // Einzelner QR-Code (Rückwärtskompatibilität)
-        if l.starts_with("qr_code =") && l.contains('{') {
-            if let Some(start) = l.find('{') {
-                let inner = &l[start+1..l.find('}').unwrap_or(l.len())];
-                let mut x = 50.0;
-                let mut y = 50.0;
-                let mut size = 18.0;
-                let mut all_pages = false;
-                for part in inner.split(',') {
-                    let part = part.trim();
-                    if part.starts_with("x =") {
-                        x = part[3..].trim().parse().unwrap_or(50.0);
-                    } else if part.starts_with("y =") {
-                        y = part[3..].trim().parse().unwrap_or(50.0);
-                    } else if part.starts_with("size =") {
-                        size = part[6..].trim().parse().unwrap_or(18.0);
-                    } else if part.starts_with("all_pages =") {
-                        all_pages = part[11..].trim() == "true";
-                    }
+// Zeichenkette für die TOML-Datei in Anführungszeichen setzen; `\` und `"` werden maskiert,
+// damit Pfade, Texte und Vorlagen mit diesen Zeichen die Inline-Tabelle nicht zerreißen
+fn toml_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Gegenstück zu toml_quote: Anführungszeichen entfernen und `\\` bzw. `\"` auflösen. Andere
+// Backslashes bleiben stehen, damit ältere Dateien mit unmaskierten Windows-Pfaden lesbar bleiben.
+fn toml_unquote(value: &str) -> String {
+    let value = value.trim();
+    let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+        return value.trim_matches('"').to_string();
+    };
+    let mut result = String::with_capacity(inner.len());
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(&next) = chars.peek() {
+                if next == '\\' || next == '"' {
+                    result.push(next);
+                    chars.next();
+                    continue;
                 }
-                qr_codes.push(QrCodeConfig { x, y, size, pages: vec![1], all_pages });
             }
         }
+        result.push(c);
+    }
+    result
+}
 
-        // QR-Code Array (funktioniert sowohl mit als auch ohne [positions] Section)
-        else if l.starts_with("qr_codes = [") {
-            in_qr_array = true;
-            continue;
-        } else if in_qr_array {
-            if l.starts_with(']') {
-                in_qr_array = false;
-                continue;
+// Schlüssel und Wert eines Eintrags trennen; der Schlüssel darf in Anführungszeichen stehen
+// (dann zählt ein `=` darin nicht als Trenner)
+fn split_toml_key_value(part: &str) -> Option<(String, &str)> {
+    let part = part.trim();
+    if !part.starts_with('"') {
+        let (key, value) = part.split_once('=')?;
+        return Some((key.trim().to_string(), value));
+    }
+    let mut escaped = false;
+    for (i, c) in part.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => {
+                let value = part[i + 1..].trim_start().strip_prefix('=')?;
+                return Some((toml_unquote(&part[..=i]), value));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Seitenangabe eines Elements: "ALL", "1;2;3" oder [1, 2, 3]
+fn parse_toml_pages_value(value: &str, all_pages: &mut bool, pages_vec: &mut Vec<u32>) {
+    let rhs = value.trim().trim_matches(',').trim().trim_matches('"');
+    if rhs.eq_ignore_ascii_case("ALL") {
+        *all_pages = true;
+        return;
+    }
+    let inner = rhs.trim_start_matches('[').trim_end_matches(']');
+    for tok in inner.split([';', ',']) {
+        if let Ok(n) = tok.trim().parse::<u32>() { pages_vec.push(n); }
+    }
+}
+
+// QR-Code aus einer Inline-Tabelle (`{ x = 50, y = 50, size = 18, ... }`) lesen
+fn parse_qr_toml_line(line: &str) -> QrCodeConfig {
+    let mut x = 50.0;
+    let mut y = 50.0;
+    let mut size = 18.0;
+    let mut all_pages = false;
+    let mut pages_vec: Vec<u32> = Vec::new();
+    let mut logo_path = String::new();
+    let mut logo_size = default_logo_size();
+    let mut payload = QrPayloadType::Link;
+    let mut payload_fields = std::collections::BTreeMap::new();
+    let mut link_source = QrLinkSource::Auto;
+    let mut link_column = String::new();
+    let mut link_template = String::new();
+    let mut rotation = 0.0;
+    let mut caption = String::new();
+    let mut caption_font = default_caption_font();
+    let mut caption_size = default_caption_size();
+    let mut caption_offset = default_caption_offset();
+    let mut form_field = String::new();
+    for part in split_inline_table(line) {
+        let part = part.trim();
+        if let Some(value) = part.strip_prefix("x =") {
+            x = value.trim().parse().unwrap_or(50.0);
+        } else if let Some(value) = part.strip_prefix("y =") {
+            y = value.trim().parse().unwrap_or(50.0);
+        } else if let Some(value) = part.strip_prefix("size =") {
+            size = value.trim().parse().unwrap_or(18.0);
+        } else if let Some(value) = part.strip_prefix("all_pages =") {
+            all_pages = value.trim() == "true";
+        } else if let Some(value) = part.strip_prefix("logo_path =") {
+            logo_path = toml_unquote(value);
+        } else if let Some(value) = part.strip_prefix("logo_size =") {
+            logo_size = value.trim().parse().unwrap_or(default_logo_size());
+        } else if let Some(value) = part.strip_prefix("payload =") {
+            payload = QrPayloadType::from_key(value.trim().trim_matches('"')).unwrap_or(QrPayloadType::Link);
+        } else if let Some(value) = part.strip_prefix("rotation =") {
+            rotation = value.trim().parse().unwrap_or(0.0);
+        } else if let Some(value) = part.strip_prefix("form_field =") {
+            form_field = toml_unquote(value);
+        } else if let Some(value) = part.strip_prefix("caption =") {
+            caption = toml_unquote(value);
+        } else if let Some(value) = part.strip_prefix("caption_font =") {
+            caption_font = toml_unquote(value);
+        } else if let Some(value) = part.strip_prefix("caption_size =") {
+            caption_size = value.trim().parse().unwrap_or(default_caption_size());
+        } else if let Some(value) = part.strip_prefix("caption_offset =") {
+            caption_offset = value.trim().parse().unwrap_or(default_caption_offset());
+        } else if let Some(value) = part.strip_prefix("link_source =") {
+            link_source = QrLinkSource::from_key(value.trim().trim_matches('"')).unwrap_or_default();
+        } else if let Some(value) = part.strip_prefix("link_column =") {
+            link_column = toml_unquote(value);
+        } else if let Some(value) = part.strip_prefix("link_template =") {
+            link_template = toml_unquote(value);
+        } else if let Some(value) = part.strip_prefix("payload_") {
+            if let Some((key, value)) = value.split_once('=') {
+                payload_fields.insert(key.trim().to_string(), toml_unquote(value));
+            }
+        } else if let Some(value) = part.strip_prefix("pages =") {
+            // pages can be written as a quoted semicolon-separated string or as an array; try to parse both
+            let mut rhs = value.trim().trim_matches(',').trim().to_string();
+            // remove surrounding quotes if present
+            if rhs.starts_with('"') && rhs.ends_with('"') {
+                rhs = rhs.trim_matches('"').to_string();
+            }
+            if rhs.eq_ignore_ascii_case("ALL") {
+                all_pages = true;
+            } else if rhs.starts_with('[') && rhs.ends_with(']') {
+                let inner = rhs.trim_start_matches('[').trim_end_matches(']');
+                for tok in inner.split(',') {
+                    if let Ok(n) = tok.trim().parse::<u32>() { pages_vec.push(n); }
+                }
+            } else {
+                // semicolon or comma separated values
+                for tok in rhs.split([';', ',']) {
+                    let t = tok.trim();
+                    if t.is_empty() { continue; }
+                    if let Ok(n) = t.parse::<u32>() { pages_vec.push(n); }
+                }
+            }
+        }
+    }
+    if pages_vec.is_empty() { pages_vec.push(1); }
+    QrCodeConfig { x, y, size, pages: pages_vec, all_pages, logo_path, logo_size, payload, payload_fields, rotation, caption, caption_font, caption_size, caption_offset, link_source, link_column, link_template, form_field }
+}
+
+// Strichcode aus einer Inline-Tabelle lesen
+fn parse_barcode_toml_line(line: &str) -> BarcodeConfig {
+    let mut barcode = BarcodeConfig { pages: Vec::new(), ..Default::default() };
+    for part in split_inline_table(line) {
+        let part = part.trim();
+        if let Some(value) = part.strip_prefix("x =") {
+            barcode.x = value.trim().parse().unwrap_or(50.0);
+        } else if let Some(value) = part.strip_prefix("y =") {
+            barcode.y = value.trim().parse().unwrap_or(100.0);
+        } else if let Some(value) = part.strip_prefix("size =") {
+            barcode.size = value.trim().parse().unwrap_or(120.0);
+        } else if let Some(value) = part.strip_prefix("bar_height =") {
+            barcode.bar_height = value.trim().parse().unwrap_or(default_bar_height());
+        } else if let Some(value) = part.strip_prefix("symbology =") {
+            barcode.symbology = BarcodeSymbology::from_key(value.trim().trim_matches('"')).unwrap_or_default();
+        } else if let Some(value) = part.strip_prefix("content =") {
+            barcode.content = toml_unquote(value);
+        } else if let Some(value) = part.strip_prefix("show_text =") {
+            barcode.show_text = value.trim() == "true";
+        } else if let Some(value) = part.strip_prefix("text_size =") {
+            barcode.text_size = value.trim().parse().unwrap_or(default_barcode_text_size());
+        } else if let Some(value) = part.strip_prefix("all_pages =") {
+            barcode.all_pages = value.trim() == "true";
+        } else if let Some(value) = part.strip_prefix("pages =") {
+            parse_toml_pages_value(value, &mut barcode.all_pages, &mut barcode.pages);
+        }
+    }
+    if barcode.pages.is_empty() { barcode.pages.push(1); }
+    barcode
+}
+
+// Bild aus einer Inline-Tabelle lesen
+fn parse_image_toml_line(line: &str) -> ImageConfig {
+    let mut image = ImageConfig { pages: Vec::new(), ..Default::default() };
+    for part in split_inline_table(line) {
+        let part = part.trim();
+        if let Some(value) = part.strip_prefix("x =") {
+            image.x = value.trim().parse().unwrap_or(50.0);
+        } else if let Some(value) = part.strip_prefix("y =") {
+            image.y = value.trim().parse().unwrap_or(50.0);
+        } else if let Some(value) = part.strip_prefix("width =") {
+            image.width = value.trim().parse().unwrap_or(120.0);
+        } else if let Some(value) = part.strip_prefix("height =") {
+            image.height = value.trim().parse().unwrap_or(60.0);
+        } else if let Some(value) = part.strip_prefix("path =") {
+            image.path = toml_unquote(value);
+        } else if let Some(value) = part.strip_prefix("fit =") {
+            image.fit = ImageFit::from_key(value.trim().trim_matches('"')).unwrap_or_default();
+        } else if let Some(value) = part.strip_prefix("all_pages =") {
+            image.all_pages = value.trim() == "true";
+        } else if let Some(value) = part.strip_prefix("pages =") {
+            parse_toml_pages_value(value, &mut image.all_pages, &mut image.pages);
+        }
+    }
+    if image.pages.is_empty() { image.pages.push(1); }
+    image
+}
+
+// Dokument-Metadaten aus einer Inline-Tabelle lesen (fehlende Schlüssel behalten ihren Default)
+fn parse_metadata_toml_line(line: &str) -> MetadataConfig {
+    let mut metadata = MetadataConfig::default();
+    for part in split_inline_table(line) {
+        let Some((key, value)) = split_toml_key_value(&part) else { continue };
+        let value = toml_unquote(value);
+        match key.as_str() {
+            "title" => metadata.title = value,
+            "subject" => metadata.subject = value,
+            "author" => metadata.author = value,
+            "keywords" => metadata.keywords = value,
+            key => {
+                if let Some(name) = key.strip_prefix("custom_") {
+                    metadata.custom.insert(name.to_string(), value);
+                }
+            }
+        }
+    }
+    metadata
+}
+
+// Verschlüsselung aus einer Inline-Tabelle lesen (die Zeile steht nur bei aktiver Verschlüsselung)
+fn parse_encryption_toml_line(line: &str) -> EncryptionConfig {
+    let mut encryption = EncryptionConfig { enabled: true, ..Default::default() };
+    for part in split_inline_table(line) {
+        let Some((key, value)) = part.trim().split_once('=') else { continue };
+        let value = value.trim();
+        match key.trim() {
+            "enabled" => encryption.enabled = value == "true",
+            "owner_password" => encryption.owner_password = toml_unquote(value),
+            "user_password" => encryption.user_password = toml_unquote(value),
+            "allow_print" => encryption.allow_print = value == "true",
+            "allow_modify" => encryption.allow_modify = value == "true",
+            "allow_copy" => encryption.allow_copy = value == "true",
+            "allow_annotate" => encryption.allow_annotate = value == "true",
+            "allow_fill_forms" => encryption.allow_fill_forms = value == "true",
+            _ => {}
+        }
+    }
+    encryption
+}
+
+// Vertreternummer-/Textfeld aus einer Inline-Tabelle lesen
+fn parse_vertreter_toml_line(line: &str) -> VertreterConfig {
+    let mut x = 0.0;
+    let mut y = 0.0;
+    let mut size = 12.0; // Default size für Vertreter
+    let mut font_name = "Arial".to_string();
+    let mut font_size = 12.0;
+    let mut font_style = "Normal".to_string();
+    let mut all_pages = false;
+    let mut pages_vec: Vec<u32> = Vec::new();
+    let mut align = TextAlign::Left;
+    let mut anchor = TextAnchor::Baseline;
+    let mut rotation = 0.0;
+    let mut color = [0, 0, 0];
+    let mut char_spacing = 0.0;
+    let mut word_spacing = 0.0;
+    let mut horizontal_scaling = default_horizontal_scaling();
+    let mut format = default_vertreter_format();
+    let mut form_field = String::new();
+    
+    for part in split_inline_table(line) {
+        let part = part.trim();
+        if let Some(value) = part.strip_prefix("x =") {
+            x = value.trim().parse().unwrap_or(0.0);
+        } else if let Some(value) = part.strip_prefix("y =") {
+            y = value.trim().parse().unwrap_or(0.0);
+        } else if let Some(value) = part.strip_prefix("size =") {
+            size = value.trim().parse().unwrap_or(12.0);
+        } else if let Some(value) = part.strip_prefix("font_name =") {
+            font_name = toml_unquote(value);
+        } else if let Some(value) = part.strip_prefix("font_size =") {
+            font_size = value.trim().parse().unwrap_or(12.0);
+        } else if let Some(value) = part.strip_prefix("font_style =") {
+            font_style = toml_unquote(value);
+        } else if let Some(value) = part.strip_prefix("align =") {
+            align = TextAlign::from_key(value.trim().trim_matches('"')).unwrap_or_default();
+        } else if let Some(value) = part.strip_prefix("anchor =") {
+            anchor = TextAnchor::from_key(value.trim().trim_matches('"')).unwrap_or_default();
+        } else if let Some(value) = part.strip_prefix("rotation =") {
+            rotation = value.trim().parse().unwrap_or(0.0);
+        } else if let Some(value) = part.strip_prefix("color =") {
+            color = parse_hex_color(value.trim().trim_matches('"')).unwrap_or([0, 0, 0]);
+        } else if let Some(value) = part.strip_prefix("char_spacing =") {
+            char_spacing = value.trim().parse().unwrap_or(0.0);
+        } else if let Some(value) = part.strip_prefix("word_spacing =") {
+            word_spacing = value.trim().parse().unwrap_or(0.0);
+        } else if let Some(value) = part.strip_prefix("horizontal_scaling =") {
+            horizontal_scaling = value.trim().parse().unwrap_or(default_horizontal_scaling());
+        } else if let Some(value) = part.strip_prefix("format =") {
+            format = toml_unquote(value);
+        } else if let Some(value) = part.strip_prefix("form_field =") {
+            form_field = toml_unquote(value);
+        } else if let Some(value) = part.strip_prefix("all_pages =") {
+            all_pages = value.trim() == "true";
+        } else if let Some(value) = part.strip_prefix("pages =") {
+            let mut rhs = value.trim().trim_matches(',').trim().to_string();
+            if rhs.starts_with('"') && rhs.ends_with('"') { rhs = rhs.trim_matches('"').to_string(); }
+            if rhs.eq_ignore_ascii_case("ALL") {
+                all_pages = true;
+            } else if rhs.starts_with('[') && rhs.ends_with(']') {
+                let inner = rhs.trim_start_matches('[').trim_end_matches(']');
+                for tok in inner.split(',') {
+                    if let Ok(n) = tok.trim().parse::<u32>() { pages_vec.push(n); }
+                }
+            } else {
+                for tok in rhs.split([';', ',']) {
+                    let t = tok.trim();
+                    if t.is_empty() { continue; }
+                    if let Ok(n) = t.parse::<u32>() { pages_vec.push(n); }
+                }
             }
-                if l.contains("x =") && l.contains("y =") {
+        }
+    }
+    
+    // Fallback: font_size auf size setzen wenn nicht explizit gesetzt
+    if font_size == 12.0 && size != 12.0 {
+        font_size = size;
+    }
+    
+    if pages_vec.is_empty() { pages_vec.push(1); }
+    VertreterConfig { x, y, size, pages: pages_vec, all_pages, font_name, font_size, font_style, align, anchor, rotation,
+        color, char_spacing, word_spacing, horizontal_scaling, format, form_field }
+}
+
+// Rechteck aus einer Inline-Tabelle lesen (Farben als "#rrggbb", "none" = keine)
+fn parse_rect_toml_line(line: &str) -> RectConfig {
+    let mut rect = RectConfig { pages: Vec::new(), ..Default::default() };
+    for part in split_inline_table(line) {
+        let part = part.trim();
+        if let Some(value) = part.strip_prefix("x =") {
+            rect.x = value.trim().parse().unwrap_or(50.0);
+        } else if let Some(value) = part.strip_prefix("y =") {
+            rect.y = value.trim().parse().unwrap_or(50.0);
+        } else if let Some(value) = part.strip_prefix("width =") {
+            rect.width = value.trim().parse().unwrap_or(120.0);
+        } else if let Some(value) = part.strip_prefix("height =") {
+            rect.height = value.trim().parse().unwrap_or(40.0);
+        } else if let Some(value) = part.strip_prefix("fill =") {
+            rect.fill = parse_hex_color(value.trim().trim_matches('"'));
+        } else if let Some(value) = part.strip_prefix("stroke =") {
+            rect.stroke = parse_hex_color(value.trim().trim_matches('"'));
+        } else if let Some(value) = part.strip_prefix("stroke_width =") {
+            rect.stroke_width = value.trim().parse().unwrap_or(default_stroke_width());
+        } else if let Some(value) = part.strip_prefix("all_pages =") {
+            rect.all_pages = value.trim() == "true";
+        } else if let Some(value) = part.strip_prefix("pages =") {
+            parse_toml_pages_value(value, &mut rect.all_pages, &mut rect.pages);
+        }
+    }
+    if rect.pages.is_empty() { rect.pages.push(1); }
+    rect
+}
+
+// Linie aus einer Inline-Tabelle lesen
+fn parse_line_toml_line(line: &str) -> LineConfig {
+    let mut element = LineConfig { pages: Vec::new(), ..Default::default() };
+    for part in split_inline_table(line) {
+        let part = part.trim();
+        if let Some(value) = part.strip_prefix("x =") {
+            element.x = value.trim().parse().unwrap_or(50.0);
+        } else if let Some(value) = part.strip_prefix("y =") {
+            element.y = value.trim().parse().unwrap_or(50.0);
+        } else if let Some(value) = part.strip_prefix("x2 =") {
+            element.x2 = value.trim().parse().unwrap_or(200.0);
+        } else if let Some(value) = part.strip_prefix("y2 =") {
+            element.y2 = value.trim().parse().unwrap_or(50.0);
+        } else if let Some(value) = part.strip_prefix("color =") {
+            element.color = parse_hex_color(value.trim().trim_matches('"')).unwrap_or([0, 0, 0]);
+        } else if let Some(value) = part.strip_prefix("stroke_width =") {
+            element.stroke_width = value.trim().parse().unwrap_or(default_stroke_width());
+        } else if let Some(value) = part.strip_prefix("all_pages =") {
+            element.all_pages = value.trim() == "true";
+        } else if let Some(value) = part.strip_prefix("pages =") {
+            parse_toml_pages_value(value, &mut element.all_pages, &mut element.pages);
+        }
+    }
+    if element.pages.is_empty() { element.pages.push(1); }
+    element
+}
+
+// Element der Elementliste lesen: gemeinsame Felder (`type`, `name`, `visible`, `z_order`)
+// hier, die übrigen über den Parser der jeweiligen Art. Unbekannte Arten werden übersprungen.
+fn parse_element_toml_line(line: &str) -> Option<Element> {
+    let mut kind_key = String::new();
+    let mut name = String::new();
+    let mut visible = true;
+    let mut z_order = 0;
+    for part in split_inline_table(line) {
+        let part = part.trim();
+        if let Some(value) = part.strip_prefix("type =") {
+            kind_key = value.trim().trim_matches('"').to_lowercase();
+        } else if let Some(value) = part.strip_prefix("name =") {
+            name = toml_unquote(value);
+        } else if let Some(value) = part.strip_prefix("visible =") {
+            visible = value.trim() != "false";
+        } else if let Some(value) = part.strip_prefix("z_order =") {
+            z_order = value.trim().parse().unwrap_or(0);
+        }
+    }
+    let kind = match kind_key.as_str() {
+        "qr" => ElementKind::Qr(parse_qr_toml_line(line)),
+        "text" => ElementKind::Text(parse_vertreter_toml_line(line)),
+        "image" => ElementKind::Image(parse_image_toml_line(line)),
+        "barcode" => ElementKind::Barcode(parse_barcode_toml_line(line)),
+        "rect" => ElementKind::Rect(parse_rect_toml_line(line)),
+        "line" => ElementKind::Line(parse_line_toml_line(line)),
+        other => {
+            println!("WARNUNG: Unbekannte Elementart '{}' in der Config übersprungen", other);
+            return None;
+        }
+    };
+    Some(Element { name, visible, z_order, kind })
+}
+
+// Hilfsfunktion: Parsen eines TOML-Strings in ein Config-Objekt
+fn parse_toml_to_config(toml: &str) -> Config {
+    // Startwerte - werden überschrieben wenn in der Datei gefunden
+    let mut qr_codes = Vec::new();
+    let mut vertreter = Vec::new();
+    let mut pages = Vec::new();
+    let mut link_template = String::new();
+    let mut force_link_template = false;
+    let mut flatten_form = false;
+    let mut output_pages = String::new();
+    let mut metadata = MetadataConfig::default();
+    let mut encryption = EncryptionConfig::default();
+
+    let mut barcodes = Vec::new();
+    let mut images = Vec::new();
+    let mut elements = Vec::new();
+
+    let mut in_element_array = false;
+    let mut saw_elements_array = false;
+    let mut saw_legacy_lists = false;
+    let mut in_qr_array = false;
+    let mut in_barcode_array = false;
+    let mut in_image_array = false;
+    let mut in_vertreter_array = false;
+    let mut _in_positions_section = false;
+    let mut _in_pages_section = false;
+
+    for l in toml.lines() {
+        let l = l.trim();
+
+        // Section-Header erkennen
+        if l == "[positions]" {
+            _in_positions_section = true;
+            _in_pages_section = false;
+            continue;
+        } else if l == "[pages]" {
+            _in_positions_section = false;
+            _in_pages_section = true;
+            continue;
+        } else if l.starts_with('[') {
+            // Andere Section
+            _in_positions_section = false;
+            _in_pages_section = false;
+            continue;
+        }
+
+        // Link-Vorlage (Top-Level-Schlüssel)
+        if let Some(value) = l.strip_prefix("link_template =") {
+            link_template = toml_unquote(value);
+            continue;
+        } else if let Some(value) = l.strip_prefix("force_link_template =") {
+            force_link_template = value.trim() == "true";
+            continue;
+        } else if let Some(value) = l.strip_prefix("flatten_form =") {
+            flatten_form = value.trim() == "true";
+            continue;
+        } else if let Some(value) = l.strip_prefix("output_pages =") {
+            output_pages = toml_unquote(value);
+            continue;
+        } else if let Some(value) = l.strip_prefix("metadata =") {
+            metadata = parse_metadata_toml_line(value);
+            continue;
+        } else if let Some(value) = l.strip_prefix("encryption =") {
+            encryption = parse_encryption_toml_line(value);
+            continue;
+        }
+
+        // Einzelner QR-Code (Rückwärtskompatibilität)
+        if l.starts_with("qr_code =") && l.contains('{') {
+            if let Some(start) = l.find('{') {
+                let inner = &l[start+1..l.find('}').unwrap_or(l.len())];
                 let mut x = 50.0;
                 let mut y = 50.0;
                 let mut size = 18.0;
                 let mut all_pages = false;
-                let mut pages_vec: Vec<u32> = Vec::new();
-                for part in l.trim_matches(|c| c == '{' || c == '}' || c == ',').split(',') {
+                for part in inner.split(',') {
                     let part = part.trim();
-                    if part.starts_with("x =") {
-                        x = part[3..].trim().parse().unwrap_or(50.0);
-                    } else if part.starts_with("y =") {
-                        y = part[3..].trim().parse().unwrap_or(50.0);
-                    } else if part.starts_with("size =") {
-                        size = part[6..].trim().parse().unwrap_or(18.0);
-                    } else if part.starts_with("all_pages =") {
-                        all_pages = part[11..].trim() == "true";
-                    } else if part.starts_with("pages =") {
-                        // pages can be written as a quoted semicolon-separated string or as an array; try to parse both
-                        let mut rhs = part[7..].trim().trim_matches(',').trim().to_string();
-                        // remove surrounding quotes if present
-                        if rhs.starts_with('"') && rhs.ends_with('"') {
-                            rhs = rhs.trim_matches('"').to_string();
-                        }
-                        if rhs.eq_ignore_ascii_case("ALL") {
-                            all_pages = true;
-                        } else if rhs.starts_with('[') && rhs.ends_with(']') {
-                            let inner = rhs.trim_start_matches('[').trim_end_matches(']');
-                            for tok in inner.split(',') {
-                                if let Ok(n) = tok.trim().parse::<u32>() { pages_vec.push(n); }
-                            }
-                        } else {
-                            // semicolon or comma separated values
-                            for tok in rhs.split(|c| c == ';' || c == ',') {
-                                let t = tok.trim();
-                                if t.is_empty() { continue; }
-                                if let Ok(n) = t.parse::<u32>() { pages_vec.push(n); }
-                            }
-                        }
+                    if let Some(value) = part.strip_prefix("x =") {
+                        x = value.trim().parse().unwrap_or(50.0);
+                    } else if let Some(value) = part.strip_prefix("y =") {
+                        y = value.trim().parse().unwrap_or(50.0);
+                    } else if let Some(value) = part.strip_prefix("size =") {
+                        size = value.trim().parse().unwrap_or(18.0);
+                    } else if let Some(value) = part.strip_prefix("all_pages =") {
+                        all_pages = value.trim() == "true";
                     }
                 }
-                if pages_vec.is_empty() { pages_vec.push(1); }
-                qr_codes.push(QrCodeConfig { x, y, size, pages: pages_vec, all_pages });
+                qr_codes.push(QrCodeConfig { x, y, size, pages: vec![1], all_pages, ..Default::default() });
+            }
+        }
+
+        // Elementliste (ein Element je Zeile mit `type = "..."`)
+        else if l.starts_with("elements = [") {
+            in_element_array = true;
+            saw_elements_array = true;
+            continue;
+        } else if in_element_array {
+            if l.starts_with(']') {
+                in_element_array = false;
+                continue;
+            }
+            if l.starts_with('{') {
+                elements.extend(parse_element_toml_line(l));
+            }
+        }
+
+        // QR-Code Array (funktioniert sowohl mit als auch ohne [positions] Section)
+        else if l.starts_with("qr_codes = [") {
+            in_qr_array = true;
+            saw_legacy_lists = true;
+            continue;
+        } else if in_qr_array {
+            if l.starts_with(']') {
+                in_qr_array = false;
+                continue;
+            }
+            if l.contains("x =") && l.contains("y =") {
+                qr_codes.push(parse_qr_toml_line(l));
+            }
+        }
+
+        // Strichcodes
+        else if l.starts_with("barcodes = [") {
+            in_barcode_array = true;
+            saw_legacy_lists = true;
+            continue;
+        } else if in_barcode_array {
+            if l.starts_with(']') {
+                in_barcode_array = false;
+                continue;
+            }
+            if l.contains("x =") && l.contains("y =") {
+                barcodes.push(parse_barcode_toml_line(l));
+            }
+        }
+
+        // Bilder
+        else if l.starts_with("images = [") {
+            in_image_array = true;
+            saw_legacy_lists = true;
+            continue;
+        } else if in_image_array {
+            if l.starts_with(']') {
+                in_image_array = false;
+                continue;
+            }
+            if l.contains("x =") && l.contains("y =") {
+                images.push(parse_image_toml_line(l));
             }
         }
 
         // Vertreter-Positionen
         else if l.starts_with("vertreter_nummer = [") {
             in_vertreter_array = true;
+            saw_legacy_lists = true;
             continue;
         } else if in_vertreter_array {
             if l.starts_with(']') {
                 in_vertreter_array = false;
                 continue;
             }
-                if l.contains("x =") && l.contains("y =") {
-                let mut x = 0.0;
-                let mut y = 0.0;
-                let mut size = 12.0; // Default size für Vertreter
-                let mut font_name = "Arial".to_string();
-                let mut font_size = 12.0;
-                let mut font_style = "Normal".to_string();
-                let mut all_pages = false;
-                let mut pages_vec: Vec<u32> = Vec::new();
-                
-                for part in l.trim_matches(|c| c == '{' || c == '}' || c == ',').split(',') {
-                    let part = part.trim();
-                    if part.starts_with("x =") {
-                        x = part[3..].trim().parse().unwrap_or(0.0);
-                    } else if part.starts_with("y =") {
-                        y = part[3..].trim().parse().unwrap_or(0.0);
-                    } else if part.starts_with("size =") {
-                        size = part[6..].trim().parse().unwrap_or(12.0);
-                    } else if part.starts_with("font_name =") {
-                        font_name = part[11..].trim().trim_matches('"').to_string();
-                    } else if part.starts_with("font_size =") {
-                        font_size = part[11..].trim().parse().unwrap_or(12.0);
-                    } else if part.starts_with("font_style =") {
-                        font_style = part[12..].trim().trim_matches('"').to_string();
-                    } else if part.starts_with("all_pages =") {
-                        all_pages = part[11..].trim() == "true";
-                    } else if part.starts_with("pages =") {
-                        let mut rhs = part[7..].trim().trim_matches(',').trim().to_string();
-                        if rhs.starts_with('"') && rhs.ends_with('"') { rhs = rhs.trim_matches('"').to_string(); }
-                        if rhs.eq_ignore_ascii_case("ALL") {
-                            all_pages = true;
-                        } else if rhs.starts_with('[') && rhs.ends_with(']') {
-                            let inner = rhs.trim_start_matches('[').trim_end_matches(']');
-                            for tok in inner.split(',') {
-                                if let Ok(n) = tok.trim().parse::<u32>() { pages_vec.push(n); }
-                            }
-                        } else {
-                            for tok in rhs.split(|c| c == ';' || c == ',') {
-                                let t = tok.trim();
-                                if t.is_empty() { continue; }
-                                if let Ok(n) = t.parse::<u32>() { pages_vec.push(n); }
-                            }
-                        }
-                    }
-                }
-                
-                // Fallback: font_size auf size setzen wenn nicht explizit gesetzt
-                if font_size == 12.0 && size != 12.0 {
-                    font_size = size;
-                }
-                
-                if pages_vec.is_empty() { pages_vec.push(1); }
-                vertreter.push(VertreterConfig { x, y, size, pages: pages_vec, all_pages, font_name, font_size, font_style });
+            if l.contains("x =") && l.contains("y =") {
+                vertreter.push(parse_vertreter_toml_line(l));
             }
         }
 
@@ -857,27 +2195,35 @@ fn parse_toml_to_config(toml: &str) -> Config {
         }
     }
 
-    // Defaults setzen wenn nichts gefunden wurde
-    if qr_codes.is_empty() {
-        qr_codes.push(QrCodeConfig { x: 50.0, y: 50.0, size: 18.0, pages: vec![1], all_pages: false });
-    }
-    if vertreter.is_empty() {
-        vertreter = vec![
-            VertreterConfig { x: 77.0, y: 80.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string() },
-            VertreterConfig { x: 100.0, y: 650.0, size: 10.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 10.0, font_style: "Normal".to_string() },
-        ];
+    // Defaults setzen wenn nichts gefunden wurde (nur für Dateien ganz ohne Elementliste und ohne
+    // die früheren Listen; eine bewusst geleerte Elementliste bleibt leer)
+    if !saw_elements_array && !saw_legacy_lists {
+        if qr_codes.is_empty() {
+            qr_codes.push(QrCodeConfig { x: 50.0, y: 50.0, size: 18.0, pages: vec![1], all_pages: false, ..Default::default() });
+        }
+        if vertreter.is_empty() {
+            vertreter = vec![
+                VertreterConfig { x: 77.0, y: 80.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string(), ..Default::default() },
+                VertreterConfig { x: 100.0, y: 650.0, size: 10.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 10.0, font_style: "Normal".to_string(), ..Default::default() },
+            ];
+        }
     }
     if pages.is_empty() {
         pages.push(1);
     }
+    elements.extend(elements_from_lists(images, qr_codes, barcodes, vertreter));
 
     let final_config = Config {
-        qr_codes,
-        vertreter,
+        elements,
+        link_template,
+        force_link_template,
+        flatten_form,
+        output_pages,
+        metadata,
+        encryption,
     };
 
-    println!("Geladene Config via parse_toml_to_config: QR={:?}, Vertreter={:?}", 
-             final_config.qr_codes, final_config.vertreter);
+    println!("Geladene Config via parse_toml_to_config: Elemente={:?}", final_config.elements);
 
     final_config
 }
@@ -903,28 +2249,22 @@ fn save_group_config(group: &str, language: &str, is_messe: bool, config: &Confi
         toml.push_str("# Messe-spezifische Konfiguration\n");
     }
     toml.push_str("# Koordinaten sind in PDF-Punkten (1 Punkt = 1/72 Zoll ≈ 0.35mm)\n\n");
+    toml.push_str(&link_template_toml_lines(config));
+    toml.push_str(&form_toml_lines(config));
+    toml.push_str(&output_pages_toml_lines(config));
+    toml.push_str(&metadata_toml_lines(config));
+    toml.push_str(&encryption_toml_lines(config));
     
-    // QR-Codes
-    toml.push_str("qr_codes = [\n");
-    for qr in &config.qr_codes {
-        toml.push_str(&format!("  {{ x = {}, y = {}, size = {}, all_pages = {} }},\n", qr.x, qr.y, qr.size, qr.all_pages));
-    }
-    toml.push_str("]\n\n");
-    
-    toml.push_str("[positions]\n");
-    toml.push_str("vertreter_nummer = [\n");
-    for v in &config.vertreter {
-        toml.push_str(&format!("  {{ x = {}, y = {}, size = {}, all_pages = {}, font_name = \"{}\", font_size = {}, font_style = \"{}\" }},\n", v.x, v.y, v.size, v.all_pages, v.font_name, v.font_size, v.font_style));
-    }
-    toml.push_str("]\n\n");
+    // Overlay-Elemente
+    toml.push_str(&elements_toml_lines(config));
     
     // If we have a previously loaded config path, prefer saving back to it
     if let Some(p) = get_current_config_path() {
-        if let Err(e) = save_group_config_to_path(&p, &config) {
+        if let Err(e) = save_group_config_to_path(&p, config) {
             eprintln!("Konnte gruppenspezifische Config nicht in geladenem Pfad {:?} speichern: {}", p, e);
         } else {
             println!("✅ Gruppenspezifische Config gespeichert (geladene Datei): {:?}", p);
-            println!("Config-Werte: QR={:?}, Vertreter={:?}", config.qr_codes, config.vertreter);
+            println!("Config-Werte: Elemente={:?}", config.elements);
             return;
         }
     }
@@ -934,9 +2274,194 @@ fn save_group_config(group: &str, language: &str, is_messe: bool, config: &Confi
         eprintln!("Konnte gruppenspezifische Config nicht speichern: {}", e);
     } else {
         println!("✅ Gruppenspezifische Config gespeichert: {:?}", group_filename);
-        println!("Config-Werte: QR={:?}, Vertreter={:?}", 
-                 config.qr_codes, config.vertreter);
+        println!("Config-Werte: Elemente={:?}", 
+                 config.elements);
+    }
+}
+
+// Optionale QR-Felder für die TOML-Zeile (nur geschrieben wenn vom Default abweichend,
+// damit bestehende Config-Dateien unverändert lesbar bleiben)
+fn qr_optional_toml_fields(qr: &QrCodeConfig) -> String {
+    let mut fields = String::new();
+    if !qr.logo_path.is_empty() {
+        fields.push_str(&format!(", logo_path = {}, logo_size = {}", toml_quote(&qr.logo_path), qr.logo_size));
+    }
+    if qr.rotation != 0.0 {
+        fields.push_str(&format!(", rotation = {}", qr.rotation));
+    }
+    if !qr.form_field.is_empty() {
+        fields.push_str(&format!(", form_field = {}", toml_quote(&qr.form_field)));
+    }
+    if !qr.caption.is_empty() {
+        fields.push_str(&format!(", caption = {}, caption_font = {}, caption_size = {}, caption_offset = {}", toml_quote(&qr.caption), toml_quote(&qr.caption_font), qr.caption_size, qr.caption_offset));
+    }
+    if qr.payload == QrPayloadType::Link && qr.link_source != QrLinkSource::Auto {
+        fields.push_str(&format!(", link_source = \"{}\"", qr.link_source.key()));
+        match qr.link_source {
+            QrLinkSource::Column => fields.push_str(&format!(", link_column = {}", toml_quote(&qr.link_column))),
+            QrLinkSource::Template if !qr.link_template.is_empty() => fields.push_str(&format!(", link_template = {}", toml_quote(&qr.link_template))),
+            _ => {}
+        }
+    }
+    if qr.payload != QrPayloadType::Link {
+        fields.push_str(&format!(", payload = \"{}\"", qr.payload.key()));
+        for (key, value) in &qr.payload_fields {
+            fields.push_str(&format!(", payload_{} = {}", key, toml_quote(value)));
+        }
+    }
+    fields
+}
+
+// Optionale Vertreter-Felder für die TOML-Zeile (nur wenn vom Default abweichend)
+fn vertreter_optional_toml_fields(v: &VertreterConfig) -> String {
+    let mut fields = String::new();
+    if v.align != TextAlign::Left {
+        fields.push_str(&format!(", align = \"{}\"", v.align.key()));
+    }
+    if v.anchor != TextAnchor::Baseline {
+        fields.push_str(&format!(", anchor = \"{}\"", v.anchor.key()));
+    }
+    if v.rotation != 0.0 {
+        fields.push_str(&format!(", rotation = {}", v.rotation));
+    }
+    if v.color != [0, 0, 0] {
+        fields.push_str(&format!(", color = \"{}\"", hex_color(v.color)));
     }
+    if v.char_spacing != 0.0 {
+        fields.push_str(&format!(", char_spacing = {}", v.char_spacing));
+    }
+    if v.word_spacing != 0.0 {
+        fields.push_str(&format!(", word_spacing = {}", v.word_spacing));
+    }
+    if v.horizontal_scaling != default_horizontal_scaling() {
+        fields.push_str(&format!(", horizontal_scaling = {}", v.horizontal_scaling));
+    }
+    if v.format != default_vertreter_format() {
+        fields.push_str(&format!(", format = {}", toml_quote(&v.format)));
+    }
+    if !v.form_field.is_empty() {
+        fields.push_str(&format!(", form_field = {}", toml_quote(&v.form_field)));
+    }
+    fields
+}
+
+// Farbe als "#rrggbb" für die TOML-Datei
+fn hex_color(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+// "#rrggbb" (mit oder ohne #) einlesen
+fn parse_hex_color(value: &str) -> Option<[u8; 3]> {
+    let hex = value.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+// Seitenangabe für die TOML-Zeile: "ALL" oder "1;2;3"
+fn toml_pages_value(all_pages: bool, pages: &[u32]) -> String {
+    if all_pages {
+        "ALL".to_string()
+    } else {
+        pages.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(";")
+    }
+}
+
+// Farbe oder "none" für optionale Farben (Rechteck)
+fn optional_hex_color(color: Option<[u8; 3]>) -> String {
+    color.map(hex_color).unwrap_or_else(|| "none".to_string())
+}
+
+// Elementliste, ein Element je Zeile (steht vor der ersten [Section]). Gemeinsame Felder werden
+// nur geschrieben, wenn sie vom Default abweichen.
+fn elements_toml_lines(config: &Config) -> String {
+    let mut toml = String::from("elements = [\n");
+    for element in &config.elements {
+        let mut line = format!("  {{ type = \"{}\"", element.kind.key());
+        if !element.name.is_empty() {
+            line.push_str(&format!(", name = {}", toml_quote(&element.name)));
+        }
+        if !element.visible {
+            line.push_str(", visible = false");
+        }
+        if element.z_order != 0 {
+            line.push_str(&format!(", z_order = {}", element.z_order));
+        }
+        line.push_str(&element_kind_toml_fields(&element.kind));
+        line.push_str(" },\n");
+        toml.push_str(&line);
+    }
+    toml.push_str("]\n\n");
+    toml
+}
+
+// Felder der jeweiligen Elementart für die TOML-Zeile
+fn element_kind_toml_fields(kind: &ElementKind) -> String {
+    match kind {
+        ElementKind::Qr(qr) => format!(", x = {}, y = {}, size = {}, all_pages = {}, pages = \"{}\"{}",
+            qr.x, qr.y, qr.size, qr.all_pages, toml_pages_value(qr.all_pages, &qr.pages), qr_optional_toml_fields(qr)),
+        ElementKind::Text(v) => format!(", x = {}, y = {}, size = {}, all_pages = {}, pages = \"{}\", font_name = {}, font_size = {}, font_style = {}{}",
+            v.x, v.y, v.size, v.all_pages, toml_pages_value(v.all_pages, &v.pages), toml_quote(&v.font_name), v.font_size, toml_quote(&v.font_style), vertreter_optional_toml_fields(v)),
+        ElementKind::Image(img) => format!(", x = {}, y = {}, width = {}, height = {}, path = {}, fit = \"{}\", all_pages = {}, pages = \"{}\"",
+            img.x, img.y, img.width, img.height, toml_quote(&img.path), img.fit.key(), img.all_pages, toml_pages_value(img.all_pages, &img.pages)),
+        ElementKind::Barcode(b) => format!(", x = {}, y = {}, size = {}, bar_height = {}, symbology = \"{}\", content = {}, show_text = {}, text_size = {}, all_pages = {}, pages = \"{}\"",
+            b.x, b.y, b.size, b.bar_height, b.symbology.key(), toml_quote(&b.content), b.show_text, b.text_size, b.all_pages, toml_pages_value(b.all_pages, &b.pages)),
+        ElementKind::Rect(r) => format!(", x = {}, y = {}, width = {}, height = {}, fill = \"{}\", stroke = \"{}\", stroke_width = {}, all_pages = {}, pages = \"{}\"",
+            r.x, r.y, r.width, r.height, optional_hex_color(r.fill), optional_hex_color(r.stroke), r.stroke_width, r.all_pages, toml_pages_value(r.all_pages, &r.pages)),
+        ElementKind::Line(l) => format!(", x = {}, y = {}, x2 = {}, y2 = {}, color = \"{}\", stroke_width = {}, all_pages = {}, pages = \"{}\"",
+            l.x, l.y, l.x2, l.y2, hex_color(l.color), l.stroke_width, l.all_pages, toml_pages_value(l.all_pages, &l.pages)),
+    }
+}
+
+// Top-Level-Zeilen für die Link-Vorlage (müssen vor der ersten [Section] stehen)
+fn link_template_toml_lines(config: &Config) -> String {
+    if config.link_template.is_empty() {
+        return String::new();
+    }
+    format!("link_template = {}\nforce_link_template = {}\n\n", toml_quote(&config.link_template), config.force_link_template)
+}
+
+// Top-Level-Zeile für das Formular-Flattening (nur wenn aktiviert)
+fn form_toml_lines(config: &Config) -> String {
+    if config.flatten_form { "flatten_form = true\n\n".to_string() } else { String::new() }
+}
+
+// Top-Level-Zeile für die Seitenauswahl der Ausgabe (nur wenn gesetzt)
+fn output_pages_toml_lines(config: &Config) -> String {
+    if config.output_pages.trim().is_empty() {
+        return String::new();
+    }
+    format!("output_pages = {}\n\n", toml_quote(config.output_pages.trim()))
+}
+
+// Top-Level-Zeile für die Dokument-Metadaten (nur wenn vom Default abweichend)
+fn metadata_toml_lines(config: &Config) -> String {
+    let m = &config.metadata;
+    if *m == MetadataConfig::default() {
+        return String::new();
+    }
+    let mut line = format!("metadata = {{ title = {}, subject = {}, author = {}, keywords = {}",
+        toml_quote(&m.title), toml_quote(&m.subject), toml_quote(&m.author), toml_quote(&m.keywords));
+    for (key, value) in &m.custom {
+        // Schlüssel mit Leerzeichen oder Sonderzeichen werden als TOML-String geschrieben
+        let name = format!("custom_{}", key);
+        let name = if key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') { name } else { toml_quote(&name) };
+        line.push_str(&format!(", {} = {}", name, toml_quote(value)));
+    }
+    line.push_str(" }\n\n");
+    line
+}
+
+// Top-Level-Zeile für die Verschlüsselung (nur wenn aktiviert; Passwörter stehen im Klartext)
+fn encryption_toml_lines(config: &Config) -> String {
+    let e = &config.encryption;
+    if !e.enabled {
+        return String::new();
+    }
+    format!("encryption = {{ enabled = true, owner_password = {}, user_password = {}, allow_print = {}, allow_modify = {}, allow_copy = {}, allow_annotate = {}, allow_fill_forms = {} }}\n\n",
+        toml_quote(&e.owner_password), toml_quote(&e.user_password), e.allow_print, e.allow_modify, e.allow_copy, e.allow_annotate, e.allow_fill_forms)
 }
 
 // Helper to explicitly save to a path (used by UI when user chooses "In Datei speichern")
@@ -947,34 +2472,18 @@ fn save_group_config_to_path(path: &std::path::Path, config: &Config) -> Result<
         // Use serde_yaml to serialize the Config struct to YAML
         match serde_yaml::to_string(config) {
             Ok(yaml) => std::fs::write(path, yaml),
-            Err(e) => Err(std::io::Error::new(std::io::ErrorKind::Other, format!("YAML-Serailize Fehler: {}", e)))
+            Err(e) => Err(std::io::Error::other(format!("YAML-Serailize Fehler: {}", e)))
         }
     } else {
         // Default: write TOML-compatible hand-crafted representation for backwards compatibility
         let mut toml = String::new();
         toml.push_str("# Manuell gespeicherte Config\n");
-        toml.push_str("qr_codes = [\n");
-        for qr in &config.qr_codes {
-            // pages: if all_pages -> write "ALL", else write semicolon-separated string like "1;2;3"
-            let pages_str = if qr.all_pages {
-                "ALL".to_string()
-            } else {
-                qr.pages.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(";")
-            };
-            toml.push_str(&format!("  {{ x = {}, y = {}, size = {}, all_pages = {}, pages = \"{}\" }},\n", qr.x, qr.y, qr.size, qr.all_pages, pages_str));
-        }
-        toml.push_str("]\n\n");
-        toml.push_str("[positions]\n");
-        toml.push_str("vertreter_nummer = [\n");
-        for v in &config.vertreter {
-            let pages_str = if v.all_pages {
-                "ALL".to_string()
-            } else {
-                v.pages.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(";")
-            };
-            toml.push_str(&format!("  {{ x = {}, y = {}, size = {}, all_pages = {}, pages = \"{}\", font_name = \"{}\", font_size = {}, font_style = \"{}\" }},\n", v.x, v.y, v.size, v.all_pages, pages_str, v.font_name, v.font_size, v.font_style));
-        }
-        toml.push_str("]\n");
+        toml.push_str(&link_template_toml_lines(config));
+        toml.push_str(&form_toml_lines(config));
+        toml.push_str(&output_pages_toml_lines(config));
+        toml.push_str(&metadata_toml_lines(config));
+        toml.push_str(&encryption_toml_lines(config));
+        toml.push_str(&elements_toml_lines(config));
         std::fs::write(path, toml)
     }
 }
@@ -1107,6 +2616,9 @@ pub struct MyApp {
     resume_info: Option<(usize, usize, u64)>, // (current_index, total_count, elapsed_seconds)
     // Selected element in the preview: kind ("qr"/"vertreter") and index
     selected_element: Option<(String, usize)>,
+    // Angezeigte Seitengröße der Vorlage in Punkten (Standard A4) und Datei, aus der sie stammt
+    preview_page_size: (f32, f32),
+    preview_page_source: Option<String>,
     // UI helpers for saving config
     config_save_path: String,
     show_save_as: bool,
@@ -1117,6 +2629,10 @@ pub struct MyApp {
     // Persistent README viewer (so the README can be opened without the help window closing)
     show_readme_window: bool,
     readme_text: Option<String>,
+    // Laufweite Einstellungen (z.B. Kampagne für Link-Vorlagen)
+    run_options: RunOptions,
+    // Ergebnisse des letzten Generierungslaufs (wird vom Generator-Thread befüllt)
+    run_report: Arc<Mutex<RunReport>>,
     // Full PDF preview state (removed - kept preview lightweight)
 }
 
@@ -1176,14 +2692,14 @@ impl Default for MyApp {
         let manual_vertreter_size = "12.0".to_string();
 
         // Update manual fields with loaded config values
-        if !initial_config.qr_codes.is_empty() {
-            manual_qr_x = format!("{:.1}", initial_config.qr_codes[0].x);
-            manual_qr_y = format!("{:.1}", initial_config.qr_codes[0].y);
-            manual_qr_size = format!("{:.1}", initial_config.qr_codes[0].size);
+        if let Some(first_qr) = initial_config.qr_codes().next() {
+            manual_qr_x = format!("{:.1}", first_qr.x);
+            manual_qr_y = format!("{:.1}", first_qr.y);
+            manual_qr_size = format!("{:.1}", first_qr.size);
         }
-        if !initial_config.vertreter.is_empty() {
-            manual_vertreter_x = format!("{:.1}", initial_config.vertreter[0].x);
-            manual_vertreter_y = format!("{:.1}", initial_config.vertreter[0].y);
+        if let Some(first_v) = initial_config.vertreter().next() {
+            manual_vertreter_x = format!("{:.1}", first_v.x);
+            manual_vertreter_y = format!("{:.1}", first_v.y);
         }
 
         println!("🚀 APP-START: Lade gruppenspezifische Config für {} {} (Messe: {})", default_group, default_language, default_is_messe);
@@ -1242,12 +2758,16 @@ impl Default for MyApp {
             // Resume-Information (gruppenspezifisch beim Start geladen)
             resume_info: initial_resume_info,
             selected_element: None,
+            preview_page_size: (595.0, 842.0),
+            preview_page_source: None,
             config_save_path: String::new(),
             show_save_as: false,
             show_elements_window: false,
             show_help: false,
             show_readme_window: false,
             readme_text: None,
+            run_options: RunOptions::default(),
+            run_report: Arc::new(Mutex::new(RunReport::default())),
             // preview state removed
         }
     }
@@ -1323,6 +2843,7 @@ fn clear_progress_files(group: &str, language: &str, is_messe: bool) {
 }
 
 // Global selection for generation: data CSV, template path, and whether to generate QR
+#[allow(clippy::type_complexity)]
 static CURRENT_SELECTION: Lazy<Mutex<Option<Vec<(String, String, bool)>>>> = Lazy::new(|| Mutex::new(None));
 
 fn set_current_selections(selections: Vec<(String, String, bool)>) {
@@ -1353,11 +2874,7 @@ fn find_best_template(group: &str, lang: &str, is_messe: bool, country: Option<&
         "englisch" | "english" | "en" | "en_us" => vec!["en_us".to_string(), "en".to_string()],
         other => {
             // if user already passed a code like "de_de", use it first
-            if other.contains('_') || other.len() <= 3 {
-                vec![other.to_string()]
-            } else {
-                vec![other.to_string()]
-            }
+            vec![other.to_string()]
         }
     };
 
@@ -1368,7 +2885,7 @@ fn find_best_template(group: &str, lang: &str, is_messe: bool, country: Option<&
         }
     }
 
-    if let Some(ct) = country.and_then(|c| canonical_country_tag(c)) {
+    if let Some(ct) = country.and_then(canonical_country_tag) {
         for code in &codes {
             let lang_prefix = code.split('_').next().unwrap_or(code);
             candidates.push(format!("VORLAGE/Bestellschein-{}-{}_{}.pdf", capitalize_first(&g), lang_prefix, ct));
@@ -1385,12 +2902,7 @@ fn find_best_template(group: &str, lang: &str, is_messe: bool, country: Option<&
     candidates.push(format!("VORLAGE/Bestellscheine-{}.pdf", capitalize_first(&g)));
 
     let templates_dir = get_templates_dir();
-    for c in candidates {
-        if template_candidate_exists(&c, &templates_dir) {
-            return Some(c);
-        }
-    }
-    None
+    candidates.into_iter().find(|c| template_candidate_exists(c, &templates_dir))
 }
 
 // Erweiterte Template-Suche mit benutzerdefiniertem Verzeichnis
@@ -1406,11 +2918,7 @@ fn find_best_template_in_dir(group: &str, lang: &str, is_messe: bool, country: O
         "englisch" | "english" | "en" | "en_us" => vec!["en_us".to_string(), "en".to_string()],
         other => {
             // if user already passed a code like "de_de", use it first
-            if other.contains('_') || other.len() <= 3 {
-                vec![other.to_string()]
-            } else {
-                vec![other.to_string()]
-            }
+            vec![other.to_string()]
         }
     };
 
@@ -1421,7 +2929,7 @@ fn find_best_template_in_dir(group: &str, lang: &str, is_messe: bool, country: O
         }
     }
 
-    if let Some(ct) = country.and_then(|c| canonical_country_tag(c)) {
+    if let Some(ct) = country.and_then(canonical_country_tag) {
         for code in &codes {
             let lang_prefix = code.split('_').next().unwrap_or(code);
             candidates.push(format!("{}/Bestellschein-{}-{}_{}.pdf", template_dir, capitalize_first(&g), lang_prefix, ct));
@@ -1474,7 +2982,7 @@ fn list_template_candidates(group: &str, lang: &str, is_messe: bool, country: Op
         }
     }
 
-    if let Some(ct) = country.and_then(|c| canonical_country_tag(c)) {
+    if let Some(ct) = country.and_then(canonical_country_tag) {
         for code in &codes {
             let lang_prefix = code.split('_').next().unwrap_or(code);
             push_candidate(format!("VORLAGE/Bestellschein-{}-{}_{}.pdf", capitalize_first(&g), lang_prefix, ct));
@@ -1513,7 +3021,7 @@ fn find_available_templates_with_score(group: &str, lang: &str, is_messe: bool,
 
         // Exakte Kombination Gruppe+Code (z.B. bestellschein-endkunde-en_us) besonders belohnen
         let preferred_codes = get_preferred_language_codes(lang);
-        if let Some(primary) = preferred_codes.get(0) {
+        if let Some(primary) = preferred_codes.first() {
             // primary may be like "en_us" -> prefix "en"
             let prefix = primary.split('_').next().unwrap_or(primary).to_string();
             let group_code_exact1 = format!("{}-{}", group.to_lowercase(), primary.replace(' ', ""));
@@ -1865,7 +3373,7 @@ fn load_group_config(group: &str, language: &str, is_messe: bool) -> Config {
 
     // Bevorzugte Filename für die Gruppe; wenn Messe, erstelle messe-spezifische Datei.
     // Use language code in the filename so per-language configs are created (e.g. config_apo-de_de.toml)
-    let lang_code = get_language_code_variants(language).get(0).cloned().unwrap_or_else(|| language.chars().take(2).collect());
+    let lang_code = get_language_code_variants(language).first().cloned().unwrap_or_else(|| language.chars().take(2).collect());
     let group_filename = if is_messe {
         config_dir.join(format!("config_messe_{}-{}.toml", group.to_lowercase(), lang_code))
     } else {
@@ -1875,8 +3383,8 @@ fn load_group_config(group: &str, language: &str, is_messe: bool) -> Config {
     // Falls die Gruppendatei noch nicht existiert, schreibe eine Default-Konfiguration hinein
     if !group_filename.exists() {
         let default = get_group_default_config(group, is_messe);
-        println!("Verwende gruppenspezifische Defaults für {}: QR={:?}, Vertreter={:?}", 
-                 group, default.qr_codes, default.vertreter);
+        println!("Verwende gruppenspezifische Defaults für {}: Elemente={:?}", 
+                 group, default.elements);
                  
         // Erzeuge eine einfache TOML-Repräsentation, kompatibel mit load_config parsing
         let mut toml = String::new();
@@ -1886,19 +3394,8 @@ fn load_group_config(group: &str, language: &str, is_messe: bool) -> Config {
         }
         toml.push_str("# Koordinaten sind in PDF-Punkten (1 Punkt = 1/72 Zoll ≈ 0.35mm)\n\n");
         
-        // QR-Codes
-        toml.push_str("qr_codes = [\n");
-        for qr in &default.qr_codes {
-            toml.push_str(&format!("  {{ x = {}, y = {}, size = {} }},\n", qr.x, qr.y, qr.size));
-        }
-        toml.push_str("]\n\n");
-        
-        toml.push_str("[positions]\n");
-        toml.push_str("vertreter_nummer = [\n");
-        for v in &default.vertreter {
-            toml.push_str(&format!("  {{ x = {}, y = {}, size = {}, font_name = \"{}\", font_size = {}, font_style = \"{}\" }},\n", v.x, v.y, v.size, v.font_name, v.font_size, v.font_style));
-        }
-        toml.push_str("]\n\n");
+        // Overlay-Elemente
+        toml.push_str(&elements_toml_lines(&default));
         
         if let Err(e) = std::fs::write(&group_filename, toml) {
             eprintln!("Konnte Default-Config für Gruppe {} nicht schreiben: {}", group, e);
@@ -2075,11 +3572,10 @@ impl MyApp {
                                     if filename_lower.contains("de_de") || filename_lower.contains("de") {
                                         score += 8;
                                     }
-                                } else if lang_lower.contains("englisch") || lang_lower.contains("en") {
-                                    if filename_lower.contains("en_us") || filename_lower.contains("en") {
+                                } else if (lang_lower.contains("englisch") || lang_lower.contains("en"))
+                                    && (filename_lower.contains("en_us") || filename_lower.contains("en")) {
                                         score += 8;
                                     }
-                                }
 
                                 // Zielland passt
                                 if let Some(ct) = country {
@@ -2093,7 +3589,7 @@ impl MyApp {
                                 
                                 // Höhere Priorität für exakte Matches (Gruppe+Lang) - benutze primären Preferred-Code
                                 let preferred_codes = get_preferred_language_codes(lang);
-                                if let Some(primary) = preferred_codes.get(0) {
+                                if let Some(primary) = preferred_codes.first() {
                                     if filename_lower.contains(&format!("{}-{}", group.to_lowercase(), primary.replace(' ', ""))) {
                                         score += 40;
                                     }
@@ -2169,11 +3665,12 @@ impl App for MyApp {
 
         // Keyboard nudging for selected preview element: Arrow keys move selected element.
         // Use 1 UI-pixel per press as base, Shift => 10 pixels. Convert UI pixels to PDF points
-        // using the same scale as the preview: PDF A4 ~595x842 mapped to ui a4 size.
+        // using the same scale as the preview: page size of the template mapped to the ui page.
+        let (page_w, page_h) = self.preview_page_size;
         let a4_width = 350.0_f32;
-        let a4_height = 495.0_f32;
-        let scale_x = 595.0_f32 / a4_width; // PDF points per UI pixel horizontally
-        let scale_y = 842.0_f32 / a4_height; // PDF points per UI pixel vertically
+        let a4_height = a4_width * page_h / page_w;
+        let scale_x = page_w / a4_width; // PDF points per UI pixel horizontally
+        let scale_y = page_h / a4_height; // PDF points per UI pixel vertically
 
         let ui_move_pixels = if ctx.input(|i| i.modifiers.shift) { 10.0 } else { 1.0 };
 
@@ -2193,9 +3690,9 @@ impl App for MyApp {
                 let dy = if up { pdf_move_y } else if down { -pdf_move_y } else { 0.0 };
 
                 if kind == "qr" {
-                    if let Some(qr) = self.config.qr_codes.get_mut(idx) {
-                        qr.x = (qr.x + dx).max(0.0).min(595.0 - qr.size);
-                        qr.y = (qr.y + dy).max(0.0).min(842.0 - qr.size);
+                    if let Some(qr) = self.config.qr_codes_mut().nth(idx) {
+                        qr.x = (qr.x + dx).max(0.0).min(page_w - qr.size);
+                        qr.y = (qr.y + dy).max(0.0).min(page_h - qr.size);
                         // update manual fields for immediate feedback
                         if idx == 0 {
                             self.manual_qr_x = format!("{:.1}", qr.x);
@@ -2204,9 +3701,9 @@ impl App for MyApp {
                         }
                     }
                 } else if kind == "vertreter" {
-                    if let Some(v) = self.config.vertreter.get_mut(idx) {
-                        v.x = (v.x + dx).max(0.0).min(595.0 - v.size);
-                        v.y = (v.y + dy).max(0.0).min(842.0 - v.size);
+                    if let Some(v) = self.config.vertreter_mut().nth(idx) {
+                        v.x = (v.x + dx).max(0.0).min(page_w - v.size);
+                        v.y = (v.y + dy).max(0.0).min(page_h - v.size);
                         // update manual vertreter fields only for first
                         if idx == 0 {
                             self.manual_vertreter_x = format!("{:.1}", v.x);
@@ -2246,8 +3743,8 @@ impl App for MyApp {
                 // BEREICHS-AUSWAHL für Vertreternummern
                 ui.horizontal(|ui| {
                     let range_resp = ui.checkbox(&mut self.use_range_selection, "📊 Nur bestimmten Bereich generieren");
-                    if range_resp.clicked() {
-                        if self.use_range_selection {
+                    if range_resp.clicked()
+                        && self.use_range_selection {
                             // Beim ersten Aktivieren, versuche Gesamtanzahl zu bestimmen
                             if let Some(csv_path) = self.get_current_csv_path() {
                                 let customers = read_vertreter(&csv_path);
@@ -2257,7 +3754,6 @@ impl App for MyApp {
                                 }
                             }
                         }
-                    }
                     range_resp.on_hover_text("Ermöglicht das Verarbeiten nur eines Bereichs der CSV (von Index bis Index)");
                     
                     if self.use_range_selection {
@@ -2428,7 +3924,7 @@ impl App for MyApp {
                             }
                         };
                         
-                        let csv_path = selections.get(0).map(|s| s.0.clone()).unwrap_or_else(|| get_default_csv_path("Endkunde"));
+                        let csv_path = selections.first().map(|s| s.0.clone()).unwrap_or_else(|| get_default_csv_path("Endkunde"));
                         debug_print_global(&format!("CSV-Pfad: {}", csv_path));
                         
                         // Prüfe ob CSV-Datei existiert
@@ -2479,7 +3975,7 @@ impl App for MyApp {
                         let progress_counter = Arc::new(Mutex::new(0usize));
                         
                         // Sichere Ordner-Erkennung
-                        let dirs_result = std::panic::catch_unwind(|| get_release_dirs());
+                        let dirs_result = std::panic::catch_unwind(get_release_dirs);
                         let (_cfg_dir, data_dir, templates_dir, _tools, _out) = match dirs_result {
                             Ok(dirs) => {
                                 debug_print_global("Ordner erfolgreich ermittelt");
@@ -2503,6 +3999,9 @@ impl App for MyApp {
                         // Performance-Parameter klonen
                         let thread_sleep_ms = self.thread_sleep_ms;
                         let debug_mode = self.debug_mode;
+                        let run_options = self.run_options.clone();
+                        self.run_report = Arc::new(Mutex::new(RunReport::default()));
+                        let run_report = Arc::clone(&self.run_report);
 
                         thread::spawn(move || {
                             if let Err(e) = generate_bestellscheine_resume(
@@ -2526,6 +4025,8 @@ impl App for MyApp {
                                 use_range_selection,
                                 range_start_parsed,
                                 range_end_parsed,
+                                run_options,
+                                run_report,
                             ) {
                                 eprintln!("Fehler beim Erstellen der Bestellscheine: {}", e);
                             }
@@ -2534,8 +4035,8 @@ impl App for MyApp {
                     }
                     
                     // Sekundärer Button: Von vorne beginnen (nur wenn Resume verfügbar)
-                    if self.resume_available {
-                        if ui.button("🔄 Von vorne beginnen").clicked() {
+                    if self.resume_available
+                        && ui.button("🔄 Von vorne beginnen").clicked() {
                             // WICHTIG: Aktuelle Config für PDF-Generierung setzen
                             set_current_config(&self.config);
                             
@@ -2569,8 +4070,11 @@ impl App for MyApp {
                             let threads = self.max_threads;
                             let thread_sleep_ms = self.thread_sleep_ms;
                             let debug_mode = self.debug_mode;
+                            let run_options = self.run_options.clone();
+                            self.run_report = Arc::new(Mutex::new(RunReport::default()));
+                            let run_report = Arc::clone(&self.run_report);
                             let selections = get_current_selections().unwrap_or_else(|| vec![ ("DATA/Vertreternummern.csv".to_string(), "VORLAGE/Bestellschein-Endkunde-de_de.pdf".to_string(), true) ]);
-                            let csv_path = selections.get(0).map(|s| s.0.clone()).unwrap_or_else(|| "DATA/Vertreternummern.csv".to_string());
+                            let csv_path = selections.first().map(|s| s.0.clone()).unwrap_or_else(|| "DATA/Vertreternummern.csv".to_string());
                             let vertreter_vec = read_vertreter(&csv_path);
                             let vertreter_arc = Arc::new(vertreter_vec);
                             let total = vertreter_arc.len();
@@ -2606,12 +4110,13 @@ impl App for MyApp {
                                     false, // use_range
                                     0,     // range_start
                                     total.saturating_sub(1), // range_end
+                                    run_options,
+                                    run_report,
                                 ) {
                                     eprintln!("Fehler beim Erstellen der Bestellscheine: {}", e);
                                 }
                             });
                         }
-                    }
                     
                     // Sekundärer Button: Von vorne beginnen (nur wenn Resume verfügbar)
                     if self.resume_available {
@@ -2651,8 +4156,11 @@ impl App for MyApp {
                             let threads = self.max_threads;
                             let thread_sleep_ms = self.thread_sleep_ms;
                             let debug_mode = self.debug_mode;
+                            let run_options = self.run_options.clone();
+                            self.run_report = Arc::new(Mutex::new(RunReport::default()));
+                            let run_report = Arc::clone(&self.run_report);
                             let selections = get_current_selections().unwrap_or_else(|| vec![ ("DATA/Vertreternummern.csv".to_string(), "VORLAGE/Bestellschein-Endkunde-de_de.pdf".to_string(), true) ]);
-                            let csv_path = selections.get(0).map(|s| s.0.clone()).unwrap_or_else(|| "DATA/Vertreternummern.csv".to_string());
+                            let csv_path = selections.first().map(|s| s.0.clone()).unwrap_or_else(|| "DATA/Vertreternummern.csv".to_string());
                             let vertreter_vec = read_vertreter(&csv_path);
                             let vertreter_arc = Arc::new(vertreter_vec);
                             let total = vertreter_arc.len();
@@ -2688,6 +4196,8 @@ impl App for MyApp {
                                     false, // use_range
                                     0,     // range_start
                                     total.saturating_sub(1), // range_end
+                                    run_options,
+                                    run_report,
                                 ) {
                                     eprintln!("Fehler beim Erstellen der Bestellscheine: {}", e);
                                 }
@@ -2735,19 +4245,18 @@ impl App for MyApp {
                         .clicked() {
                         // Config KOMPLETT neu laden für aktuelle Gruppe/Sprache/Messe
                         self.config = load_group_config(&self.selected_group, &self.selected_language, self.is_messe);
-                        println!("Config geladen - QR-Codes: {:?}", self.config.qr_codes);
-                        println!("Config geladen - Vertreter: {:?}", self.config.vertreter);
+                        println!("Config geladen - Elemente: {:?}", self.config.elements);
                         
                         // Initialize manual coordinate fields with current values
-                        if !self.config.qr_codes.is_empty() {
-                            self.manual_qr_x = format!("{:.1}", self.config.qr_codes[0].x);
-                            self.manual_qr_y = format!("{:.1}", self.config.qr_codes[0].y);
-                            self.manual_qr_size = format!("{:.1}", self.config.qr_codes[0].size);
+                        if let Some(first_qr) = self.config.qr_codes().next() {
+                            self.manual_qr_x = format!("{:.1}", first_qr.x);
+                            self.manual_qr_y = format!("{:.1}", first_qr.y);
+                            self.manual_qr_size = format!("{:.1}", first_qr.size);
                         }
-                        if !self.config.vertreter.is_empty() {
-                            self.manual_vertreter_x = format!("{:.1}", self.config.vertreter[0].x);
-                            self.manual_vertreter_y = format!("{:.1}", self.config.vertreter[0].y);
-                            self.manual_vertreter_size = format!("{:.1}", self.config.vertreter[0].size);
+                        if let Some(first_v) = self.config.vertreter().next() {
+                            self.manual_vertreter_x = format!("{:.1}", first_v.x);
+                            self.manual_vertreter_y = format!("{:.1}", first_v.y);
+                            self.manual_vertreter_size = format!("{:.1}", first_v.size);
                         }
                         
                         self.show_config = true;
@@ -2778,14 +4287,14 @@ impl App for MyApp {
                             let group_cfg = load_group_config(&self.selected_group, &self.selected_language, self.is_messe);
                             self.config = group_cfg;
                             // Update manual input fields with loaded values
-                            if !self.config.qr_codes.is_empty() {
-                                self.manual_qr_x = format!("{:.1}", self.config.qr_codes[0].x);
-                                self.manual_qr_y = format!("{:.1}", self.config.qr_codes[0].y);
-                                self.manual_qr_size = format!("{:.1}", self.config.qr_codes[0].size);
+                            if let Some(first_qr) = self.config.qr_codes().next() {
+                                self.manual_qr_x = format!("{:.1}", first_qr.x);
+                                self.manual_qr_y = format!("{:.1}", first_qr.y);
+                                self.manual_qr_size = format!("{:.1}", first_qr.size);
                             }
-                            if !self.config.vertreter.is_empty() {
-                                self.manual_vertreter_x = format!("{:.1}", self.config.vertreter[0].x);
-                                self.manual_vertreter_y = format!("{:.1}", self.config.vertreter[0].y);
+                            if let Some(first_v) = self.config.vertreter().next() {
+                                self.manual_vertreter_x = format!("{:.1}", first_v.x);
+                                self.manual_vertreter_y = format!("{:.1}", first_v.y);
                             }
                             println!("Gruppe geändert zu Endkunde - Config neu geladen");
                         }
@@ -2799,14 +4308,14 @@ impl App for MyApp {
                             let group_cfg = load_group_config(&self.selected_group, &self.selected_language, self.is_messe);
                             self.config = group_cfg;
                             // Update manual input fields with loaded values
-                            if !self.config.qr_codes.is_empty() {
-                                self.manual_qr_x = format!("{:.1}", self.config.qr_codes[0].x);
-                                self.manual_qr_y = format!("{:.1}", self.config.qr_codes[0].y);
-                                self.manual_qr_size = format!("{:.1}", self.config.qr_codes[0].size);
+                            if let Some(first_qr) = self.config.qr_codes().next() {
+                                self.manual_qr_x = format!("{:.1}", first_qr.x);
+                                self.manual_qr_y = format!("{:.1}", first_qr.y);
+                                self.manual_qr_size = format!("{:.1}", first_qr.size);
                             }
-                            if !self.config.vertreter.is_empty() {
-                                self.manual_vertreter_x = format!("{:.1}", self.config.vertreter[0].x);
-                                self.manual_vertreter_y = format!("{:.1}", self.config.vertreter[0].y);
+                            if let Some(first_v) = self.config.vertreter().next() {
+                                self.manual_vertreter_x = format!("{:.1}", first_v.x);
+                                self.manual_vertreter_y = format!("{:.1}", first_v.y);
                             }
                             println!("Gruppe geändert zu Apo - Config neu geladen");
                         }
@@ -2820,14 +4329,14 @@ impl App for MyApp {
                             let group_cfg = load_group_config(&self.selected_group, &self.selected_language, self.is_messe);
                             self.config = group_cfg;
                             // Update manual input fields with loaded values
-                            if !self.config.qr_codes.is_empty() {
-                                self.manual_qr_x = format!("{:.1}", self.config.qr_codes[0].x);
-                                self.manual_qr_y = format!("{:.1}", self.config.qr_codes[0].y);
-                                self.manual_qr_size = format!("{:.1}", self.config.qr_codes[0].size);
+                            if let Some(first_qr) = self.config.qr_codes().next() {
+                                self.manual_qr_x = format!("{:.1}", first_qr.x);
+                                self.manual_qr_y = format!("{:.1}", first_qr.y);
+                                self.manual_qr_size = format!("{:.1}", first_qr.size);
                             }
-                            if !self.config.vertreter.is_empty() {
-                                self.manual_vertreter_x = format!("{:.1}", self.config.vertreter[0].x);
-                                self.manual_vertreter_y = format!("{:.1}", self.config.vertreter[0].y);
+                            if let Some(first_v) = self.config.vertreter().next() {
+                                self.manual_vertreter_x = format!("{:.1}", first_v.x);
+                                self.manual_vertreter_y = format!("{:.1}", first_v.y);
                             }
                             println!("Gruppe geändert zu Fachkreise - Config neu geladen");
                         }
@@ -2842,14 +4351,14 @@ impl App for MyApp {
                             let group_cfg = load_group_config(&self.selected_group, &self.selected_language, self.is_messe);
                             self.config = group_cfg;
                             // Update manual input fields
-                            if !self.config.qr_codes.is_empty() {
-                                self.manual_qr_x = format!("{:.1}", self.config.qr_codes[0].x);
-                                self.manual_qr_y = format!("{:.1}", self.config.qr_codes[0].y);
-                                self.manual_qr_size = format!("{:.1}", self.config.qr_codes[0].size);
+                            if let Some(first_qr) = self.config.qr_codes().next() {
+                                self.manual_qr_x = format!("{:.1}", first_qr.x);
+                                self.manual_qr_y = format!("{:.1}", first_qr.y);
+                                self.manual_qr_size = format!("{:.1}", first_qr.size);
                             }
-                            if !self.config.vertreter.is_empty() {
-                                self.manual_vertreter_x = format!("{:.1}", self.config.vertreter[0].x);
-                                self.manual_vertreter_y = format!("{:.1}", self.config.vertreter[0].y);
+                            if let Some(first_v) = self.config.vertreter().next() {
+                                self.manual_vertreter_x = format!("{:.1}", first_v.x);
+                                self.manual_vertreter_y = format!("{:.1}", first_v.y);
                             }
                             println!("Messe-Option geändert zu {} - Config neu geladen", self.is_messe);
                         }
@@ -2864,14 +4373,14 @@ impl App for MyApp {
                             let group_cfg = load_group_config(&self.selected_group, &self.selected_language, self.is_messe);
                             self.config = group_cfg;
                             // Update manual input fields
-                            if !self.config.qr_codes.is_empty() {
-                                self.manual_qr_x = format!("{:.1}", self.config.qr_codes[0].x);
-                                self.manual_qr_y = format!("{:.1}", self.config.qr_codes[0].y);
-                                self.manual_qr_size = format!("{:.1}", self.config.qr_codes[0].size);
+                            if let Some(first_qr) = self.config.qr_codes().next() {
+                                self.manual_qr_x = format!("{:.1}", first_qr.x);
+                                self.manual_qr_y = format!("{:.1}", first_qr.y);
+                                self.manual_qr_size = format!("{:.1}", first_qr.size);
                             }
-                            if !self.config.vertreter.is_empty() {
-                                self.manual_vertreter_x = format!("{:.1}", self.config.vertreter[0].x);
-                                self.manual_vertreter_y = format!("{:.1}", self.config.vertreter[0].y);
+                            if let Some(first_v) = self.config.vertreter().next() {
+                                self.manual_vertreter_x = format!("{:.1}", first_v.x);
+                                self.manual_vertreter_y = format!("{:.1}", first_v.y);
                             }
                             println!("Sprache geändert zu Deutsch - Config neu geladen");
                         }
@@ -2881,14 +4390,14 @@ impl App for MyApp {
                             let group_cfg = load_group_config(&self.selected_group, &self.selected_language, self.is_messe);
                             self.config = group_cfg;
                             // Update manual input fields
-                            if !self.config.qr_codes.is_empty() {
-                                self.manual_qr_x = format!("{:.1}", self.config.qr_codes[0].x);
-                                self.manual_qr_y = format!("{:.1}", self.config.qr_codes[0].y);
-                                self.manual_qr_size = format!("{:.1}", self.config.qr_codes[0].size);
+                            if let Some(first_qr) = self.config.qr_codes().next() {
+                                self.manual_qr_x = format!("{:.1}", first_qr.x);
+                                self.manual_qr_y = format!("{:.1}", first_qr.y);
+                                self.manual_qr_size = format!("{:.1}", first_qr.size);
                             }
-                            if !self.config.vertreter.is_empty() {
-                                self.manual_vertreter_x = format!("{:.1}", self.config.vertreter[0].x);
-                                self.manual_vertreter_y = format!("{:.1}", self.config.vertreter[0].y);
+                            if let Some(first_v) = self.config.vertreter().next() {
+                                self.manual_vertreter_x = format!("{:.1}", first_v.x);
+                                self.manual_vertreter_y = format!("{:.1}", first_v.y);
                             }
                             println!("Sprache geändert zu Englisch - Config neu geladen");
                         }
@@ -2897,22 +4406,20 @@ impl App for MyApp {
                     ui.separator();
                     ui.label("Zielland:");
                     ui.horizontal(|ui| {
-                        if ui.selectable_label(self.selected_country == "Deutschland", "Deutschland").clicked() {
-                            if self.selected_country != "Deutschland" {
+                        if ui.selectable_label(self.selected_country == "Deutschland", "Deutschland").clicked()
+                            && self.selected_country != "Deutschland" {
                                 clear_progress_files(&self.selected_group, &self.selected_language, self.is_messe);
                                 self.selected_country = "Deutschland".to_string();
                                 self.resume_info = load_resume_info(&self.selected_group, &self.selected_language, self.is_messe);
                                 println!("Zielland geändert zu Deutschland");
                             }
-                        }
-                        if ui.selectable_label(self.selected_country == "Schweiz", "Schweiz").clicked() {
-                            if self.selected_country != "Schweiz" {
+                        if ui.selectable_label(self.selected_country == "Schweiz", "Schweiz").clicked()
+                            && self.selected_country != "Schweiz" {
                                 clear_progress_files(&self.selected_group, &self.selected_language, self.is_messe);
                                 self.selected_country = "Schweiz".to_string();
                                 self.resume_info = load_resume_info(&self.selected_group, &self.selected_language, self.is_messe);
                                 println!("Zielland geändert zu Schweiz");
                             }
-                        }
                     });
 
                     ui.separator();
@@ -2969,9 +4476,9 @@ impl App for MyApp {
                                 .show_ui(ui, |ui| {
                                     for (name, path) in preset_files.iter() {
                                         if ui.selectable_label(false, name).on_hover_text(path.display().to_string()).clicked() {
-                                            if let Some(cfg) = try_load_config_file(&path) {
+                                            if let Some(cfg) = try_load_config_file(path) {
                                                 self.config = cfg;
-                                                set_current_config_path(&path);
+                                                set_current_config_path(path);
                                                 println!("Preset geladen aus Startup-Dialog: {:?}", path);
                                             } else {
                                                 println!("Konnte Preset nicht laden: {:?}", path);
@@ -3293,6 +4800,75 @@ impl App for MyApp {
                     
                     ui.add_space(10.0);
                     
+                    // Kampagne für Link-Vorlagen
+                    ui.group(|ui| {
+                        ui.label(egui::RichText::new("🔗 Kampagne").size(16.0));
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            ui.label("Kampagne:");
+                            ui.text_edit_singleline(&mut self.run_options.campaign)
+                                .on_hover_text("Wird in Link-Vorlagen für {campaign} eingesetzt (z.B. messe2026)");
+                        });
+                    });
+                    
+                    ui.add_space(10.0);
+                    
+                    // QR-Prüfung
+                    ui.group(|ui| {
+                        ui.label(egui::RichText::new("✅ QR-Prüfung").size(16.0));
+                        ui.separator();
+                        ui.label("Jeder QR-Code wird vor dem Einbetten dekodiert und mit dem Inhalt verglichen.");
+                        ui.checkbox(&mut self.run_options.verify_saved_qr, "Zusätzlich aus gespeicherter PDF zurücklesen")
+                            .on_hover_text("Langsamer: lädt jede erzeugte PDF erneut und dekodiert die eingebetteten QR-Bilder");
+                    });
+                    
+                    ui.add_space(10.0);
+                    
+                    // Ausgabe-Optimierung
+                    ui.group(|ui| {
+                        ui.label(egui::RichText::new("📦 PDF-Ausgabe").size(16.0));
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            for mode in OutputMode::ALL {
+                                ui.selectable_value(&mut self.run_options.output_mode, mode, mode.label());
+                            }
+                        });
+                        ui.label(egui::RichText::new("Klein: gleiche Schriften/Bilder zusammenfassen, unbenutzte Objekte entfernen, Streams komprimieren").size(11.0).color(egui::Color32::GRAY));
+                        if self.run_options.output_mode == OutputMode::PdfA {
+                            ui.horizontal(|ui| {
+                                ui.label("ICC-Profil:");
+                                ui.add(egui::TextEdit::singleline(&mut self.run_options.icc_profile).hint_text("leer = sRGB (eingebaut)").desired_width(220.0))
+                                    .on_hover_text("Ausgabebedingung der PDF/A-Dateien, z.B. ISO Coated v2 (ECI) für CMYK-Druckvorlagen");
+                                if ui.small_button("📂").clicked() {
+                                    if let Some(path) = rfd::FileDialog::new().add_filter("ICC-Profil", &["icc", "icm"]).pick_file() {
+                                        self.run_options.icc_profile = path.display().to_string();
+                                    }
+                                }
+                            });
+                            ui.label(egui::RichText::new("PDF/A-2b: Texte nur mit eingebetteten Schriften; Zeilen, die nicht konform werden können, stehen als Fehler im Laufbericht").size(11.0).color(egui::Color32::GRAY));
+                        }
+                        ui.separator();
+                        ui.checkbox(&mut self.run_options.print_file, "Zusätzlich Druckdatei je Vorlage")
+                            .on_hover_text("Alle Einzel-PDFs des Bereichs in einer Datei mit Lesezeichen und Seitenbeschriftung je Vertreter");
+                        if self.run_options.print_file {
+                            ui.checkbox(&mut self.run_options.print_file_duplex, "Duplex: jeder Vertreter beginnt auf einer Vorderseite")
+                                .on_hover_text("Fügt nach Vertretern mit ungerader Seitenzahl eine Leerseite ein");
+                        }
+                        ui.checkbox(&mut self.run_options.imposition.enabled, "Zusätzlich auf Druckbogen ausschießen")
+                            .on_hover_text("Stellt die erzeugten Seiten mehrfach nebeneinander auf größere Bogen (z.B. A5 auf SRA3)");
+                        if self.run_options.imposition.enabled {
+                            imposition_ui(ui, &mut self.run_options.imposition);
+                        }
+                        if self.run_options.print_file || self.run_options.imposition.enabled {
+                            ui.horizontal(|ui| {
+                                ui.label("Sortieren nach Spalte:");
+                                ui.add(egui::TextEdit::singleline(&mut self.run_options.print_file_sort_column).hint_text("leer = Vertreternummer").desired_width(160.0));
+                            });
+                        }
+                    });
+                    
+                    ui.add_space(10.0);
+                    
                     // Template-Ordner Sektion
                     ui.group(|ui| {
                         ui.label(egui::RichText::new("📄 Vorlagen-Ordner").size(16.0));
@@ -3537,7 +5113,7 @@ impl App for MyApp {
                 
                 // Stop-Status-Datei prüfen (separate von progress.txt, versteckt)
                 let stop_status_path = get_temp_file_path("stop_status.txt");
-                if let Ok(_) = std::fs::read_to_string(&stop_status_path) {
+                if std::fs::read_to_string(&stop_status_path).is_ok() {
                     println!("Stop-Status-Datei gefunden - beende Generierung");
                     self.status_message = format!("Gestoppt bei {}% - kann fortgesetzt werden", (self.progress * 100.0) as u32);
                     self.is_generating = false;
@@ -3690,7 +5266,7 @@ impl App for MyApp {
                                                 } else {
                                                     if self.is_messe {
                                                         // Suggest a YAML filename for Messe configs
-                                                        let lang_code = get_language_code_variants(&self.selected_language).get(0).cloned().unwrap_or_else(|| self.selected_language.chars().take(2).collect());
+                                                        let lang_code = get_language_code_variants(&self.selected_language).first().cloned().unwrap_or_else(|| self.selected_language.chars().take(2).collect());
                                                         let suggested = format!("CONFIG/config_messe_{}-{}.yaml", self.selected_group.to_lowercase(), lang_code);
                                                         self.config_save_path = suggested;
                                                     } else {
@@ -3704,9 +5280,10 @@ impl App for MyApp {
                                 });
                             });
                             
-                            // DIN A4 Verhältnis: 210mm x 297mm ≈ 1:1.414
+                            // Seitenverhältnis der Vorlage (sichtbarer Bereich in Anzeigelage, Standard DIN A4)
+                            let (page_w, page_h) = self.preview_page_size;
                             let a4_width = 350.0;
-                            let a4_height = 495.0; // 350 * 1.414
+                            let a4_height = a4_width * page_h / page_w;
                             
                             // Bereich für DIN A4 Darstellung reservieren
                             let (a4_rect, _a4_response) = ui.allocate_exact_size(
@@ -3728,13 +5305,20 @@ impl App for MyApp {
                                         // grobe Suche: enthält Gruppenname und ggf. Sprachkürzel
                                         let grp = self.selected_group.to_lowercase();
                                         let lang_prefix = self.selected_language.chars().take(2).collect::<String>().to_lowercase();
-                                        if fname_l.contains(&grp) && (fname_l.contains(&lang_prefix) || fname_l.contains(&"de") || fname_l.contains(&"en")) {
+                                        if fname_l.contains(&grp) && (fname_l.contains(&lang_prefix) || fname_l.contains("de") || fname_l.contains("en")) {
                                             template_found = Some(fname.to_string());
                                             break;
                                         }
                                     }
                                 }
                             }
+                            // Seitengröße nur bei Wechsel der Vorlage neu einlesen (wirkt ab dem nächsten Frame)
+                            if template_found != self.preview_page_source {
+                                self.preview_page_size = template_found.as_ref()
+                                    .and_then(|name| template_page_size(&templates_dir.join(name)))
+                                    .unwrap_or((595.0, 842.0));
+                                self.preview_page_source = template_found.clone();
+                            }
                             let (template_status, template_loaded) = if let Some(ref name) = template_found {
                                 (format!("✅ Template vorhanden: {}", name), true)
                             } else {
@@ -3749,7 +5333,7 @@ impl App for MyApp {
                             };
                             
                             let (_, data_dir, _, _, _) = get_release_dirs_with_debug(self.debug_mode);
-                            let full_csv_path = data_dir.join(&csv_path.replace("Data/", ""));
+                            let full_csv_path = data_dir.join(csv_path.replace("Data/", ""));
                             let csv_exists = full_csv_path.exists();
                             let csv_status = if csv_exists {
                                 format!("✅ CSV-Datei gefunden: {}", csv_path)
@@ -3769,44 +5353,44 @@ impl App for MyApp {
                             ui.painter().rect_filled(inner_rect, 3.0, egui::Color32::from_rgb(250, 250, 250));
                             ui.painter().rect_stroke(inner_rect, 3.0, egui::Stroke::new(1.0, egui::Color32::from_rgb(200, 200, 200)));
 
-                            // A4 in mm: 210 x 297
-                            let a4_mm_w = 210.0;
-                            let a4_mm_h = 297.0;
+                            // Seitengröße in mm (A4: 210 x 297)
+                            let a4_mm_w = page_w * 25.4 / 72.0;
+                            let a4_mm_h = page_h * 25.4 / 72.0;
                             let mm_to_ui_x = inner_rect.width() / a4_mm_w;
                             let mm_to_ui_y = inner_rect.height() / a4_mm_h;
 
                             // Draw grid lines every 10 mm (lighter every 10, darker every 50)
-                            for mm in (0..=210).step_by(5) {
+                            for mm in (0..=a4_mm_w as u32).step_by(5) {
                                 let x = inner_rect.left() + (mm as f32) * mm_to_ui_x;
                                 let color = if mm % 50 == 0 { egui::Color32::from_gray(200) } else if mm % 10 == 0 { egui::Color32::from_gray(220) } else { egui::Color32::from_gray(235) };
                                 ui.painter().line_segment([egui::pos2(x, inner_rect.top()), egui::pos2(x, inner_rect.bottom())], egui::Stroke::new(1.0, color));
                             }
-                            for mm in (0..=297).step_by(5) {
+                            for mm in (0..=a4_mm_h as u32).step_by(5) {
                                 let y = inner_rect.top() + (mm as f32) * mm_to_ui_y;
                                 let color = if mm % 50 == 0 { egui::Color32::from_gray(200) } else if mm % 10 == 0 { egui::Color32::from_gray(220) } else { egui::Color32::from_gray(235) };
                                 ui.painter().line_segment([egui::pos2(inner_rect.left(), y), egui::pos2(inner_rect.right(), y)], egui::Stroke::new(1.0, color));
                             }
 
                             // Draw simple rulers (top and left) with mm labels every 50 mm
-                            for mm in (0..=210).step_by(50) {
+                            for mm in (0..=a4_mm_w as u32).step_by(50) {
                                 let x = inner_rect.left() + (mm as f32) * mm_to_ui_x;
                                 ui.painter().text(egui::pos2(x + 2.0, inner_rect.top() - 14.0), egui::Align2::LEFT_TOP, format!("{}mm", mm), egui::FontId::proportional(10.0), egui::Color32::from_gray(120));
                             }
-                            for mm in (0..=297).step_by(50) {
+                            for mm in (0..=a4_mm_h as u32).step_by(50) {
                                 let y = inner_rect.top() + (mm as f32) * mm_to_ui_y;
                                 ui.painter().text(egui::pos2(inner_rect.left() - 40.0, y - 6.0), egui::Align2::LEFT_TOP, format!("{}mm", mm), egui::FontId::proportional(10.0), egui::Color32::from_gray(120));
                             }
 
                             // Overlay configured QR boxes using config coordinates (PDF points -> UI scale).
-                            // PDF points: A4 ~ 595 x 842
-                            let pdf_w = 595.0_f32;
-                            let pdf_h = 842.0_f32;
+                            // PDF points of the visible page (A4 ~ 595 x 842)
+                            let pdf_w = page_w;
+                            let pdf_h = page_h;
                             let scale_x = inner_rect.width() / pdf_w;
                             let scale_y = inner_rect.height() / pdf_h;
-                            for (i, qr) in self.config.qr_codes.iter().enumerate() {
-                                let qr_ui_x = inner_rect.left() + qr.x as f32 * scale_x;
-                                let qr_ui_y = inner_rect.top() + qr.y as f32 * scale_y;
-                                let size_ui = qr.size as f32 * scale_x;
+                            for (i, qr) in self.config.qr_codes().enumerate() {
+                                let qr_ui_x = inner_rect.left() + qr.x * scale_x;
+                                let qr_ui_y = inner_rect.top() + qr.y * scale_y;
+                                let size_ui = qr.size * scale_x;
                                 let qr_rect = egui::Rect::from_min_size(egui::pos2(qr_ui_x, qr_ui_y), egui::vec2(size_ui, size_ui));
                                 ui.painter().rect_stroke(qr_rect, 2.0, egui::Stroke::new(1.5, egui::Color32::from_rgb(120, 160, 220)));
                                 ui.painter().text(egui::pos2(qr_ui_x + 2.0, qr_ui_y + 2.0), egui::Align2::LEFT_TOP, format!("QR{}", i+1), egui::FontId::proportional(10.0), egui::Color32::from_rgb(80,80,80));
@@ -3838,7 +5422,7 @@ impl App for MyApp {
                             ui.painter().text(
                                 egui::pos2(header_rect.center().x, header_rect.top() + 45.0),
                                 egui::Align2::CENTER_TOP,
-                                &format!("📄 {} {} Vorlage{}", 
+                                format!("📄 {} {} Vorlage{}", 
                                         self.selected_group,
                                         self.selected_language,
                                         if self.is_messe { " (Messe)" } else { "" }),
@@ -3850,11 +5434,11 @@ impl App for MyApp {
                             
                             // (Removed simulated customer/order form sections to keep preview minimal and unambiguous)
                             // Skalierungsfaktor für die Koordinaten (DIN A4 in PDF ist ca. 595x842 Punkte)
-                            let scale_x = 595.0 / a4_width;
-                            let scale_y = 842.0 / a4_height;
+                            let scale_x = page_w / a4_width;
+                            let scale_y = page_h / a4_height;
                             
                             // QR-Codes
-                            for (i, qr) in self.config.qr_codes.iter_mut().enumerate() {
+                            for (i, qr) in self.config.qr_codes_mut().enumerate() {
                                 let qr_display_size = qr.size * 1.5; // Größer für bessere Sichtbarkeit
                                 let qr_pos_x = qr.x / scale_x;
                                 let qr_pos_y = a4_height - (qr.y / scale_y); // Y-Koordinate umkehren
@@ -3888,6 +5472,12 @@ impl App for MyApp {
                                     }
                                 }
                                 ui.painter().rect_filled(qr_rect, 3.0, egui::Color32::from_rgb(255, 165, 0)); // Orange
+                                if !qr.logo_path.is_empty() {
+                                    // Logo-Fläche in der Mitte andeuten
+                                    let logo_side = qr_display_size * qr.logo_size.clamp(0.05, 0.30);
+                                    let logo_rect = egui::Rect::from_center_size(qr_rect.center(), egui::vec2(logo_side, logo_side));
+                                    ui.painter().rect_filled(logo_rect, 1.0, egui::Color32::WHITE);
+                                }
                                 ui.painter().text(
                                     qr_rect.center(),
                                     egui::Align2::CENTER_CENTER,
@@ -3895,14 +5485,35 @@ impl App for MyApp {
                                     egui::FontId::default(),
                                     egui::Color32::BLACK,
                                 );
+                                if qr.rotation.rem_euclid(360.0) != 0.0 {
+                                    // Gedrehten Umriss andeuten (UI-Y zeigt nach unten, daher negativer Winkel)
+                                    let (sin, cos) = (-qr.rotation.to_radians()).sin_cos();
+                                    let half = qr_display_size / 2.0;
+                                    let center = qr_rect.center();
+                                    let corners: Vec<egui::Pos2> = [(-half, -half), (half, -half), (half, half), (-half, half)]
+                                        .iter()
+                                        .map(|(dx, dy)| egui::pos2(center.x + dx * cos - dy * sin, center.y + dx * sin + dy * cos))
+                                        .collect();
+                                    ui.painter().add(egui::Shape::closed_line(corners, egui::Stroke::new(1.5, egui::Color32::from_rgb(200, 90, 0))));
+                                    ui.painter().text(qr_rect.right_top(), egui::Align2::RIGHT_BOTTOM, format!("{:.0}°", qr.rotation), egui::FontId::proportional(9.0), egui::Color32::from_rgb(120, 60, 0));
+                                }
+                                if !qr.caption.is_empty() {
+                                    ui.painter().text(
+                                        egui::pos2(qr_rect.center().x, qr_rect.bottom() + qr.caption_offset * 1.5),
+                                        egui::Align2::CENTER_TOP,
+                                        &qr.caption,
+                                        egui::FontId::proportional((qr.caption_size * 1.5).max(8.0)),
+                                        egui::Color32::from_rgb(60, 60, 60),
+                                    );
+                                }
                                 
                                 if qr_response.dragged() {
                                     let delta = qr_response.drag_delta();
                                     qr.x += delta.x * scale_x;
                                     qr.y -= delta.y * scale_y; // Y-Koordinate umkehren
                                     // Grenzen prüfen
-                                    qr.x = qr.x.max(0.0).min(595.0 - qr.size);
-                                    qr.y = qr.y.max(0.0).min(842.0 - qr.size);
+                                    qr.x = qr.x.max(0.0).min(page_w - qr.size);
+                                    qr.y = qr.y.max(0.0).min(page_h - qr.size);
                                     
                                     // Update manual input fields for first QR code
                                     if i == 0 {
@@ -3917,17 +5528,24 @@ impl App for MyApp {
                             }
                             
                             // Draw minimal markers for Vertreternummer positions so users can see and nudge them.
-                            for (i, v) in self.config.vertreter.iter_mut().enumerate() {
+                            for (i, v) in self.config.vertreter_mut().enumerate() {
                                 // Use the same transform as above (map PDF points -> A4 UI coordinates)
                                 // Compute a rectangular marker that reflects the font size and expected text width.
                                 // Approximate width: character_width_factor * font_size * estimated_chars
-                                let est_chars = v.font_name.len().max(6) as f32; // rough estimate (at least room for number)
+                                // Textformat mit typischer Nummernlänge, Zeichenabstand und Skalierung schätzen
+                                let est_chars = v.format.replace("{kundennr}", "000000").chars().count().max(6) as f32;
                                 let char_width_factor = 0.6; // approximate em-width factor
-                                let text_width_pts = v.font_size * char_width_factor * est_chars; // in PDF pts
+                                let text_width_pts = (v.font_size * char_width_factor + v.char_spacing) * est_chars * v.horizontal_scaling / 100.0; // in PDF pts
                                 // Convert PDF points to UI using same scale_x/scale_y mapping
                                 let marker_w_ui = text_width_pts / scale_x;
                                 let marker_h_ui = (v.font_size * 1.2) / scale_y; // little padding
-                                let v_pos_x = v.x / scale_x;
+                                // Ausrichtung berücksichtigen: der Marker beginnt links vom Ankerpunkt
+                                let align_shift = match v.align {
+                                    TextAlign::Left => 0.0,
+                                    TextAlign::Center => marker_w_ui.max(12.0) / 2.0,
+                                    TextAlign::Right => marker_w_ui.max(12.0),
+                                };
+                                let v_pos_x = v.x / scale_x - align_shift;
                                 let v_pos_y = a4_height - (v.y / scale_y); // invert Y like for QR
 
                                 let vert_rect = egui::Rect::from_min_size(
@@ -3953,7 +5571,7 @@ impl App for MyApp {
                                     egui::Align2::LEFT_CENTER,
                                     format!("V{} ({})", i + 1, v.font_name),
                                     egui::FontId::proportional((v.font_size / 1.2).max(10.0)),
-                                    egui::Color32::from_rgb(40, 40, 40),
+                                    egui::Color32::from_rgb(v.color[0], v.color[1], v.color[2]),
                                 );
 
                                 if vert_resp.dragged() {
@@ -3962,8 +5580,8 @@ impl App for MyApp {
                                     v.y -= delta.y * scale_y; // invert Y
 
                                     // Boundaries (PDF coordinates)
-                                    v.x = v.x.max(0.0).min(595.0 - v.size);
-                                    v.y = v.y.max(0.0).min(842.0 - v.size);
+                                    v.x = v.x.max(0.0).min(page_w - v.size);
+                                    v.y = v.y.max(0.0).min(page_h - v.size);
 
                                     // If first vertreter, update manual input fields for quick feedback
                                     // No manual text fields for vertreter currently; positions update in config.
@@ -3972,6 +5590,110 @@ impl App for MyApp {
                                     self.selected_element = Some(("vertreter".to_string(), i));
                                 }
                             }
+
+                            // Strichcodes (Breite x Strichhöhe, DataMatrix quadratisch)
+                            for (i, barcode) in self.config.barcodes_mut().enumerate() {
+                                let height = if barcode.symbology.is_square() { barcode.size } else { barcode.bar_height };
+                                let bc_rect = egui::Rect::from_min_size(
+                                    egui::pos2(a4_rect.left() + barcode.x / scale_x, a4_rect.top() + a4_height - ((barcode.y + height) / scale_y)),
+                                    egui::vec2((barcode.size / scale_x).max(12.0), (height / scale_y).max(8.0)),
+                                );
+                                let bc_resp = ui.interact(bc_rect, egui::Id::new(format!("barcode_drag_{}", i)), egui::Sense::drag());
+                                ui.painter().rect_filled(bc_rect, 2.0, egui::Color32::from_rgb(220, 220, 220));
+                                ui.painter().rect_stroke(bc_rect, 2.0, egui::Stroke::new(1.0, egui::Color32::from_rgb(60, 60, 60)));
+                                if let Some((ref kind, idx)) = self.selected_element {
+                                    if kind == "barcode" && idx == i {
+                                        ui.painter().rect_stroke(bc_rect.expand(4.0), 3.0, egui::Stroke::new(2.0, egui::Color32::from_rgb(40, 160, 40)));
+                                    }
+                                }
+                                ui.painter().text(
+                                    bc_rect.center(),
+                                    egui::Align2::CENTER_CENTER,
+                                    format!("▮▯ B{} {}", i + 1, barcode.symbology.label()),
+                                    egui::FontId::proportional(10.0),
+                                    egui::Color32::BLACK,
+                                );
+                                if bc_resp.dragged() {
+                                    let delta = bc_resp.drag_delta();
+                                    barcode.x += delta.x * scale_x;
+                                    barcode.y -= delta.y * scale_y;
+                                    barcode.x = barcode.x.max(0.0).min(page_w - barcode.size);
+                                    barcode.y = barcode.y.max(0.0).min(page_h - height);
+                                }
+                                if bc_resp.clicked() {
+                                    self.selected_element = Some(("barcode".to_string(), i));
+                                }
+                            }
+
+                            // Bilder (Rahmen Breite x Höhe)
+                            for (i, image) in self.config.images_mut().enumerate() {
+                                let img_rect = egui::Rect::from_min_size(
+                                    egui::pos2(a4_rect.left() + image.x / scale_x, a4_rect.top() + a4_height - ((image.y + image.height) / scale_y)),
+                                    egui::vec2((image.width / scale_x).max(12.0), (image.height / scale_y).max(8.0)),
+                                );
+                                let img_resp = ui.interact(img_rect, egui::Id::new(format!("image_drag_{}", i)), egui::Sense::drag());
+                                ui.painter().rect_filled(img_rect, 2.0, egui::Color32::from_rgba_unmultiplied(255, 235, 200, 180));
+                                ui.painter().rect_stroke(img_rect, 2.0, egui::Stroke::new(1.0, egui::Color32::from_rgb(180, 120, 40)));
+                                if let Some((ref kind, idx)) = self.selected_element {
+                                    if kind == "image" && idx == i {
+                                        ui.painter().rect_stroke(img_rect.expand(4.0), 3.0, egui::Stroke::new(2.0, egui::Color32::from_rgb(40, 160, 40)));
+                                    }
+                                }
+                                ui.painter().text(
+                                    img_rect.center(),
+                                    egui::Align2::CENTER_CENTER,
+                                    format!("🖼 I{}", i + 1),
+                                    egui::FontId::proportional(10.0),
+                                    egui::Color32::BLACK,
+                                );
+                                if img_resp.dragged() {
+                                    let delta = img_resp.drag_delta();
+                                    image.x += delta.x * scale_x;
+                                    image.y -= delta.y * scale_y;
+                                    image.x = image.x.max(0.0).min(page_w - image.width);
+                                    image.y = image.y.max(0.0).min(page_h - image.height);
+                                }
+                                if img_resp.clicked() {
+                                    self.selected_element = Some(("image".to_string(), i));
+                                }
+                            }
+
+                            // Rechtecke (Rahmen/Füllung in Originalfarben)
+                            for (i, rect) in self.config.rects_mut().enumerate() {
+                                let shape_rect = egui::Rect::from_min_size(
+                                    egui::pos2(a4_rect.left() + rect.x / scale_x, a4_rect.top() + a4_height - ((rect.y + rect.height) / scale_y)),
+                                    egui::vec2((rect.width / scale_x).max(4.0), (rect.height / scale_y).max(4.0)),
+                                );
+                                let rect_resp = ui.interact(shape_rect, egui::Id::new(format!("rect_drag_{}", i)), egui::Sense::drag());
+                                if let Some([r, g, b]) = rect.fill {
+                                    ui.painter().rect_filled(shape_rect, 0.0, egui::Color32::from_rgb(r, g, b));
+                                }
+                                let [r, g, b] = rect.stroke.unwrap_or([150, 150, 150]);
+                                ui.painter().rect_stroke(shape_rect, 0.0, egui::Stroke::new(1.0, egui::Color32::from_rgb(r, g, b)));
+                                if let Some((ref kind, idx)) = self.selected_element {
+                                    if kind == "rect" && idx == i {
+                                        ui.painter().rect_stroke(shape_rect.expand(4.0), 3.0, egui::Stroke::new(2.0, egui::Color32::from_rgb(40, 160, 40)));
+                                    }
+                                }
+                                if rect_resp.dragged() {
+                                    let delta = rect_resp.drag_delta();
+                                    rect.x += delta.x * scale_x;
+                                    rect.y -= delta.y * scale_y;
+                                    rect.x = rect.x.max(0.0).min(page_w - rect.width);
+                                    rect.y = rect.y.max(0.0).min(page_h - rect.height);
+                                }
+                                if rect_resp.clicked() {
+                                    self.selected_element = Some(("rect".to_string(), i));
+                                }
+                            }
+
+                            // Linien (nur Anzeige, Endpunkte werden in der Liste gesetzt)
+                            for line in self.config.lines() {
+                                let [r, g, b] = line.color;
+                                let from = egui::pos2(a4_rect.left() + line.x / scale_x, a4_rect.top() + a4_height - line.y / scale_y);
+                                let to = egui::pos2(a4_rect.left() + line.x2 / scale_x, a4_rect.top() + a4_height - line.y2 / scale_y);
+                                ui.painter().line_segment([from, to], egui::Stroke::new((line.stroke_width / scale_x).max(1.0), egui::Color32::from_rgb(r, g, b)));
+                            }
                         });
 
                         // Speichern-unter Modal
@@ -4016,25 +5738,53 @@ impl App for MyApp {
                         ui.vertical(|ui| {
                             ui.heading("Elemente verwalten");
                             
+                            link_template_ui(ui, &mut self.config);
+                            metadata_ui(ui, &mut self.config);
+                            encryption_ui(ui, &mut self.config);
+                            
                             ui.group(|ui| {
                                 ui.label("QR-Codes:");
                                 ui.horizontal(|ui| {
                                     if ui.button("+ QR-Code hinzufügen").clicked() {
-                                        self.config.qr_codes.push(QrCodeConfig { x: 100.0, y: 100.0, size: 18.0, pages: vec![1], all_pages: false });
+                                        self.config.elements.push(Element::new(ElementKind::Qr(QrCodeConfig { x: 100.0, y: 100.0, size: 18.0, pages: vec![1], all_pages: false, ..Default::default() })));
                                     }
-                                    if ui.button("- QR-Code entfernen").clicked() && !self.config.qr_codes.is_empty() {
-                                        self.config.qr_codes.pop();
+                                    if ui.button("- QR-Code entfernen").clicked() {
+                                        self.config.remove_last_of("qr");
                                     }
                                 });
                                 
                                 // QR-Code Größen-Slider und Seiten-Auswahl für jeden QR-Code
-                                for (i, qr) in self.config.qr_codes.iter_mut().enumerate() {
+                                for (i, qr) in self.config.qr_codes_mut().enumerate() {
                                     ui.group(|ui| {
                                         ui.horizontal(|ui| {
                                             ui.label(format!("QR-Code {} Größe:", i + 1));
                                             ui.add(egui::Slider::new(&mut qr.size, 10.0..=50.0).suffix(" pt"));
                                         });
                                         
+                                        // Logo in der QR-Code-Mitte (optional)
+                                        ui.horizontal(|ui| {
+                                            ui.label("Logo:");
+                                            ui.add(egui::TextEdit::singleline(&mut qr.logo_path).hint_text("kein Logo").desired_width(150.0));
+                                            if ui.button("📂").clicked() {
+                                                if let Some(path) = rfd::FileDialog::new().add_filter("Logo", &["png", "jpg", "jpeg", "pdf"]).pick_file() {
+                                                    qr.logo_path = path.display().to_string();
+                                                }
+                                            }
+                                            if !qr.logo_path.is_empty() && ui.small_button("❌").clicked() {
+                                                qr.logo_path.clear();
+                                            }
+                                        });
+                                        if !qr.logo_path.is_empty() {
+                                            ui.horizontal(|ui| {
+                                                ui.label("Logo-Größe:");
+                                                ui.add(egui::Slider::new(&mut qr.logo_size, 0.05..=0.30).step_by(0.01));
+                                            });
+                                        }
+                                        
+                                        qr_payload_ui(ui, qr, &format!("panel_{}", i));
+                                        qr_rotation_caption_ui(ui, qr, &format!("panel_{}", i));
+                                        form_field_ui(ui, &mut qr.form_field);
+                                        
                                         // Seiten-Auswahl für diesen QR-Code
                                         ui.horizontal(|ui| {
                                             ui.label("Seiten:");
@@ -4084,18 +5834,30 @@ impl App for MyApp {
                             
                             ui.separator();
                             
+                            barcodes_ui(ui, &mut self.config, "panel");
+                            
+                            ui.separator();
+                            
+                            images_ui(ui, &mut self.config, "panel");
+
+                            ui.separator();
+
+                            elements_ui(ui, &mut self.config, "panel");
+                            
+                            ui.separator();
+                            
                             ui.group(|ui| {
                                 ui.label("Vertreternummer-Felder:");
                                 ui.horizontal(|ui| {
                                     if ui.button("+ Feld hinzufügen").clicked() {
-                                        self.config.vertreter.push(VertreterConfig { x: 100.0, y: 200.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string() });
+                                        self.config.elements.push(Element::new(ElementKind::Text(VertreterConfig { x: 100.0, y: 200.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string(), ..Default::default() })));
                                     }
-                                    if ui.button("- Feld entfernen").clicked() && !self.config.vertreter.is_empty() {
-                                        self.config.vertreter.pop();
+                                    if ui.button("- Feld entfernen").clicked() {
+                                        self.config.remove_last_of("text");
                                     }
                                 });
                                 // Font-Einstellungen und Seiten-Auswahl für jedes Vertreternummer-Feld
-                                for (i, v) in self.config.vertreter.iter_mut().enumerate() {
+                                for (i, v) in self.config.vertreter_mut().enumerate() {
                                     ui.group(|ui| {
                                         ui.label(format!("Vertreternummer Feld {}", i + 1));
                                         
@@ -4185,6 +5947,9 @@ impl App for MyApp {
                                             ui.label("Schriftgröße:");
                                             ui.add(egui::Slider::new(&mut v.font_size, 6.0..=48.0).suffix(" pt"));
                                         });
+                                        vertreter_layout_ui(ui, v, &format!("panel_{}", i));
+                                        vertreter_text_style_ui(ui, v);
+                                        form_field_ui(ui, &mut v.form_field);
                                         
                                         // Seiten-Auswahl für dieses Vertreternummer-Feld
                                         ui.horizontal(|ui| {
@@ -4237,7 +6002,7 @@ impl App for MyApp {
                             
                             // Koordinaten anzeigen (optional)
                             ui.collapsing("📍 Genaue Koordinaten", |ui| {
-                                for (i, qr) in self.config.qr_codes.iter().enumerate() {
+                                for (i, qr) in self.config.qr_codes().enumerate() {
                                     let pages_str = if qr.all_pages {
                                         "alle Seiten".to_string()
                                     } else {
@@ -4246,7 +6011,7 @@ impl App for MyApp {
                                     ui.label(format!("QR-Code {}: x={:.1}, y={:.1}, Größe={:.1}, {}", 
                                         i + 1, qr.x, qr.y, qr.size, pages_str));
                                 }
-                                for (i, pos) in self.config.vertreter.iter().enumerate() {
+                                for (i, pos) in self.config.vertreter().enumerate() {
                                     let pages_str = if pos.all_pages {
                                         "alle Seiten".to_string()
                                     } else {
@@ -4263,7 +6028,7 @@ impl App for MyApp {
                                 ui.separator();
                                 
                                 // QR-Code manual input
-                                if !self.config.qr_codes.is_empty() {
+                                if self.config.qr_codes().next().is_some() {
                                     ui.group(|ui| {
                                         ui.label("QR-Code Position (erster QR-Code):");
                                         ui.horizontal(|ui| {
@@ -4284,9 +6049,11 @@ impl App for MyApp {
                                                 self.manual_qr_y.parse::<f32>(),
                                                 self.manual_qr_size.parse::<f32>()
                                             ) {
-                                                self.config.qr_codes[0].x = x;
-                                                self.config.qr_codes[0].y = y;
-                                                self.config.qr_codes[0].size = size;
+                                                if let Some(qr) = self.config.qr_codes_mut().next() {
+                                                    qr.x = x;
+                                                    qr.y = y;
+                                                    qr.size = size;
+                                                }
                                                 println!("QR-Code Position manuell gesetzt: x={}, y={}, size={}", x, y, size);
                                             }
                                         }
@@ -4295,7 +6062,7 @@ impl App for MyApp {
                                 }
                                 
                                 // Vertreter position manual input
-                                if !self.config.vertreter.is_empty() {
+                                if self.config.vertreter().next().is_some() {
                                     ui.group(|ui| {
                                         ui.label("Kundennummer Position (erste Position):");
                                         ui.horizontal(|ui| {
@@ -4315,11 +6082,14 @@ impl App for MyApp {
                                                 self.manual_vertreter_x.parse::<f32>(),
                                                 self.manual_vertreter_y.parse::<f32>()
                                             ) {
-                                                self.config.vertreter[0].x = x;
-                                                self.config.vertreter[0].y = y;
-                                                // Try to parse and set size as well (optional)
-                                                if let Ok(sz) = self.manual_vertreter_size.parse::<f32>() {
-                                                    self.config.vertreter[0].size = sz;
+                                                let size = self.manual_vertreter_size.parse::<f32>().ok();
+                                                if let Some(v) = self.config.vertreter_mut().next() {
+                                                    v.x = x;
+                                                    v.y = y;
+                                                    // Try to parse and set size as well (optional)
+                                                    if let Some(sz) = size {
+                                                        v.size = sz;
+                                                    }
                                                 }
                                                 println!("Kundennummer Position manuell gesetzt: x={}, y={}", x, y);
                                             }
@@ -4329,7 +6099,7 @@ impl App for MyApp {
                                 
                                 ui.separator();
                                 ui.label("💡 Tipp: PDF-Koordinaten beginnen links-unten bei (0,0)");
-                                ui.label("📐 Referenz: DIN A4 = 595×842 Punkte");
+                                ui.label("📐 Referenz: DIN A4 = 595×842 Punkte, Ursprung links unten im sichtbaren Bereich (CropBox) der angezeigten Seite");
                             });
                             
                             ui.separator();
@@ -4338,8 +6108,8 @@ impl App for MyApp {
                             ui.horizontal(|ui| {
                                 if ui.button("💾 Speichern").on_hover_text("Speichert die aktuelle gruppenspezifische Config in die geladene Datei").clicked() {
                                     println!("=== SPEICHERN GEDRÜCKT ===");
-                                    println!("VOR Speichern - Config: QR={:?}, Vertreter={:?}", 
-                                            self.config.qr_codes, self.config.vertreter);
+                                    println!("VOR Speichern - Config: Elemente={:?}", 
+                                            self.config.elements);
                                     
                                     // Nur noch gruppenspezifische Config speichern (keine app_config.toml mehr!)
                                     set_current_config(&self.config);
@@ -4362,8 +6132,8 @@ impl App for MyApp {
                                     
                                     // Nach dem Speichern nochmal laden um sicherzustellen dass alles stimmt
                                     let loaded_config = load_group_config(&self.selected_group, &self.selected_language, self.is_messe);
-                                    println!("NACH Laden - Geladene gruppenspezifische Config: QR={:?}, Vertreter={:?}", 
-                                            loaded_config.qr_codes, loaded_config.vertreter);
+                                    println!("NACH Laden - Geladene gruppenspezifische Config: Elemente={:?}", 
+                                            loaded_config.elements);
                                     self.config = loaded_config;
                                     println!("=== SPEICHERN ABGESCHLOSSEN ===");
                                 }
@@ -4371,8 +6141,8 @@ impl App for MyApp {
                                     println!("=== ABBRECHEN GEDRÜCKT ===");
                                     // Gruppenspezifische Config komplett neu laden um alle Änderungen zu verwerfen
                                     self.config = load_group_config(&self.selected_group, &self.selected_language, self.is_messe);
-                                    println!("Abgebrochen - gruppenspezifische Config zurückgesetzt: QR={:?}, Vertreter={:?}", 
-                                            self.config.qr_codes, self.config.vertreter);
+                                    println!("Abgebrochen - gruppenspezifische Config zurückgesetzt: Elemente={:?}", 
+                                            self.config.elements);
                                     // Request close via the Cell so the final assignment below picks it up
                                     close_requested.set(true);
                                 }
@@ -4397,24 +6167,51 @@ impl App for MyApp {
                         ui.heading("Elemente verwalten");
                         ui.separator();
 
+                        link_template_ui(ui, &mut self.config);
+                        metadata_ui(ui, &mut self.config);
+                        encryption_ui(ui, &mut self.config);
+
                         ui.group(|ui| {
                             ui.label("QR-Codes:").on_hover_text("Verwalte QR-Code Marker, Größe und auf welchen Seiten sie erscheinen.");
                             ui.horizontal(|ui| {
                                 if ui.button("+ QR-Code hinzufügen").on_hover_text("Fügt einen neuen QR-Code Platzhalter hinzu").clicked() {
-                                    self.config.qr_codes.push(QrCodeConfig { x: 100.0, y: 100.0, size: 18.0, pages: vec![1], all_pages: false });
+                                    self.config.elements.push(Element::new(ElementKind::Qr(QrCodeConfig { x: 100.0, y: 100.0, size: 18.0, pages: vec![1], all_pages: false, ..Default::default() })));
                                 }
-                                if ui.button("- QR-Code entfernen").on_hover_text("Entfernt das zuletzt hinzugefügte QR-Code Feld").clicked() && !self.config.qr_codes.is_empty() {
-                                    self.config.qr_codes.pop();
+                                if ui.button("- QR-Code entfernen").on_hover_text("Entfernt das zuletzt hinzugefügte QR-Code Feld").clicked() {
+                                    self.config.remove_last_of("qr");
                                 }
                             });
 
-                            for (i, qr) in self.config.qr_codes.iter_mut().enumerate() {
+                            for (i, qr) in self.config.qr_codes_mut().enumerate() {
                                 ui.group(|ui| {
                                     ui.horizontal(|ui| {
                                         ui.label(format!("QR-Code {} Größe:", i + 1)).on_hover_text("Größe des QR-Codes in Punkt (pt). Größere Werte führen zu größeren QR-Codes im PDF.");
                                         ui.add(egui::Slider::new(&mut qr.size, 10.0..=50.0).suffix(" pt")).on_hover_text("Ziehe um die Größe des QR-Codes anzupassen (10–50 pt).");
                                     });
 
+                                    ui.horizontal(|ui| {
+                                        ui.label("Logo:").on_hover_text("PNG, JPEG oder PDF, das mittig im QR-Code erscheint. Der Code wird dann mit höchster Fehlerkorrektur erzeugt und auf Lesbarkeit geprüft.");
+                                        ui.add(egui::TextEdit::singleline(&mut qr.logo_path).hint_text("kein Logo").desired_width(150.0));
+                                        if ui.button("📂").on_hover_text("Logo-Datei auswählen").clicked() {
+                                            if let Some(path) = rfd::FileDialog::new().add_filter("Logo", &["png", "jpg", "jpeg", "pdf"]).pick_file() {
+                                                qr.logo_path = path.display().to_string();
+                                            }
+                                        }
+                                        if !qr.logo_path.is_empty() && ui.small_button("❌").on_hover_text("Logo entfernen").clicked() {
+                                            qr.logo_path.clear();
+                                        }
+                                    });
+                                    if !qr.logo_path.is_empty() {
+                                        ui.horizontal(|ui| {
+                                            ui.label("Logo-Größe:").on_hover_text("Kantenlänge des Logos relativ zum QR-Code. Größere Logos verdecken mehr Module.");
+                                            ui.add(egui::Slider::new(&mut qr.logo_size, 0.05..=0.30).step_by(0.01));
+                                        });
+                                    }
+
+                                    qr_payload_ui(ui, qr, &format!("window_{}", i));
+                                    qr_rotation_caption_ui(ui, qr, &format!("window_{}", i));
+                                    form_field_ui(ui, &mut qr.form_field);
+
                                     ui.horizontal(|ui| {
                                         ui.label("Seiten:").on_hover_text("Auf welchen Seiten des PDFs der QR-Code erscheinen soll.");
                                         if ui.checkbox(&mut qr.all_pages, "Alle Seiten").on_hover_text("Aktiviert diesen QR-Code für alle Seiten und versteckt die per-Seite Liste").clicked() {
@@ -4456,18 +6253,30 @@ impl App for MyApp {
 
                         ui.separator();
 
+                        barcodes_ui(ui, &mut self.config, "window");
+
+                        ui.separator();
+
+                        images_ui(ui, &mut self.config, "window");
+
+                        ui.separator();
+
+                        elements_ui(ui, &mut self.config, "window");
+
+                        ui.separator();
+
                         ui.group(|ui| {
                             ui.label("Vertreternummer-Felder:").on_hover_text("Verwaltet Platzhalter für Vertreternummern (Schrift, Größe, Seiten).");
                             ui.horizontal(|ui| {
                                 if ui.button("+ Feld hinzufügen").on_hover_text("Fügt ein neues Vertreter-Feld hinzu").clicked() {
-                                    self.config.vertreter.push(VertreterConfig { x: 100.0, y: 200.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string() });
+                                    self.config.elements.push(Element::new(ElementKind::Text(VertreterConfig { x: 100.0, y: 200.0, size: 12.0, pages: vec![1], all_pages: false, font_name: "Arial".to_string(), font_size: 12.0, font_style: "Normal".to_string(), ..Default::default() })));
                                 }
-                                if ui.button("- Feld entfernen").on_hover_text("Entfernt das zuletzt hinzugefügte Vertreter-Feld").clicked() && !self.config.vertreter.is_empty() {
-                                    self.config.vertreter.pop();
+                                if ui.button("- Feld entfernen").on_hover_text("Entfernt das zuletzt hinzugefügte Vertreter-Feld").clicked() {
+                                    self.config.remove_last_of("text");
                                 }
                             });
 
-                            for (i, v) in self.config.vertreter.iter_mut().enumerate() {
+                            for (i, v) in self.config.vertreter_mut().enumerate() {
                                 ui.group(|ui| {
                                     ui.label(format!("Vertreternummer Feld {}", i + 1)).on_hover_text("Einstellungen für dieses Feld (Position/Schrift/Seiten).");
                                     ui.horizontal(|ui| {
@@ -4482,6 +6291,9 @@ impl App for MyApp {
                                         ui.label("Schriftgröße:");
                                         ui.add(egui::Slider::new(&mut v.font_size, 6.0..=48.0).suffix(" pt")).on_hover_text("Textgröße in Punkten für die Vertreternummer.");
                                     });
+                                    vertreter_layout_ui(ui, v, &format!("window_{}", i));
+                                    vertreter_text_style_ui(ui, v);
+                                    form_field_ui(ui, &mut v.form_field);
 
                                     ui.horizontal(|ui| {
                                         ui.label("Seiten:").on_hover_text("Auf welchen Seiten dieses Feld erscheinen soll.");
@@ -4569,6 +6381,19 @@ impl App for MyApp {
                     .show(ctx, |ui| {
                         ui.vertical_centered(|ui| {
                             ui.label("Die PDFs wurden erfolgreich erstellt!");
+                            if let Ok(report) = self.run_report.try_lock() {
+                                let (created, verified, skipped, failed) = report.summary();
+                                ui.label(format!("{} erstellt ({} mit geprüften QR-Codes), {} übersprungen", created, verified, skipped));
+                                if report.print_output_problems() > 0 {
+                                    ui.colored_label(egui::Color32::RED, format!("⚠ {} Druckdatei(en)/Druckbogen nicht erstellt - siehe Laufbericht", report.print_output_problems()));
+                                }
+                                if report.noted() > 0 {
+                                    ui.colored_label(egui::Color32::from_rgb(200, 150, 0), format!("ℹ {} mit Hinweisen (z.B. QR-Code ohne Logo) - siehe Laufbericht", report.noted()));
+                                }
+                                if failed > 0 {
+                                    ui.colored_label(egui::Color32::RED, format!("⚠ {} fehlgeschlagen - siehe Laufbericht im Output-Ordner", failed));
+                                }
+                            }
                         });
                     });
             }
@@ -4605,465 +6430,1816 @@ impl App for MyApp {
     }
 }
 
-fn read_vertreter(file_path: &str) -> Vec<(String, String, String)> {
-    debug_print_global(&format!("Versuche CSV zu lesen: {}", file_path));
-    
-    let content = match fs::read_to_string(file_path) {
-        Ok(content) => {
-                debug_print_global(&format!("CSV erfolgreich gelesen, {} Zeichen", content.len()));
-            content
-        },
-        Err(e) => {
-            println!("ERROR: CSV konnte nicht gelesen werden: {}", e);
-            return Vec::new(); // Leere Liste statt Panic
-        }
-    };
+// Werte ohne Kodierung einsetzen (vCard/MeCard/EPC escapen selbst)
+fn no_encoding(value: &str) -> String {
+    value.to_string()
+}
 
-    // Ermitteln des Trennzeichens: wenn eine Zeile ';' enthält, priorisiere ';',
-    // sonst benutze ',' als Fallback. Falls beides vorkommt, wähle das häufiger
-    // vorkommende Zeichen in der ersten nicht-leeren Zeile.
-    let mut delimiter = ',';
-    for line in content.lines() {
-        let l = line.trim();
-        if l.is_empty() { continue; }
-        let semi = l.matches(';').count();
-        let comma = l.matches(',').count();
-        if semi > 0 || comma > 0 {
-            delimiter = if semi >= comma { ';' } else { ',' };
-            break;
+// Felder einer QR-Config mit den Zeilenwerten füllen; leere Ergebnisse werden weggelassen
+fn fill_payload_fields(qr: &QrCodeConfig, vars: &std::collections::HashMap<String, String>) -> Result<std::collections::BTreeMap<String, String>, String> {
+    let mut filled = std::collections::BTreeMap::new();
+    for (key, template) in &qr.payload_fields {
+        let value = fill_placeholders(template, vars, no_encoding)?;
+        let value = value.trim();
+        if !value.is_empty() {
+            filled.insert(key.to_lowercase(), value.to_string());
         }
     }
+    Ok(filled)
+}
 
-    let mut lines = content.lines();
-    
-    // Header überspringen (erste Zeile)
-        if let Some(header) = lines.next() {
-        debug_print_global(&format!("Header übersprungen: {}", header.trim()));
-    }
+// Sonderzeichen für vCard-Werte escapen (RFC 6350)
+fn vcard_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(',', "\\,").replace(';', "\\;").replace('\n', "\\n")
+}
 
-    lines
-        .filter_map(|line| {
-            let l = line.trim();
-            if l.is_empty() { return None; }
-            let parts: Vec<&str> = l.split(delimiter).collect();
-            if parts.len() >= 3 {
-                let vertreternr = parts[0].trim();
-                let de_link = parts[1].trim();
-                let en_link = parts[2].trim();
-                if !vertreternr.is_empty() && !de_link.is_empty() && !en_link.is_empty() {
-                    // Vertreternummer auf 4 Stellen formatieren (führende Nullen)
-                    if let Ok(num) = vertreternr.parse::<u32>() {
-                        let formatted_nr = if num >= 10000 {
-                            num.to_string() // Zahlen >= 10000 bleiben unverändert
-                        } else {
-                            format!("{:04}", num) // Zahlen < 10000 werden auf 4 Stellen aufgefüllt
-                        };
-                        return Some((formatted_nr, de_link.to_string(), en_link.to_string()));
-                    }
-                }
-            } else if parts.len() >= 2 {
-                // Fallback für alte CSV-Struktur (nur 2 Spalten)
-                let vertreternr = parts[0].trim();
-                let link = parts[1].trim();
-                if !vertreternr.is_empty() && !link.is_empty() {
-                    if let Ok(num) = vertreternr.parse::<u32>() {
-                        let formatted_nr = if num >= 10000 {
-                            num.to_string()
-                        } else {
-                            format!("{:04}", num)
-                        };
-                        return Some((formatted_nr, link.to_string(), link.to_string())); // Gleicher Link für beide Sprachen
-                    }
-                }
-            }
-            None
-        })
-        .collect()
+// Sonderzeichen für MeCard-Werte escapen
+fn mecard_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(';', "\\;").replace(':', "\\:").replace(',', "\\,").replace('"', "\\\"")
 }
 
-// Erzeuge ein kleines eingebettetes Icon (blockiges "B") als RGBA-Pixel-Array.
-// So hat die Anwendung auch ohne externe Datei ein eigenes Icon in der Taskleiste.
-#[allow(dead_code)]
-fn make_app_icon() -> egui::IconData {
-    let width: usize = 32;
-    let height: usize = 32;
-    let mut rgba = vec![0u8; width * height * 4];
+// Vor- und Nachname aus den Feldern bestimmen (explizite Felder oder letzter Teil von "name")
+fn split_contact_name(fields: &std::collections::BTreeMap<String, String>) -> (String, String) {
+    let first = fields.get("first_name").cloned().unwrap_or_default();
+    let last = fields.get("last_name").cloned().unwrap_or_default();
+    if !first.is_empty() || !last.is_empty() {
+        return (first, last);
+    }
+    let full = fields.get("name").map(|n| n.trim()).unwrap_or("");
+    match full.rsplit_once(' ') {
+        Some((first, last)) => (first.trim().to_string(), last.trim().to_string()),
+        None => (String::new(), full.to_string()),
+    }
+}
 
-    // Hintergrund: Orange-ish
-    for y in 0..height {
-        for x in 0..width {
-            let i = (y * width + x) * 4;
-            rgba[i + 0] = 255; // R
-            rgba[i + 1] = 140; // G
-            rgba[i + 2] = 0;   // B
-            rgba[i + 3] = 255; // A
+/// vCard 3.0 für den Vertreter-Kontakt
+fn build_vcard(fields: &std::collections::BTreeMap<String, String>) -> Result<String, String> {
+    let (first, last) = split_contact_name(fields);
+    let full_name = fields.get("name").cloned().unwrap_or_else(|| format!("{} {}", first, last).trim().to_string());
+    if full_name.is_empty() {
+        return Err("vCard benötigt einen Namen (Feld name oder first_name/last_name)".to_string());
+    }
+    let mut card = String::from("BEGIN:VCARD\r\nVERSION:3.0\r\n");
+    card.push_str(&format!("N:{};{};;;\r\n", vcard_escape(&last), vcard_escape(&first)));
+    card.push_str(&format!("FN:{}\r\n", vcard_escape(&full_name)));
+    let props = [
+        ("org", "ORG"),
+        ("title", "TITLE"),
+        ("phone", "TEL;TYPE=WORK,VOICE"),
+        ("mobile", "TEL;TYPE=CELL"),
+        ("email", "EMAIL;TYPE=INTERNET"),
+        ("url", "URL"),
+        ("note", "NOTE"),
+    ];
+    for (key, prop) in props {
+        if let Some(value) = fields.get(key) {
+            card.push_str(&format!("{}:{}\r\n", prop, vcard_escape(value)));
         }
     }
+    card.push_str("END:VCARD");
+    Ok(card)
+}
 
-    // Zeichne ein blockiges 'B' in Weiß (einfach aus Rechtecken zusammengesetzt)
-    // Vertikale Hauptlinie
-    for y in 6..26 {
-        for x in 6..10 {
-            let i = (y * width + x) * 4;
-            rgba[i + 0] = 255; rgba[i + 1] = 255; rgba[i + 2] = 255; rgba[i + 3] = 255;
-        }
+/// MeCard (kompakter als vCard, ergibt kleinere QR-Codes)
+fn build_mecard(fields: &std::collections::BTreeMap<String, String>) -> Result<String, String> {
+    let (first, last) = split_contact_name(fields);
+    if first.is_empty() && last.is_empty() {
+        return Err("MeCard benötigt einen Namen (Feld name oder first_name/last_name)".to_string());
     }
-    // Oberer B-Bogen
-    for y in 6..14 {
-        for x in 10..22 {
-            let i = (y * width + x) * 4;
-            rgba[i + 0] = 255; rgba[i + 1] = 255; rgba[i + 2] = 255; rgba[i + 3] = 255;
+    let mut card = if first.is_empty() {
+        format!("MECARD:N:{};", mecard_escape(&last))
+    } else {
+        format!("MECARD:N:{},{};", mecard_escape(&last), mecard_escape(&first))
+    };
+    let props = [("org", "ORG"), ("phone", "TEL"), ("mobile", "TEL"), ("email", "EMAIL"), ("url", "URL"), ("note", "NOTE")];
+    for (key, prop) in props {
+        if let Some(value) = fields.get(key) {
+            card.push_str(&format!("{}:{};", prop, mecard_escape(value)));
         }
     }
-    // Unterer B-Bogen
-    for y in 18..26 {
-        for x in 10..22 {
-            let i = (y * width + x) * 4;
-            rgba[i + 0] = 255; rgba[i + 1] = 255; rgba[i + 2] = 255; rgba[i + 3] = 255;
+    card.push(';');
+    Ok(card)
+}
+
+/// mailto-Link für eine Bestell-E-Mail (Betreff enthält typischerweise `{kundennr}`)
+fn build_mailto(fields: &std::collections::BTreeMap<String, String>) -> Result<String, String> {
+    let to = fields.get("to").ok_or("mailto benötigt eine Empfängeradresse (Feld to)")?;
+    let mut params = Vec::new();
+    for key in ["cc", "subject", "body"] {
+        if let Some(value) = fields.get(key) {
+            params.push(format!("{}={}", key, url_encode_component(value)));
         }
     }
+    let mut uri = format!("mailto:{}", to.replace(' ', ""));
+    if !params.is_empty() {
+        uri.push('?');
+        uri.push_str(&params.join("&"));
+    }
+    Ok(uri)
+}
 
-    egui::IconData { rgba, width: width as _, height: height as _ }
+/// Strukturierte Gläubigerreferenz nach ISO 11649 (`RF` + 2 Prüfziffern + bis zu 21 Zeichen, Mod 97)
+fn is_valid_creditor_reference(reference: &str) -> bool {
+    if reference.len() < 5 || reference.len() > 25 || !reference.starts_with("RF") || !reference.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return false;
+    }
+    let rearranged = format!("{}{}", &reference[4..], &reference[..4]);
+    let remainder = rearranged.chars().fold(0u32, |acc, c| {
+        let value = c.to_digit(36).unwrap_or(0);
+        if value < 10 { (acc * 10 + value) % 97 } else { (acc * 100 + value) % 97 }
+    });
+    remainder == 1
 }
 
-fn generate_qr(link: &str) -> (Vec<u8>, usize) {
-    let code = QrCode::new(link).expect("Konnte QR-Code nicht generieren");
-    let matrix: String = code
-        .render::<char>()
-        .quiet_zone(false)
-        .module_dimensions(1, 1)
-        .build(); // String
-    let height = matrix.lines().count();
-    let width = if height > 0 { matrix.lines().next().unwrap().chars().count() } else { 0 };
-    let mut data = Vec::with_capacity(width * height);
-    for row in matrix.lines() {
-        for c in row.chars() {
-            data.push(if c == '█' { 0u8 } else { 255u8 });
+/// EPC-QR-Code (GiroCode) nach EPC069-12, Version 002, UTF-8
+fn build_epc(fields: &std::collections::BTreeMap<String, String>) -> Result<String, String> {
+    let field = |key: &str| fields.get(key).map(|s| s.trim()).unwrap_or("");
+    let name = field("name");
+    if name.is_empty() || name.chars().count() > 70 {
+        return Err("EPC benötigt einen Empfängernamen mit höchstens 70 Zeichen (Feld name)".to_string());
+    }
+    let iban: String = field("iban").chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase();
+    if iban.len() < 15 || iban.len() > 34 || !iban.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!("EPC: ungültige IBAN '{}'", iban));
+    }
+    let bic = field("bic").replace(' ', "").to_uppercase();
+    if !bic.is_empty() && bic.len() != 8 && bic.len() != 11 {
+        return Err(format!("EPC: ungültige BIC '{}'", bic));
+    }
+    let amount = match fields.get("amount") {
+        Some(raw) => {
+            let value: f64 = raw.replace(',', ".").trim().parse().map_err(|_| format!("EPC: ungültiger Betrag '{}'", raw))?;
+            if !(0.01..=999_999_999.99).contains(&value) {
+                return Err(format!("EPC: Betrag {} außerhalb von 0,01 bis 999999999,99", value));
+            }
+            format!("EUR{:.2}", value)
         }
+        None => String::new(),
+    };
+    let purpose = field("purpose");
+    if purpose.len() > 4 {
+        return Err(format!("EPC: Purpose-Code '{}' darf höchstens 4 Zeichen haben", purpose));
     }
-    (data, width)
+    let reference = field("reference").replace(' ', "").to_uppercase();
+    let text = field("text");
+    if !reference.is_empty() && !text.is_empty() {
+        return Err("EPC: Verwendungszweck entweder als reference oder als text angeben, nicht beides".to_string());
+    }
+    if !reference.is_empty() && !is_valid_creditor_reference(&reference) {
+        return Err(format!("EPC: '{}' ist keine gültige RF-Referenz nach ISO 11649 (freien Verwendungszweck als text angeben)", reference));
+    }
+    if text.chars().count() > 140 {
+        return Err("EPC: Verwendungszweck zu lang (text max. 140 Zeichen)".to_string());
+    }
+
+    let payload = [
+        "BCD", "002", "1", "SCT",
+        bic.as_str(), name, iban.as_str(), amount.as_str(),
+        purpose, reference.as_str(), text,
+    ].join("\n");
+    if payload.len() > 331 {
+        return Err(format!("EPC: Inhalt mit {} Bytes überschreitet 331 Bytes", payload.len()));
+    }
+    Ok(payload.trim_end_matches('\n').to_string())
 }
 
-// Globale Variable für die aktuelle Config (wird von UI gesetzt)
-static mut CURRENT_CONFIG: Option<Config> = None;
-static CONFIG_MUTEX: std::sync::Mutex<()> = std::sync::Mutex::new(());
+/// Inhalt eines QR-Elements für eine Zeile erzeugen. `link` ist der bereits aufgelöste
+/// Link (CSV oder Link-Vorlage) und wird nur für `QrPayloadType::Link` benötigt.
+fn build_qr_payload(qr: &QrCodeConfig, link: Option<&str>, vars: &std::collections::HashMap<String, String>) -> Result<String, String> {
+    match qr.payload {
+        QrPayloadType::Link => link
+            .map(|l| l.to_string())
+            .ok_or_else(|| "Kein Link (CSV-Spalte leer, keine Link-Vorlage)".to_string()),
+        QrPayloadType::VCard => build_vcard(&fill_payload_fields(qr, vars)?),
+        QrPayloadType::MeCard => build_mecard(&fill_payload_fields(qr, vars)?),
+        QrPayloadType::Mailto => build_mailto(&fill_payload_fields(qr, vars)?),
+        QrPayloadType::Epc => build_epc(&fill_payload_fields(qr, vars)?),
+    }
+}
 
-// Track the last-loaded config file path in a safe Mutex for multi-threaded access
-use once_cell::sync::Lazy;
-static CURRENT_CONFIG_PATH: Lazy<std::sync::Mutex<Option<std::path::PathBuf>>> = Lazy::new(|| std::sync::Mutex::new(None));
-// Recent config list (strings) for quick access in UI
-static RECENT_CONFIGS: Lazy<std::sync::Mutex<Vec<String>>> = Lazy::new(|| std::sync::Mutex::new(Vec::new()));
+/// QR-Inhalte aller QR-Elemente einer Config für eine Zeile erzeugen (parallel zu `config.qr_codes()`)
+/// `ui_link` ist der nach UI-Sprache gewählte CSV-Link (für `QrLinkSource::Auto`).
+fn build_row_qr_payloads(config: &Config, record: &VertreterRecord, ui_link: &str, vars: &std::collections::HashMap<String, String>) -> Result<Vec<Option<String>>, String> {
+    config.qr_codes()
+        .map(|qr| {
+            let link = if qr.payload == QrPayloadType::Link { resolve_element_link(config, qr, record, ui_link, vars)? } else { None };
+            build_qr_payload(qr, link.as_deref(), vars).map(Some)
+        })
+        .collect()
+}
 
-// Funktion um die aktuelle Config zu setzen (threadsafe)
-fn set_current_config(config: &Config) {
-    let _lock = CONFIG_MUTEX.lock().unwrap();
-    unsafe {
-        CURRENT_CONFIG = Some(config.clone());
+// Link eines einzelnen QR-Elements nach seiner Linkquelle bestimmen. Bei den Sprachspalten
+// werden `{lang}`/`{lang_code}` für eine eventuelle Ersatz-Vorlage auf die Spaltensprache gesetzt.
+fn resolve_element_link(config: &Config, qr: &QrCodeConfig, record: &VertreterRecord, ui_link: &str, vars: &std::collections::HashMap<String, String>) -> Result<Option<String>, String> {
+    let with_lang = |lang: &str, lang_code: &str| {
+        let mut vars = vars.clone();
+        vars.insert("lang".to_string(), lang.to_string());
+        vars.insert("lang_code".to_string(), lang_code.to_string());
+        vars
+    };
+    match qr.link_source {
+        QrLinkSource::Auto => resolve_qr_link(config, ui_link, vars),
+        QrLinkSource::German => resolve_qr_link(config, &record.de_link, &with_lang("de", "de_de")),
+        QrLinkSource::English => resolve_qr_link(config, &record.en_link, &with_lang("en", "en_us")),
+        QrLinkSource::Column => {
+            if qr.link_column.trim().is_empty() {
+                return Err("Linkquelle 'Eigene CSV-Spalte' ohne Spaltennamen".to_string());
+            }
+            let link = record.column(&qr.link_column)
+                .ok_or_else(|| format!("CSV-Spalte '{}' nicht vorhanden", qr.link_column))?;
+            resolve_qr_link(config, link, vars)
+        }
+        QrLinkSource::Template => {
+            let template = if qr.link_template.trim().is_empty() { config.link_template.trim() } else { qr.link_template.trim() };
+            if template.is_empty() {
+                return Err("Linkquelle 'Link-Vorlage' ohne Vorlage".to_string());
+            }
+            fill_link_template(template, vars).map(Some)
+        }
     }
-    println!("Aktuelle Config gesetzt für PDF-Generierung: QR={:?}, Vertreter={:?}", 
-             config.qr_codes, config.vertreter);
 }
 
-fn set_current_config_path(path: &std::path::Path) {
-    let mut g = CURRENT_CONFIG_PATH.lock().unwrap();
-    *g = Some(path.to_path_buf());
-    println!("Aktuelle Config-Datei gesetzt: {:?}", path);
-    // Add to recent list
-    add_recent_config(path);
+/// Strichcode-Inhalte aller Strichcode-Elemente einer Config für eine Zeile (parallel zu `config.barcodes()`)
+fn build_row_barcode_contents(config: &Config, vars: &std::collections::HashMap<String, String>) -> Result<Vec<String>, String> {
+    config.barcodes()
+        .map(|b| fill_placeholders(&b.content, vars, no_encoding).map(|c| c.trim().to_string()))
+        .collect()
 }
 
-fn get_current_config_path() -> Option<std::path::PathBuf> {
-    let g = CURRENT_CONFIG_PATH.lock().unwrap();
-    g.clone()
+/// Bildpfade aller Bild-Elemente einer Config für eine Zeile (parallel zu `config.images()`, leer = kein Bild)
+fn build_row_image_paths(config: &Config, vars: &std::collections::HashMap<String, String>) -> Result<Vec<String>, String> {
+    config.images()
+        .map(|img| fill_placeholders(&img.path, vars, no_encoding).map(|p| p.trim().to_string()))
+        .collect()
 }
 
-fn add_recent_config(path: &std::path::Path) {
-    let s = path.display().to_string();
-    let mut list = RECENT_CONFIGS.lock().unwrap();
-    // Remove existing equal entries
-    list.retain(|x| x != &s);
-    // Insert at front
-    list.insert(0, s);
-    // Cap at 12 entries
-    if list.len() > 12 {
-        list.truncate(12);
+/// Ausgegebene Texte aller Vertreternummer-Felder einer Config für eine Zeile (parallel zu `config.vertreter()`)
+fn build_row_vertreter_texts(config: &Config, vars: &std::collections::HashMap<String, String>) -> Result<Vec<String>, String> {
+    config.vertreter()
+        .map(|v| fill_placeholders(&v.format, vars, no_encoding))
+        .collect()
+}
+
+/// Für eine Zeile gefüllte Dokument-Metadaten (leere Felder werden nicht geschrieben)
+struct PdfMetadata {
+    title: String,
+    subject: String,
+    author: String,
+    keywords: String,
+    /// Eigene Info-Schlüssel in Schreibreihenfolge
+    custom: Vec<(String, String)>,
+}
+
+// Metadaten einer Zeile aus den Mustern der Config füllen; die festen Schlüssel (Vertreternummer,
+// Gruppe, Sprache, Vorlagen-Prüfsumme, Generator) kommen vor den eigenen Schlüsseln
+fn build_row_metadata(config: &Config, vars: &std::collections::HashMap<String, String>, template_hash: &str) -> Result<PdfMetadata, String> {
+    let m = &config.metadata;
+    let fill = |pattern: &str| fill_placeholders(pattern, vars, no_encoding).map(|v| v.trim().to_string());
+    let var = |name: &str| vars.get(name).cloned().unwrap_or_default();
+    let mut custom = vec![
+        ("Vertreternummer".to_string(), var("vertreternummer")),
+        ("Gruppe".to_string(), var("group")),
+        ("Sprache".to_string(), var("lang_code")),
+        ("VorlageSHA256".to_string(), template_hash.to_string()),
+        ("Generator".to_string(), generator_name()),
+    ];
+    for (key, pattern) in &m.custom {
+        custom.push((key.clone(), fill(pattern).map_err(|e| format!("Metadaten '{}': {}", key, e))?));
     }
+    Ok(PdfMetadata {
+        title: fill(&m.title).map_err(|e| format!("Metadaten-Titel: {}", e))?,
+        subject: fill(&m.subject).map_err(|e| format!("Metadaten-Betreff: {}", e))?,
+        author: fill(&m.author).map_err(|e| format!("Metadaten-Autor: {}", e))?,
+        keywords: fill(&m.keywords).map_err(|e| format!("Metadaten-Stichwörter: {}", e))?,
+        custom,
+    })
 }
 
-fn get_recent_config_list() -> Vec<String> {
-    let list = RECENT_CONFIGS.lock().unwrap();
-    list.clone()
+// Name und Version des Programms für /Producer, /Generator und xmp:CreatorTool
+fn generator_name() -> String {
+    format!("Bestellschein Generator {}", env!("CARGO_PKG_VERSION"))
 }
 
-// Return last-modified timestamp string for a file, if available
-fn file_modified_time_str(path: &std::path::Path) -> Option<String> {
-    if let Ok(md) = std::fs::metadata(path) {
-        if let Ok(system_time) = md.modified() {
-            // Convert SystemTime -> chrono DateTime using local timezone
-            let dt: chrono::DateTime<chrono::Local> = chrono::DateTime::from(system_time);
-            return Some(dt.format("%Y-%m-%d %H:%M:%S").to_string());
+/// Kodierter Strichcode: Modul-Matrix (0 = dunkel, 255 = hell) und Klartext
+struct BarcodeMatrix {
+    data: Vec<u8>,
+    width: usize,
+    height: usize,
+    text: String,
+}
+
+// Strichcode mit rxing (Pure-Rust-Port von ZXing) erzeugen. 1D-Codes bestehen aus einer
+// einzigen Zeile inkl. Ruhezone und werden beim Zeichnen auf die Strichhöhe gestreckt.
+fn encode_barcode(symbology: BarcodeSymbology, content: &str) -> Result<BarcodeMatrix, String> {
+    use rxing::Writer;
+    if content.is_empty() {
+        return Err(format!("{}: Inhalt ist leer", symbology.label()));
+    }
+    let (format, text) = match symbology {
+        BarcodeSymbology::Code128 => (rxing::BarcodeFormat::CODE_128, content.to_string()),
+        BarcodeSymbology::Ean13 => (rxing::BarcodeFormat::EAN_13, ean13_with_check_digit(content)?),
+        BarcodeSymbology::DataMatrix => (rxing::BarcodeFormat::DATA_MATRIX, content.to_string()),
+        BarcodeSymbology::Pdf417 => (rxing::BarcodeFormat::PDF_417, content.to_string()),
+    };
+    let matrix = rxing::MultiFormatWriter
+        .encode(&text, &format, 0, 0)
+        .map_err(|e| format!("{} für '{}' konnte nicht erzeugt werden: {}", symbology.label(), content, e))?;
+    let width = matrix.getWidth() as usize;
+    let height = matrix.getHeight() as usize;
+    let mut data = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            data.push(if matrix.get(x as u32, y as u32) { 0u8 } else { 255u8 });
         }
     }
-    None
+    Ok(BarcodeMatrix { data, width, height, text })
 }
 
-// Determine a simple source label for a config path
-fn detect_config_source(path: &std::path::Path) -> &'static str {
-    let s = path.display().to_string().to_lowercase();
-    if s.contains("config/presets") || s.contains("config\\presets") { "preset" }
-    else if s.contains("config_messe") || s.contains("messe") { "messe" }
-    else if s.contains("config_") || s.contains("config-") { "group" }
-    else { "unknown" }
+// EAN-13: 12 Ziffern bekommen die Prüfziffer angehängt, bei 13 Ziffern wird sie geprüft
+fn ean13_with_check_digit(content: &str) -> Result<String, String> {
+    let digits: String = content.chars().filter(|c| !c.is_whitespace()).collect();
+    if !(digits.len() == 12 || digits.len() == 13) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("EAN-13 benötigt 12 oder 13 Ziffern, erhalten: '{}'", content));
+    }
+    let sum: u32 = digits.bytes().take(12).enumerate()
+        .map(|(i, b)| (b - b'0') as u32 * if i % 2 == 0 { 1 } else { 3 })
+        .sum();
+    let check = (10 - sum % 10) % 10;
+    if digits.len() == 13 && (digits.as_bytes()[12] - b'0') as u32 != check {
+        return Err(format!("EAN-13 '{}' hat eine falsche Prüfziffer (erwartet {})", digits, check));
+    }
+    Ok(format!("{}{}", &digits[..12], check))
 }
 
-// Load a config from an arbitrary path (used by recent-list selection / save-as results)
-fn load_config_from_path(path: &std::path::Path) -> Option<Config> {
-    // Use extension-aware loader to support both TOML and YAML configs
-    match try_load_config_file(path) {
-        Some(cfg) => Some(cfg),
-        None => {
-            eprintln!("Konnte Config-Datei {:?} nicht lesen oder parsen", path);
-            None
+// Ausrichtung, Bezugspunkt und Drehung eines Vertreternummer-Feldes (beide "Elemente verwalten"-Ansichten)
+fn vertreter_layout_ui(ui: &mut egui::Ui, v: &mut VertreterConfig, id_salt: &str) {
+    ui.horizontal(|ui| {
+        ui.label("Ausrichtung:").on_hover_text("Bezogen auf die X-Position; rechtsbündig bleibt bei wechselnder Nummernlänge am rechten Rand");
+        for align in TextAlign::ALL {
+            ui.selectable_value(&mut v.align, align, align.label());
         }
-    }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Bezugspunkt Y:");
+        egui::ComboBox::from_id_source(format!("vertreter_anchor_{}", id_salt))
+            .selected_text(v.anchor.label())
+            .show_ui(ui, |ui| {
+                for anchor in TextAnchor::ALL {
+                    ui.selectable_value(&mut v.anchor, anchor, anchor.label());
+                }
+            });
+        ui.label("Drehung:");
+        ui.add(egui::DragValue::new(&mut v.rotation).clamp_range(-360.0..=360.0).speed(1.0).suffix("°"));
+    });
 }
 
-// Try loading a config file and detect format by extension (yaml/yml -> YAML; else TOML fallback)
-fn try_load_config_file(path: &std::path::Path) -> Option<Config> {
-    match std::fs::read_to_string(path) {
-        Ok(content) => {
-            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
-            if ext == "yaml" || ext == "yml" || content.trim_start().starts_with("---") {
-                match serde_yaml::from_str::<Config>(&content) {
-                    Ok(cfg) => {
-                        set_current_config_path(path);
-                        println!("✅ YAML-Config geladen von {:?}", path);
-                        return Some(cfg);
+// Textformat, Farbe und Abstände eines Vertreternummer-Feldes (beide "Elemente verwalten"-Ansichten)
+fn vertreter_text_style_ui(ui: &mut egui::Ui, v: &mut VertreterConfig) {
+    ui.horizontal(|ui| {
+        ui.label("Text:").on_hover_text("Platzhalter wie {kundennr} oder CSV-Spaltennamen, z.B. 'Vertreter-Nr.: {kundennr}'. Die Configs sind je Sprache getrennt.");
+        ui.text_edit_singleline(&mut v.format);
+        ui.label("Farbe:");
+        ui.color_edit_button_srgb(&mut v.color);
+    });
+    ui.horizontal(|ui| {
+        ui.label("Zeichenabstand:").on_hover_text("Zusätzlicher Abstand nach jedem Zeichen, z.B. für Kästchenfelder");
+        ui.add(egui::DragValue::new(&mut v.char_spacing).clamp_range(-5.0..=50.0).speed(0.1).suffix(" pt"));
+        ui.label("Wortabstand:");
+        ui.add(egui::DragValue::new(&mut v.word_spacing).clamp_range(-5.0..=50.0).speed(0.1).suffix(" pt"));
+        ui.label("Breite:").on_hover_text("Horizontale Skalierung, 100 % = unverändert");
+        ui.add(egui::DragValue::new(&mut v.horizontal_scaling).clamp_range(10.0..=300.0).speed(1.0).suffix(" %"));
+    });
+}
+
+// Größte zulässige Seitenkante einer PDF (200 Zoll) als Obergrenze für Koordinaten-Eingaben
+const PDF_MAX_PAGE_SIZE: f32 = 14400.0;
+
+// Strichcode-Elemente verwalten (wird in beiden "Elemente verwalten"-Ansichten angezeigt)
+fn barcodes_ui(ui: &mut egui::Ui, config: &mut Config, id_salt: &str) {
+    ui.group(|ui| {
+        ui.label("Strichcodes:").on_hover_text("Code 128, EAN-13, DataMatrix oder PDF417, z.B. für die Vertreternummer am Handscanner.");
+        ui.horizontal(|ui| {
+            if ui.button("+ Strichcode hinzufügen").clicked() {
+                config.elements.push(Element::new(ElementKind::Barcode(BarcodeConfig::default())));
+            }
+            if ui.button("- Strichcode entfernen").clicked() {
+                config.remove_last_of("barcode");
+            }
+        });
+
+        for (i, barcode) in config.barcodes_mut().enumerate() {
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Strichcode {}:", i + 1));
+                    egui::ComboBox::from_id_source(format!("barcode_symbology_{}_{}", id_salt, i))
+                        .selected_text(barcode.symbology.label())
+                        .show_ui(ui, |ui| {
+                            for symbology in BarcodeSymbology::ALL {
+                                ui.selectable_value(&mut barcode.symbology, symbology, symbology.label());
+                            }
+                        });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Inhalt:");
+                    ui.text_edit_singleline(&mut barcode.content)
+                        .on_hover_text("Platzhalter wie in Link-Vorlagen, z.B. {kundennr} oder CSV-Spaltennamen");
+                });
+                if barcode.symbology == BarcodeSymbology::Ean13 {
+                    ui.small("EAN-13 nur für 12- oder 13-stellige Ziffernfolgen; die Prüfziffer wird ergänzt bzw. geprüft.");
+                }
+                ui.horizontal(|ui| {
+                    ui.label("X:");
+                    ui.add(egui::DragValue::new(&mut barcode.x).clamp_range(0.0..=PDF_MAX_PAGE_SIZE).suffix(" pt"));
+                    ui.label("Y:");
+                    ui.add(egui::DragValue::new(&mut barcode.y).clamp_range(0.0..=PDF_MAX_PAGE_SIZE).suffix(" pt"));
+                });
+                ui.horizontal(|ui| {
+                    ui.label(if barcode.symbology.is_square() { "Größe:" } else { "Breite:" });
+                    ui.add(egui::Slider::new(&mut barcode.size, 20.0..=300.0).suffix(" pt"));
+                });
+                if !barcode.symbology.is_square() {
+                    ui.horizontal(|ui| {
+                        ui.label("Höhe:");
+                        ui.add(egui::Slider::new(&mut barcode.bar_height, 5.0..=120.0).suffix(" pt"));
+                    });
+                }
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut barcode.show_text, "Klartext darunter");
+                    if barcode.show_text {
+                        ui.add(egui::DragValue::new(&mut barcode.text_size).clamp_range(4.0..=24.0).suffix(" pt"));
                     }
-                    Err(e) => {
-                        eprintln!("Fehler beim Parsen der YAML-Config {:?}: {}", path, e);
-                        // fall through to try TOML as a last resort
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Seiten:");
+                    if ui.checkbox(&mut barcode.all_pages, "Alle Seiten").clicked() {
+                        if barcode.all_pages {
+                            barcode.pages.clear();
+                        } else if barcode.pages.is_empty() {
+                            barcode.pages.push(1);
+                        }
                     }
+                });
+                if !barcode.all_pages {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label("Seiten:");
+                        let mut pages_to_remove = Vec::new();
+                        let pages_clone = barcode.pages.clone();
+                        for (pi, page_val) in pages_clone.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                let mut page_mut = *page_val;
+                                if ui.add(egui::DragValue::new(&mut page_mut).clamp_range(1..=100).prefix("S")).changed() {
+                                    barcode.pages[pi] = page_mut;
+                                }
+                                if ui.small_button("❌").clicked() && barcode.pages.len() > 1 {
+                                    pages_to_remove.push(pi);
+                                }
+                            });
+                        }
+                        for &pi in pages_to_remove.iter().rev() { barcode.pages.remove(pi); }
+                        if ui.small_button("+ Seite").clicked() {
+                            let new_page = barcode.pages.iter().max().unwrap_or(&0) + 1;
+                            barcode.pages.push(new_page);
+                            barcode.pages.sort();
+                        }
+                    });
                 }
-            }
-            // Fallback: try TOML parser used by the project
-            let cfg = parse_toml_to_config(&content);
-            set_current_config_path(path);
-            println!("✅ TOML-Config geladen von {:?}", path);
-            Some(cfg)
+            });
         }
-        Err(e) => {
-            eprintln!("Konnte Config-Datei {:?} nicht lesen: {}", path, e);
-            None
+    });
+}
+
+// Bild-Elemente verwalten (wird in beiden "Elemente verwalten"-Ansichten angezeigt)
+fn images_ui(ui: &mut egui::Ui, config: &mut Config, id_salt: &str) {
+    ui.group(|ui| {
+        ui.label("Bilder:").on_hover_text("Logos, Unterschriften oder Stempel aus PNG (mit Transparenz), JPEG oder einseitiger PDF.");
+        ui.horizontal(|ui| {
+            if ui.button("+ Bild hinzufügen").clicked() {
+                config.elements.push(Element::new(ElementKind::Image(ImageConfig::default())));
+            }
+            if ui.button("- Bild entfernen").clicked() {
+                config.remove_last_of("image");
+            }
+        });
+
+        for (i, image) in config.images_mut().enumerate() {
+            ui.group(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Bild {}:", i + 1));
+                    ui.add(egui::TextEdit::singleline(&mut image.path).hint_text("Pfad, z.B. Data/{Vertreter}.png").desired_width(180.0))
+                        .on_hover_text("Platzhalter wie in Link-Vorlagen, z.B. eine CSV-Spalte mit dem Dateinamen. Leerer Pfad = kein Bild für diese Zeile. Relative Pfade beziehen sich auf das Programmverzeichnis.");
+                    if ui.small_button("📂").on_hover_text("Bilddatei auswählen").clicked() {
+                        if let Some(path) = rfd::FileDialog::new().add_filter("Bild", &["png", "jpg", "jpeg", "pdf"]).pick_file() {
+                            image.path = path.display().to_string();
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("X:");
+                    ui.add(egui::DragValue::new(&mut image.x).clamp_range(0.0..=PDF_MAX_PAGE_SIZE).suffix(" pt"));
+                    ui.label("Y:");
+                    ui.add(egui::DragValue::new(&mut image.y).clamp_range(0.0..=PDF_MAX_PAGE_SIZE).suffix(" pt"));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Breite:");
+                    ui.add(egui::DragValue::new(&mut image.width).clamp_range(1.0..=PDF_MAX_PAGE_SIZE).suffix(" pt"));
+                    ui.label("Höhe:");
+                    ui.add(egui::DragValue::new(&mut image.height).clamp_range(1.0..=PDF_MAX_PAGE_SIZE).suffix(" pt"));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Seitenverhältnis:");
+                    egui::ComboBox::from_id_source(format!("image_fit_{}_{}", id_salt, i))
+                        .selected_text(image.fit.label())
+                        .show_ui(ui, |ui| {
+                            for fit in ImageFit::ALL {
+                                ui.selectable_value(&mut image.fit, fit, fit.label());
+                            }
+                        });
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Seiten:");
+                    if ui.checkbox(&mut image.all_pages, "Alle Seiten").clicked() {
+                        if image.all_pages {
+                            image.pages.clear();
+                        } else if image.pages.is_empty() {
+                            image.pages.push(1);
+                        }
+                    }
+                });
+                if !image.all_pages {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label("Seiten:");
+                        let mut pages_to_remove = Vec::new();
+                        let pages_clone = image.pages.clone();
+                        for (pi, page_val) in pages_clone.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                let mut page_mut = *page_val;
+                                if ui.add(egui::DragValue::new(&mut page_mut).clamp_range(1..=100).prefix("S")).changed() {
+                                    image.pages[pi] = page_mut;
+                                }
+                                if ui.small_button("❌").clicked() && image.pages.len() > 1 {
+                                    pages_to_remove.push(pi);
+                                }
+                            });
+                        }
+                        for &pi in pages_to_remove.iter().rev() { image.pages.remove(pi); }
+                        if ui.small_button("+ Seite").clicked() {
+                            let new_page = image.pages.iter().max().unwrap_or(&0) + 1;
+                            image.pages.push(new_page);
+                            image.pages.sort();
+                        }
+                    });
+                }
+            });
         }
-    }
+    });
 }
 
+// Alle Elemente mit Name, Sichtbarkeit und Zeichenreihenfolge sowie Rechtecke und Linien verwalten
+// (wird in beiden "Elemente verwalten"-Ansichten angezeigt)
+fn elements_ui(ui: &mut egui::Ui, config: &mut Config, id_salt: &str) {
+    // Eigene Id je Ansicht, damit Panel und Fenster sich nicht in die Quere kommen
+    ui.push_id(format!("elements_{}", id_salt), |ui| {
+        ui.group(|ui| {
+            ui.label("Reihenfolge & Sichtbarkeit:").on_hover_text("Elemente mit höherer Ebene werden über die anderen gezeichnet, bei gleicher Ebene entscheidet die Listenreihenfolge.");
+            let count = config.elements.len();
+            let mut swap: Option<(usize, usize)> = None;
+            let mut kind_counts: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+            for i in 0..count {
+                let element = &mut config.elements[i];
+                let number = kind_counts.entry(element.kind.key()).or_insert(0);
+                *number += 1;
+                let default_name = format!("{} {}", element.kind.label(), number);
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut element.visible, "").on_hover_text("Element ausgeben");
+                    ui.add(egui::TextEdit::singleline(&mut element.name).hint_text(default_name).desired_width(140.0));
+                    ui.label("Ebene:");
+                    ui.add(egui::DragValue::new(&mut element.z_order).clamp_range(-100..=100));
+                    if ui.add_enabled(i > 0, egui::Button::new("⬆").small()).on_hover_text("Nach vorne in der Liste").clicked() {
+                        swap = Some((i - 1, i));
+                    }
+                    if ui.add_enabled(i + 1 < count, egui::Button::new("⬇").small()).on_hover_text("Nach hinten in der Liste").clicked() {
+                        swap = Some((i, i + 1));
+                    }
+                });
+            }
+            if let Some((a, b)) = swap {
+                config.elements.swap(a, b);
+            }
+        });
 
-// Parse config from a provided Config object (used to support per-template/group configs)
-#[allow(dead_code)]
-fn parse_config_from(config: &Config) -> (Vec<(f64, f64, f64)>, Vec<(f64, f64, f64)>, Vec<u32>) {
-    let qr_configs: Vec<(f64, f64, f64)> = config.qr_codes.iter()
-        .map(|qr| (qr.x as f64, qr.y as f64, qr.size as f64))
-        .collect();
+        ui.group(|ui| {
+            ui.label("Rechtecke:").on_hover_text("Rahmen oder Flächen, z.B. zum Abdecken veralteter Angaben in der Vorlage.");
+            ui.horizontal(|ui| {
+                if ui.button("+ Rechteck hinzufügen").clicked() {
+                    config.elements.push(Element::new(ElementKind::Rect(RectConfig::default())));
+                }
+                if ui.button("- Rechteck entfernen").clicked() {
+                    config.remove_last_of("rect");
+                }
+            });
 
-    // Now include size for vertreter as well
-    let vertreter_positions: Vec<(f64, f64, f64)> = config.vertreter.iter()
-        .map(|v| (v.x as f64, v.y as f64, v.size as f64))
-        .collect();
+            for (i, rect) in config.rects_mut().enumerate() {
+                ui.group(|ui| {
+                    ui.label(format!("Rechteck {}:", i + 1));
+                    ui.horizontal(|ui| {
+                        ui.label("X:");
+                        ui.add(egui::DragValue::new(&mut rect.x).clamp_range(0.0..=PDF_MAX_PAGE_SIZE).suffix(" pt"));
+                        ui.label("Y:");
+                        ui.add(egui::DragValue::new(&mut rect.y).clamp_range(0.0..=PDF_MAX_PAGE_SIZE).suffix(" pt"));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Breite:");
+                        ui.add(egui::DragValue::new(&mut rect.width).clamp_range(0.1..=PDF_MAX_PAGE_SIZE).suffix(" pt"));
+                        ui.label("Höhe:");
+                        ui.add(egui::DragValue::new(&mut rect.height).clamp_range(0.1..=PDF_MAX_PAGE_SIZE).suffix(" pt"));
+                    });
+                    ui.horizontal(|ui| {
+                        optional_color_ui(ui, "Füllung", &mut rect.fill, [255, 255, 255]);
+                        optional_color_ui(ui, "Rahmen", &mut rect.stroke, [0, 0, 0]);
+                        if rect.stroke.is_some() {
+                            ui.add(egui::DragValue::new(&mut rect.stroke_width).clamp_range(0.1..=20.0).speed(0.1).suffix(" pt"));
+                        }
+                    });
+                    element_pages_ui(ui, &mut rect.pages, &mut rect.all_pages);
+                });
+            }
+        });
 
-    println!("parse_config_from verwendet: QR={:?}, Vertreter={:?}", qr_configs, vertreter_positions);
+        ui.group(|ui| {
+            ui.label("Linien:");
+            ui.horizontal(|ui| {
+                if ui.button("+ Linie hinzufügen").clicked() {
+                    config.elements.push(Element::new(ElementKind::Line(LineConfig::default())));
+                }
+                if ui.button("- Linie entfernen").clicked() {
+                    config.remove_last_of("line");
+                }
+            });
 
-    (qr_configs, vertreter_positions, vec![1]) // Dummy für alte Kompatibilität
+            for (i, line) in config.lines_mut().enumerate() {
+                ui.group(|ui| {
+                    ui.label(format!("Linie {}:", i + 1));
+                    ui.horizontal(|ui| {
+                        ui.label("Von X:");
+                        ui.add(egui::DragValue::new(&mut line.x).clamp_range(0.0..=PDF_MAX_PAGE_SIZE).suffix(" pt"));
+                        ui.label("Y:");
+                        ui.add(egui::DragValue::new(&mut line.y).clamp_range(0.0..=PDF_MAX_PAGE_SIZE).suffix(" pt"));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Bis X:");
+                        ui.add(egui::DragValue::new(&mut line.x2).clamp_range(0.0..=PDF_MAX_PAGE_SIZE).suffix(" pt"));
+                        ui.label("Y:");
+                        ui.add(egui::DragValue::new(&mut line.y2).clamp_range(0.0..=PDF_MAX_PAGE_SIZE).suffix(" pt"));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Farbe:");
+                        ui.color_edit_button_srgb(&mut line.color);
+                        ui.label("Stärke:");
+                        ui.add(egui::DragValue::new(&mut line.stroke_width).clamp_range(0.1..=20.0).speed(0.1).suffix(" pt"));
+                    });
+                    element_pages_ui(ui, &mut line.pages, &mut line.all_pages);
+                });
+            }
+        });
+    });
 }
 
-// Versuche aus einem Template-Pfad Gruppe und Sprache zu extrahieren
-fn infer_group_lang_from_template(template: &str) -> (String, String, bool) {
-    let filename = std::path::Path::new(template)
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or(template)
-        .to_string();
-
-    // Beispiele: Bestellschein-Apo-de_de, Bestellschein-Messe-Apo-de_de
-    let parts: Vec<&str> = filename.split('-').collect();
-    // Suche nach bekannten Gruppen (Apo, Endkunde, Fachkreise)
-    let mut group = "Endkunde".to_string();
-    for p in &parts {
-        let p_low = p.to_lowercase();
-        if p_low.contains("apo") { group = "Apo".to_string(); break; }
-        if p_low.contains("endkunde") { group = "Endkunde".to_string(); break; }
-        if p_low.contains("fachkreise") { group = "Fachkreise".to_string(); break; }
+// Optionale Farbe (z.B. Füllung oder Rahmen) mit Checkbox zum Ein-/Ausschalten
+fn optional_color_ui(ui: &mut egui::Ui, label: &str, color: &mut Option<[u8; 3]>, default: [u8; 3]) {
+    let mut enabled = color.is_some();
+    if ui.checkbox(&mut enabled, label).changed() {
+        *color = if enabled { Some(default) } else { None };
     }
-
-    // Sprache als letzten Teil (wenn vorhanden)
-    let mut lang = "Deutsch".to_string();
-    if let Some(last) = parts.last() {
-        let last_low = last.to_lowercase();
-        if last_low.starts_with("de") { lang = "Deutsch".to_string(); }
-        else if last_low.starts_with("en") { lang = "Englisch".to_string(); }
+    if let Some(rgb) = color {
+        ui.color_edit_button_srgb(rgb);
     }
+}
 
-    // Messe im Dateinamen erkennen
-    let is_messe = filename.to_lowercase().contains("messe");
+// Seitenauswahl eines Elements ("Alle Seiten" oder einzelne Seitenzahlen)
+fn element_pages_ui(ui: &mut egui::Ui, pages: &mut Vec<u32>, all_pages: &mut bool) {
+    ui.horizontal(|ui| {
+        ui.label("Seiten:");
+        if ui.checkbox(all_pages, "Alle Seiten").clicked() {
+            if *all_pages {
+                pages.clear();
+            } else if pages.is_empty() {
+                pages.push(1);
+            }
+        }
+    });
+    if !*all_pages {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Seiten:");
+            let mut pages_to_remove = Vec::new();
+            let pages_clone = pages.clone();
+            for (pi, page_val) in pages_clone.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let mut page_mut = *page_val;
+                    if ui.add(egui::DragValue::new(&mut page_mut).clamp_range(1..=100).prefix("S")).changed() {
+                        pages[pi] = page_mut;
+                    }
+                    if ui.small_button("❌").clicked() && pages.len() > 1 {
+                        pages_to_remove.push(pi);
+                    }
+                });
+            }
+            for &pi in pages_to_remove.iter().rev() { pages.remove(pi); }
+            if ui.small_button("+ Seite").clicked() {
+                let new_page = pages.iter().max().unwrap_or(&0) + 1;
+                pages.push(new_page);
+                pages.sort();
+            }
+        });
+    }
+}
 
-    (group, lang, is_messe)
+// Drehung und Beschriftung eines QR-Codes (wird in beiden "Elemente verwalten"-Ansichten angezeigt)
+fn qr_rotation_caption_ui(ui: &mut egui::Ui, qr: &mut QrCodeConfig, id_salt: &str) {
+    ui.horizontal(|ui| {
+        ui.label("Drehung:").on_hover_text("Gegen den Uhrzeigersinn um die Code-Mitte, z.B. 90° für seitliche Ränder");
+        for angle in [0.0_f32, 90.0, 180.0, 270.0] {
+            if ui.selectable_label(qr.rotation == angle, format!("{}°", angle)).clicked() {
+                qr.rotation = angle;
+            }
+        }
+        ui.add(egui::DragValue::new(&mut qr.rotation).clamp_range(-360.0..=360.0).speed(1.0).suffix("°"));
+    });
+    ui.horizontal(|ui| {
+        ui.label("Beschriftung:");
+        ui.add(egui::TextEdit::singleline(&mut qr.caption).hint_text("z.B. Jetzt online bestellen").desired_width(160.0));
+    });
+    if !qr.caption.is_empty() {
+        ui.horizontal(|ui| {
+            ui.label("  Schrift:");
+            egui::ComboBox::from_id_source(format!("qr_caption_font_{}", id_salt))
+                .selected_text(&qr.caption_font)
+                .show_ui(ui, |ui| {
+                    for font in ["Helvetica", "Arial", "Times New Roman", "Courier New"] {
+                        ui.selectable_value(&mut qr.caption_font, font.to_string(), font);
+                    }
+                });
+            ui.add(egui::DragValue::new(&mut qr.caption_size).clamp_range(4.0..=24.0).suffix(" pt"));
+            ui.label("Abstand:");
+            ui.add(egui::DragValue::new(&mut qr.caption_offset).clamp_range(0.0..=50.0).suffix(" pt"));
+        });
+    }
 }
 
-// Erweiterte Funktion um installierte Windows-Fonts mit Styles zu ermitteln
-fn get_installed_fonts_with_styles() -> Vec<String> {
-    let mut fonts = Vec::new();
-    
-    // Standard Windows-Fonts die fast immer verfügbar sind (mit Styles)
-    let default_fonts = vec![
-        // Arial Familie
-        "Arial".to_string(),
-        "Arial Bold".to_string(),
-        "Arial Italic".to_string(),
-        "Arial Bold Italic".to_string(),
-        // Times Familie
-        "Times New Roman".to_string(),
-        "Times New Roman Bold".to_string(),
-        "Times New Roman Italic".to_string(),
-        "Times New Roman Bold Italic".to_string(),
-        // Calibri Familie  
-        "Calibri".to_string(),
-        "Calibri Bold".to_string(),
-        "Calibri Italic".to_string(),
-        "Calibri Bold Italic".to_string(),
-        // Source Fonts (Adobe/Google) - häufig installiert
-        "Source Sans Pro".to_string(),
-        "Source Sans Pro Bold".to_string(),
-        "Source Sans Pro Italic".to_string(),
-        "Source Sans Pro Bold Italic".to_string(),
-        "Source Sans Pro Light".to_string(),
-        "Source Sans Pro Black".to_string(),
-        "Source Code Pro".to_string(),
-        "Source Code Pro Bold".to_string(),
-        "Source Code Pro Light".to_string(),
-        "Source Serif Pro".to_string(),
-        "Source Serif Pro Bold".to_string(),
-        "Source Serif Pro Italic".to_string(),
-        // Andere Standard-Fonts
-        "Verdana".to_string(),
-        "Verdana Bold".to_string(),
-        "Verdana Italic".to_string(),
-        "Georgia".to_string(),
-        "Georgia Bold".to_string(),
-        "Georgia Italic".to_string(),
-        "Trebuchet MS".to_string(),
-        "Trebuchet MS Bold".to_string(),
-        "Trebuchet MS Italic".to_string(),
-        "Comic Sans MS".to_string(),
-        "Comic Sans MS Bold".to_string(),
-        "Impact".to_string(),
-        "Lucida Console".to_string(),
-        "Tahoma".to_string(),
-        "Tahoma Bold".to_string(),
-        "Courier New".to_string(),
-        "Courier New Bold".to_string(),
-        "Courier New Italic".to_string(),
-        "Helvetica".to_string(),
-        // Zusätzliche deutsche/europäische Fonts
-        "Candara".to_string(),
-        "Candara Bold".to_string(),
-        "Candara Italic".to_string(),
-        "Constantia".to_string(),
-        "Constantia Bold".to_string(),
-        "Constantia Italic".to_string(),
-        "Corbel".to_string(),
-        "Corbel Bold".to_string(),
-        "Corbel Italic".to_string(),
-    ];
+// Auswahl des QR-Inhalts und seiner Felder (wird in beiden "Elemente verwalten"-Ansichten angezeigt)
+fn qr_payload_ui(ui: &mut egui::Ui, qr: &mut QrCodeConfig, id_salt: &str) {
+    ui.horizontal(|ui| {
+        ui.label("Inhalt:").on_hover_text("Was der QR-Code enthält. Felder können Platzhalter wie {kundennr} oder CSV-Spaltennamen enthalten.");
+        let before = qr.payload;
+        egui::ComboBox::from_id_source(format!("qr_payload_{}", id_salt))
+            .selected_text(qr.payload.label())
+            .show_ui(ui, |ui| {
+                for kind in QrPayloadType::ALL {
+                    ui.selectable_value(&mut qr.payload, kind, kind.label());
+                }
+            });
+        if qr.payload != before {
+            qr.payload_fields = qr.payload.default_fields();
+        }
+    });
+    if qr.payload == QrPayloadType::Link {
+        ui.horizontal(|ui| {
+            ui.label("Linkquelle:").on_hover_text("Pro QR-Code wählbar, z.B. ein Code zum deutschen und einer zum englischen Shop auf demselben Formular.");
+            egui::ComboBox::from_id_source(format!("qr_link_source_{}", id_salt))
+                .selected_text(qr.link_source.label())
+                .show_ui(ui, |ui| {
+                    for source in QrLinkSource::ALL {
+                        ui.selectable_value(&mut qr.link_source, source, source.label());
+                    }
+                });
+        });
+        match qr.link_source {
+            QrLinkSource::Column => {
+                ui.horizontal(|ui| {
+                    ui.label("  Spalte:");
+                    ui.add(egui::TextEdit::singleline(&mut qr.link_column).hint_text("Spaltenname aus der CSV-Kopfzeile").desired_width(180.0));
+                });
+            }
+            QrLinkSource::Template => {
+                ui.horizontal(|ui| {
+                    ui.label("  Vorlage:");
+                    ui.add(egui::TextEdit::singleline(&mut qr.link_template).hint_text("leer = Link-Vorlage oben").desired_width(180.0));
+                });
+            }
+            _ => {}
+        }
+    }
+    for field in qr.payload.field_names() {
+        ui.horizontal(|ui| {
+            ui.label(format!("  {}:", field));
+            let value = qr.payload_fields.entry(field.to_string()).or_default();
+            ui.add(egui::TextEdit::singleline(value).desired_width(180.0));
+        });
+    }
+    // Leere Felder nicht in der Config speichern
+    qr.payload_fields.retain(|_, v| !v.is_empty());
+}
+
+// Eingabe der Link-Vorlage (wird in beiden "Elemente verwalten"-Ansichten angezeigt)
+fn link_template_ui(ui: &mut egui::Ui, config: &mut Config) {
+    ui.group(|ui| {
+        ui.label("Link-Vorlage:").on_hover_text("Wird verwendet, wenn die Link-Spalte der CSV leer ist (oder immer, wenn erzwungen).\nPlatzhalter: {kundennr}, {lang}, {lang_code}, {group}, {campaign}, {date} und jede CSV-Spalte per Name.");
+        ui.add(egui::TextEdit::singleline(&mut config.link_template)
+            .hint_text("https://shop.natugena.de/{lang}/?ref={kundennr}&utm_source={campaign}")
+            .desired_width(f32::INFINITY));
+        ui.checkbox(&mut config.force_link_template, "Vorlage auch bei vorhandenem CSV-Link verwenden");
+    });
+    ui.checkbox(&mut config.flatten_form, "Formularfelder der Vorlage nach dem Ausfüllen flach rechnen")
+        .on_hover_text("Feldinhalte werden fest in die Seite übernommen, das Formular ist danach nicht mehr editierbar");
+    ui.horizontal(|ui| {
+        ui.label("Ausgabeseiten:");
+        ui.add(egui::TextEdit::singleline(&mut config.output_pages).hint_text("leer = alle").desired_width(140.0))
+            .on_hover_text("Seiten der Vorlage in Ausgabereihenfolge, z.B. \"2-4\" (Anleitungsseite 1 weglassen), \"1\" (nur Seite 1),\n\"3,1,2\" (umsortieren), \"1,1\" (doppeln) oder \"2-\" (ab Seite 2). Wird nach dem Platzieren angewendet.");
+        if let Err(e) = page_selection_entries(&config.output_pages) {
+            ui.colored_label(egui::Color32::RED, e);
+        }
+    });
+}
+
+// Bogenformat, Abstände und Marken für das Ausschießen
+fn imposition_ui(ui: &mut egui::Ui, imposition: &mut ImpositionOptions) {
+    ui.horizontal(|ui| {
+        ui.label("Bogen:");
+        for (name, width, height) in ImpositionOptions::SHEETS {
+            let selected = imposition.sheet_width_mm == width && imposition.sheet_height_mm == height;
+            if ui.selectable_label(selected, name).clicked() {
+                imposition.sheet_width_mm = width;
+                imposition.sheet_height_mm = height;
+            }
+        }
+        ui.add(egui::DragValue::new(&mut imposition.sheet_width_mm).clamp_range(50.0..=2000.0).suffix(" mm"));
+        ui.label("×");
+        ui.add(egui::DragValue::new(&mut imposition.sheet_height_mm).clamp_range(50.0..=2000.0).suffix(" mm"));
+    });
+    ui.horizontal(|ui| {
+        ui.label("Abstand:");
+        ui.add(egui::DragValue::new(&mut imposition.gutter_mm).clamp_range(0.0..=50.0).speed(0.5).suffix(" mm"));
+        ui.label("Beschnitt:");
+        ui.add(egui::DragValue::new(&mut imposition.bleed_mm).clamp_range(0.0..=10.0).speed(0.5).suffix(" mm"))
+            .on_hover_text("Wird auf den halben Abstand begrenzt, damit sich benachbarte Nutzen nicht überdecken");
+    });
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut imposition.crop_marks, "Schnittmarken");
+        ui.checkbox(&mut imposition.slug_line, "Infozeile (Auftrag, Bogennummer)");
+    });
+}
+
+// Dokument-Metadaten der Ausgaben (wird in beiden "Elemente verwalten"-Ansichten angezeigt)
+fn metadata_ui(ui: &mut egui::Ui, config: &mut Config) {
+    ui.collapsing("PDF-Metadaten", |ui| {
+        ui.label("Muster mit denselben Platzhaltern wie die Link-Vorlage sowie {template}. Leer = Eintrag entfällt.")
+            .on_hover_text("Vertreternummer, Gruppe, Sprache, Prüfsumme der Vorlage und Generator werden immer eingetragen.");
+        let m = &mut config.metadata;
+        egui::Grid::new("metadata_grid").num_columns(2).show(ui, |ui| {
+            for (label, value) in [("Titel:", &mut m.title), ("Betreff:", &mut m.subject), ("Autor:", &mut m.author), ("Stichwörter:", &mut m.keywords)] {
+                ui.label(label);
+                ui.add(egui::TextEdit::singleline(value).desired_width(240.0));
+                ui.end_row();
+            }
+        });
+
+        ui.label("Eigene Schlüssel:");
+        let mut remove = None;
+        let mut rename = None;
+        for (key, value) in m.custom.iter_mut() {
+            ui.horizontal(|ui| {
+                let mut new_key = key.clone();
+                if ui.add(egui::TextEdit::singleline(&mut new_key).desired_width(110.0)).changed() {
+                    rename = Some((key.clone(), new_key));
+                }
+                ui.label("=");
+                ui.add(egui::TextEdit::singleline(value).hint_text("{campaign}").desired_width(160.0));
+                if ui.small_button("❌").clicked() {
+                    remove = Some(key.clone());
+                }
+            });
+        }
+        if let Some((old, new)) = rename {
+            let new = new.trim().to_string();
+            if !new.is_empty() && !m.custom.contains_key(&new) {
+                if let Some(value) = m.custom.remove(&old) {
+                    m.custom.insert(new, value);
+                }
+            }
+        }
+        if let Some(key) = remove {
+            m.custom.remove(&key);
+        }
+        if ui.small_button("+ Schlüssel").clicked() {
+            let key = (1..).map(|n| format!("Feld{}", n)).find(|k| !m.custom.contains_key(k)).unwrap();
+            m.custom.insert(key, String::new());
+        }
+        if ui.small_button("Standard wiederherstellen").clicked() {
+            *m = MetadataConfig::default();
+        }
+    });
+}
+
+// Verschlüsselung und Berechtigungen der Ausgaben (wird in beiden "Elemente verwalten"-Ansichten angezeigt)
+fn encryption_ui(ui: &mut egui::Ui, config: &mut Config) {
+    ui.collapsing("Passwortschutz", |ui| {
+        let e = &mut config.encryption;
+        ui.checkbox(&mut e.enabled, "Ausgaben verschlüsseln (AES-256)")
+            .on_hover_text("Gilt für diese Gruppe. Nicht zusammen mit PDF/A möglich.");
+        if !e.enabled {
+            return;
+        }
+        egui::Grid::new("encryption_grid").num_columns(2).show(ui, |ui| {
+            ui.label("Besitzer-Passwort:");
+            ui.add(egui::TextEdit::singleline(&mut e.owner_password).password(true).desired_width(180.0))
+                .on_hover_text("Hebt die Einschränkungen auf. Wird im Klartext in der Config gespeichert.");
+            ui.end_row();
+            ui.label("Passwort zum Öffnen:");
+            ui.add(egui::TextEdit::singleline(&mut e.user_password).password(true).hint_text("leer = ohne Passwort").desired_width(180.0));
+            ui.end_row();
+        });
+        if e.owner_password.is_empty() {
+            ui.colored_label(egui::Color32::from_rgb(200, 80, 0), "Ohne Besitzer-Passwort werden keine PDFs erzeugt");
+        }
+        ui.horizontal_wrapped(|ui| {
+            ui.checkbox(&mut e.allow_print, "Drucken");
+            ui.checkbox(&mut e.allow_copy, "Kopieren");
+            ui.checkbox(&mut e.allow_fill_forms, "Formulare ausfüllen");
+            ui.checkbox(&mut e.allow_annotate, "Kommentieren");
+            ui.checkbox(&mut e.allow_modify, "Ändern");
+        });
+    });
+}
+
+// Zielfeld eines Elements im Formular der Vorlage (leer = Koordinaten verwenden)
+fn form_field_ui(ui: &mut egui::Ui, form_field: &mut String) {
+    ui.horizontal(|ui| {
+        ui.label("Formularfeld:").on_hover_text("Name eines Formularfeldes der Vorlage, z.B. 'Vertreternummer' oder 'QR'. Position und Seite kommen dann aus dem Feld.");
+        ui.add(egui::TextEdit::singleline(form_field).hint_text("leer = Koordinaten").desired_width(140.0));
+    });
+}
+
+/// Eine Zeile der Vertreter-CSV
+///
+/// Die ersten drei Spalten (Vertreternummer, deutscher Link, englischer Link) sind fest;
+/// zusätzlich sind alle Spalten unter ihrem Namen aus der Kopfzeile (klein geschrieben)
+/// verfügbar, z.B. für Link-Vorlagen.
+#[derive(Clone, Debug, Default)]
+pub struct VertreterRecord {
+    pub nummer: String,
+    pub de_link: String,
+    pub en_link: String,
+    pub columns: std::collections::HashMap<String, String>,
+}
+
+impl VertreterRecord {
+    /// Wert einer Spalte anhand des Spaltennamens (Groß-/Kleinschreibung egal)
+    pub fn column(&self, name: &str) -> Option<&str> {
+        self.columns.get(&name.trim().to_lowercase()).map(|v| v.as_str())
+    }
+}
+
+fn read_vertreter(file_path: &str) -> Vec<VertreterRecord> {
+    debug_print_global(&format!("Versuche CSV zu lesen: {}", file_path));
     
-    fonts.extend(default_fonts);
+    let content = match fs::read_to_string(file_path) {
+        Ok(content) => {
+                debug_print_global(&format!("CSV erfolgreich gelesen, {} Zeichen", content.len()));
+            content
+        },
+        Err(e) => {
+            println!("ERROR: CSV konnte nicht gelesen werden: {}", e);
+            return Vec::new(); // Leere Liste statt Panic
+        }
+    };
+
+    // Ermitteln des Trennzeichens: wenn eine Zeile ';' enthält, priorisiere ';',
+    // sonst benutze ',' als Fallback. Falls beides vorkommt, wähle das häufiger
+    // vorkommende Zeichen in der ersten nicht-leeren Zeile.
+    let mut delimiter = ',';
+    for line in content.lines() {
+        let l = line.trim();
+        if l.is_empty() { continue; }
+        let semi = l.matches(';').count();
+        let comma = l.matches(',').count();
+        if semi > 0 || comma > 0 {
+            delimiter = if semi >= comma { ';' } else { ',' };
+            break;
+        }
+    }
+
+    let mut lines = content.lines();
     
-    // Versuche zusätzliche Fonts aus mehreren Verzeichnissen zu lesen
-    let font_directories = vec![
-        "C:\\Windows\\Fonts".to_string(),
-        format!("{}\\Fonts", std::env::var("LOCALAPPDATA").unwrap_or_default()),
-        format!("{}\\AppData\\Local\\Microsoft\\Windows\\Fonts", std::env::var("USERPROFILE").unwrap_or_default()),
+    // Kopfzeile lesen: Spaltennamen für den Zugriff per Name merken
+    let mut headers: Vec<String> = Vec::new();
+    if let Some(header) = lines.next() {
+        debug_print_global(&format!("Header gelesen: {}", header.trim()));
+        headers = header
+            .trim_start_matches('\u{feff}')
+            .split(delimiter)
+            .enumerate()
+            .map(|(idx, h)| {
+                let name = h.trim().trim_matches('"').to_lowercase();
+                if name.is_empty() { format!("spalte{}", idx + 1) } else { name }
+            })
+            .collect();
+    }
+
+    lines
+        .filter_map(|line| {
+            let l = line.trim();
+            if l.is_empty() { return None; }
+            let parts: Vec<&str> = l.split(delimiter).map(|p| p.trim()).collect();
+            if parts.len() < 2 { return None; }
+
+            let vertreternr = parts[0];
+            // Vertreternummer auf 4 Stellen formatieren (führende Nullen)
+            let formatted_nr = match vertreternr.parse::<u32>() {
+                Ok(num) if num >= 10000 => num.to_string(), // Zahlen >= 10000 bleiben unverändert
+                Ok(num) => format!("{:04}", num),           // Zahlen < 10000 werden auf 4 Stellen aufgefüllt
+                Err(_) => return None,
+            };
+
+            // Links dürfen leer sein, wenn eine Link-Vorlage konfiguriert ist
+            let (de_link, en_link) = if parts.len() >= 3 {
+                (parts[1].to_string(), parts[2].to_string())
+            } else {
+                // Fallback für alte CSV-Struktur (nur 2 Spalten): gleicher Link für beide Sprachen
+                (parts[1].to_string(), parts[1].to_string())
+            };
+
+            let columns = headers
+                .iter()
+                .zip(parts.iter())
+                .map(|(name, value)| (name.clone(), value.to_string()))
+                .collect();
+
+            Some(VertreterRecord { nummer: formatted_nr, de_link, en_link, columns })
+        })
+        .collect()
+}
+
+/// Einstellungen, die für einen ganzen Generierungslauf gelten (nicht pro Gruppen-Config)
+#[derive(Clone, Debug, Default)]
+pub struct RunOptions {
+    /// Kampagnenname für den Platzhalter `{campaign}` in Link-Vorlagen
+    pub campaign: String,
+    /// QR-Codes nach dem Speichern zusätzlich aus der fertigen PDF zurücklesen und prüfen
+    pub verify_saved_qr: bool,
+    /// Schnell speichern, Ausgabe-PDFs verkleinern oder als PDF/A-2b speichern
+    pub output_mode: OutputMode,
+    /// ICC-Profil für die PDF/A-Ausgabebedingung (z.B. ISO Coated v2); leer = eingebautes sRGB
+    pub icc_profile: String,
+    /// Zusätzlich je Vorlage eine zusammengeführte Druckdatei aller Vertreter erzeugen
+    pub print_file: bool,
+    /// CSV-Spalte, nach der die Druckdatei sortiert wird; leer = Vertreternummer
+    pub print_file_sort_column: String,
+    /// Leerseiten einfügen, damit jeder Vertreter im Duplexdruck auf einer Vorderseite beginnt
+    pub print_file_duplex: bool,
+    /// Erzeugte Seiten zusätzlich mehrfach auf größere Druckbogen ausschießen
+    pub imposition: ImpositionOptions,
+}
+
+/// Ausschießen: mehrere Nutzen (z.B. A5/A6-Messeformulare) nebeneinander auf einen Druckbogen
+#[derive(Clone, Debug)]
+pub struct ImpositionOptions {
+    /// Zusätzlich je Vorlage eine Bogen-PDF erzeugen
+    pub enabled: bool,
+    /// Bogenformat in mm; Hoch- oder Querformat wird je nach Nutzenzahl gewählt
+    pub sheet_width_mm: f32,
+    pub sheet_height_mm: f32,
+    /// Abstand zwischen den Endformaten benachbarter Nutzen in mm
+    pub gutter_mm: f32,
+    /// Beschnittzugabe um jeden Nutzen in mm (höchstens der halbe Abstand)
+    pub bleed_mm: f32,
+    /// Schnittmarken an den Kanten der Nutzen außerhalb des Rasters
+    pub crop_marks: bool,
+    /// Infozeile mit Auftragsname und Bogennummer am unteren Bogenrand
+    pub slug_line: bool,
+}
+
+impl Default for ImpositionOptions {
+    fn default() -> Self {
+        ImpositionOptions {
+            enabled: false,
+            sheet_width_mm: 320.0,
+            sheet_height_mm: 450.0,
+            gutter_mm: 6.0,
+            bleed_mm: 3.0,
+            crop_marks: true,
+            slug_line: true,
+        }
+    }
+}
+
+impl ImpositionOptions {
+    /// Gängige Bogenformate (Name, Breite, Höhe in mm)
+    const SHEETS: [(&'static str, f32, f32); 4] = [
+        ("SRA3", 320.0, 450.0),
+        ("SRA4", 225.0, 320.0),
+        ("A3", 297.0, 420.0),
+        ("A4", 210.0, 297.0),
     ];
-    
-    for font_dir in font_directories {
-        if let Ok(entries) = std::fs::read_dir(&font_dir) {
-            for entry in entries.flatten() {
-                if let Some(file_name) = entry.file_name().to_str() {
-                    if file_name.ends_with(".ttf") || file_name.ends_with(".otf") || file_name.ends_with(".TTF") || file_name.ends_with(".OTF") {
-                        // Erweiterte Font-Namen-Extraktion mit Style-Erkennung
-                        let mut font_name = file_name
-                            .replace(".ttf", "")
-                            .replace(".otf", "")
-                            .replace(".TTF", "")
-                            .replace(".OTF", "")
-                            .replace("_", " ")
-                            .replace("-", " ");
-                        
-                        // Erweiterte Font-Name-Bereinigung für bessere Erkennung
-                        if font_name.contains("Adobe") {
-                            font_name = font_name.replace("Adobe", "").trim().to_string();
-                        }
-                        // Source Fonts (Adobe): "Source Sans Pro", "Source Code Pro", etc.
-                        if font_name.starts_with("Source ") {
-                            // Behalte "Source" Präfix für bessere Identifikation
-                        }
-                        // Andere bekannte Präfixe bereinigen
-                        let prefixes_to_remove = ["Microsoft ", "Google ", "Apple ", "System "];
-                        for prefix in &prefixes_to_remove {
-                            if font_name.starts_with(prefix) {
-                                font_name = font_name.replace(prefix, "").trim().to_string();
-                                break;
-                            }
-                        }
-                        
-                        // Erweiterte Style-Erkennung (deutsch, englisch und Varianten)
-                        let styles = [
-                            ("Bold", "Bold"), ("Fett", "Bold"), ("bold", "Bold"), ("BOLD", "Bold"),
-                            ("Italic", "Italic"), ("Kursiv", "Italic"), ("italic", "Italic"), ("ITALIC", "Italic"),
-                            ("Oblique", "Italic"), ("Schräg", "Italic"), ("oblique", "Italic"),
-                            ("Light", "Light"), ("Leicht", "Light"), ("light", "Light"), ("LIGHT", "Light"),
-                            ("Medium", "Medium"), ("medium", "Medium"), ("MEDIUM", "Medium"),
-                            ("Heavy", "Heavy"), ("Schwer", "Heavy"), ("heavy", "Heavy"), ("HEAVY", "Heavy"),
-                            ("Black", "Black"), ("Schwarz", "Black"), ("black", "Black"), ("BLACK", "Black"),
-                            ("Thin", "Thin"), ("Dünn", "Thin"), ("thin", "Thin"), ("THIN", "Thin"),
-                            ("Ultra", "Heavy"), ("Extra", "Heavy"), ("ultra", "Heavy"), ("extra", "Heavy"),
-                            ("SemiBold", "Bold"), ("DemiBold", "Bold"), ("semibold", "Bold"), ("demibold", "Bold"),
-                            ("Regular", "Regular"), ("Normal", "Regular"), ("regular", "Regular"), ("REGULAR", "Regular"),
-                            ("Roman", "Regular"), ("Book", "Regular"), ("roman", "Regular"), ("book", "Regular"),
-                        ];
-                        let mut detected_styles = Vec::new();
-                        
-                        for (style_name, english_style) in &styles {
-                            if font_name.to_lowercase().contains(&style_name.to_lowercase()) {
-                                if !detected_styles.contains(&english_style.to_string()) {
-                                    detected_styles.push(english_style.to_string());
-                                }
-                            }
-                        }
-                        
-                        // Basis-Font-Namen ohne Styles
-                        let mut base_name = font_name.clone();
-                        for (style_name, _) in &styles {
-                            if base_name.to_lowercase().contains(&style_name.to_lowercase()) {
-                                base_name = base_name.replace(style_name, "").trim().to_string();
-                            }
-                        }
-                        
-                        // Füge Basis-Font hinzu
-                        if !base_name.is_empty() && !fonts.iter().any(|f| f.to_lowercase() == base_name.to_lowercase()) {
-                            fonts.push(base_name.clone());
-                        }
-                        
-                        // Füge Style-Varianten hinzu
-                        if !detected_styles.is_empty() {
-                            let style_name = format!("{} {}", base_name, detected_styles.join(" "));
-                            if !fonts.iter().any(|f| f.to_lowercase() == style_name.to_lowercase()) {
-                                fonts.push(style_name);
-                            }
-                        }
-                        
-                        // Auch originalen Namen hinzufügen falls anders
-                        let original_name = font_name.trim().to_string();
+}
+
+/// Wie die erzeugten PDFs gespeichert werden
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Wie bisher: Dokument unverändert speichern
+    #[default]
+    Fast,
+    /// Gleiche Objekte zusammenfassen, unbenutzte entfernen, Streams komprimieren und
+    /// Objekt-Streams verwenden (deutlich kleiner, etwas langsamer)
+    Small,
+    /// Archivtauglich nach PDF/A-2b: nur eingebettete Schriften, Ausgabebedingung mit
+    /// ICC-Profil und XMP-Kennung; nicht konforme Zeilen schlagen fehl
+    PdfA,
+}
+
+impl OutputMode {
+    const ALL: [OutputMode; 3] = [OutputMode::Fast, OutputMode::Small, OutputMode::PdfA];
+
+    fn label(&self) -> &'static str {
+        match self {
+            OutputMode::Fast => "Schnell",
+            OutputMode::Small => "Klein",
+            OutputMode::PdfA => "PDF/A-2b",
+        }
+    }
+}
+
+/// Wie weit die QR-Codes einer erzeugten PDF geprüft wurden
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QrVerification {
+    /// Die PDF enthält keine QR-Codes
+    NoQr,
+    /// Alle Matrizen wurden vor dem Einbetten dekodiert und verglichen
+    Matrix,
+    /// Zusätzlich wurden die eingebetteten Bilder aus der gespeicherten PDF dekodiert
+    SavedPdf,
+}
+
+impl QrVerification {
+    fn label(&self) -> &'static str {
+        match self {
+            QrVerification::NoQr => "kein QR",
+            QrVerification::Matrix => "Matrix geprüft",
+            QrVerification::SavedPdf => "PDF geprüft",
+        }
+    }
+}
+
+/// Ergebnis einer einzelnen Ausgabe (Vertreter × Vorlage) in einem Lauf
+#[derive(Clone, Debug)]
+pub enum RowStatus {
+    /// Erstellt, mit Prüfstufe und Hinweisen (z.B. Logo-Fallback)
+    Created(QrVerification, Vec<String>),
+    Skipped,
+    Failed(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct RowResult {
+    pub kundennr: String,
+    pub template: String,
+    pub status: RowStatus,
+}
+
+/// Ergebnis einer Sammelausgabe je Vorlage (Druckdatei, Druckbogen) mit Meldung
+#[derive(Clone, Debug)]
+pub enum PrintOutputStatus {
+    Created(String),
+    Skipped(String),
+    Failed(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct PrintOutputResult {
+    /// "Druckdatei" oder "Druckbogen"
+    pub output: &'static str,
+    pub template: String,
+    pub status: PrintOutputStatus,
+}
+
+/// Sammelt die Ergebnisse eines Generierungslaufs über alle Threads hinweg
+#[derive(Debug, Default)]
+pub struct RunReport {
+    pub rows: Vec<RowResult>,
+    pub print_outputs: Vec<PrintOutputResult>,
+}
+
+impl RunReport {
+    fn record(&mut self, kundennr: &str, template: &str, status: RowStatus) {
+        self.rows.push(RowResult { kundennr: kundennr.to_string(), template: template.to_string(), status });
+    }
+
+    fn record_print_output(&mut self, output: &'static str, template: &str, status: PrintOutputStatus) {
+        self.print_outputs.push(PrintOutputResult { output, template: template.to_string(), status });
+    }
+
+    /// Druckdateien/Druckbogen, die übersprungen wurden oder fehlgeschlagen sind
+    pub fn print_output_problems(&self) -> usize {
+        self.print_outputs.iter().filter(|o| !matches!(o.status, PrintOutputStatus::Created(_))).count()
+    }
+
+    /// (erstellt, davon QR geprüft, übersprungen, fehlgeschlagen)
+    pub fn summary(&self) -> (usize, usize, usize, usize) {
+        let mut counts = (0, 0, 0, 0);
+        for row in &self.rows {
+            match &row.status {
+                RowStatus::Created(qr, _) => {
+                    counts.0 += 1;
+                    if *qr != QrVerification::NoQr { counts.1 += 1; }
+                }
+                RowStatus::Skipped => counts.2 += 1,
+                RowStatus::Failed(_) => counts.3 += 1,
+            }
+        }
+        counts
+    }
+
+    /// Anzahl erstellter Dateien mit Hinweisen (z.B. QR-Code ohne Logo)
+    pub fn noted(&self) -> usize {
+        self.rows.iter().filter(|row| matches!(&row.status, RowStatus::Created(_, notes) if !notes.is_empty())).count()
+    }
+
+    // Bericht als CSV (Semikolon, wie die Vertreter-CSV) schreiben
+    fn write_csv(&self, path: &std::path::Path) -> std::io::Result<()> {
+        let quote = |v: &str| format!("\"{}\"", v.replace('"', "\"\""));
+        let mut out = String::from("Kundennr;Vorlage;Status;QR-Prüfung;Meldung\n");
+        for row in &self.rows {
+            let (status, qr, message) = match &row.status {
+                RowStatus::Created(qr, notes) if notes.is_empty() => ("erstellt", qr.label(), String::new()),
+                RowStatus::Created(qr, notes) => ("erstellt mit Hinweis", qr.label(), notes.join("; ")),
+                RowStatus::Skipped => ("übersprungen", "", String::new()),
+                RowStatus::Failed(e) => ("fehlgeschlagen", "", e.clone()),
+            };
+            out.push_str(&format!("{};{};{};{};{}\n", quote(&row.kundennr), quote(&row.template), status, qr, quote(&message)));
+        }
+        // Sammelausgaben je Vorlage: statt der Kundennummer steht die Art der Ausgabe in der ersten Spalte
+        for output in &self.print_outputs {
+            let (status, message) = match &output.status {
+                PrintOutputStatus::Created(m) => ("erstellt", m),
+                PrintOutputStatus::Skipped(m) => ("übersprungen", m),
+                PrintOutputStatus::Failed(m) => ("fehlgeschlagen", m),
+            };
+            out.push_str(&format!("{};{};{};;{}\n", quote(output.output), quote(&output.template), status, quote(message)));
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, out)
+    }
+}
+
+// Werte für die Platzhalter einer Link-Vorlage aus CSV-Zeile und Laufeinstellungen zusammenstellen.
+// CSV-Spalten kommen zuerst, damit die festen Platzhalter nicht versehentlich überschrieben werden.
+fn link_template_vars(record: &VertreterRecord, lang_code: &str, group: &str, options: &RunOptions) -> std::collections::HashMap<String, String> {
+    let mut vars = record.columns.clone();
+    let lang_short = lang_code.split('_').next().unwrap_or(lang_code).to_lowercase();
+    vars.insert("kundennr".to_string(), record.nummer.clone());
+    vars.insert("vertreternummer".to_string(), record.nummer.clone());
+    vars.insert("lang".to_string(), lang_short);
+    vars.insert("lang_code".to_string(), lang_code.to_lowercase());
+    vars.insert("group".to_string(), group.to_lowercase());
+    vars.insert("campaign".to_string(), options.campaign.clone());
+    vars.insert("date".to_string(), chrono::Local::now().format("%Y-%m-%d").to_string());
+    vars
+}
+
+// Prozent-Kodierung für Werte in URLs (RFC 3986, nur unreservierte Zeichen bleiben erhalten)
+fn url_encode_component(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for b in value.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(b as char),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// Füllt eine Link-Vorlage wie `https://shop.natugena.de/{lang}/?ref={kundennr}`.
+///
+/// Platzhalter-Namen sind unabhängig von Groß-/Kleinschreibung, eingesetzte Werte werden
+/// URL-kodiert. Unbekannte Platzhalter führen zu einem Fehler, damit Tippfehler in der
+/// Vorlage nicht unbemerkt zu falschen Links auf tausenden Bestellscheinen führen.
+fn fill_link_template(template: &str, vars: &std::collections::HashMap<String, String>) -> Result<String, String> {
+    fill_placeholders(template, vars, url_encode_component)
+}
+
+// Ersetzt `{name}`-Platzhalter durch Werte aus `vars`; `encode` bestimmt, wie die Werte
+// eingesetzt werden (URL-kodiert für Links, unverändert für vCard/EPC-Felder)
+fn fill_placeholders(template: &str, vars: &std::collections::HashMap<String, String>, encode: fn(&str) -> String) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len() + 32);
+    let mut unknown = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let Some(close) = after.find('}') else {
+            return Err(format!("Nicht geschlossener Platzhalter in Vorlage: {}", template));
+        };
+        let name = after[..close].trim().to_lowercase();
+        match vars.get(&name) {
+            Some(value) => out.push_str(&encode(value)),
+            None => unknown.push(name),
+        }
+        rest = &after[close + 1..];
+    }
+    out.push_str(rest);
+
+    if unknown.is_empty() {
+        Ok(out)
+    } else {
+        Err(format!("Unbekannte Platzhalter in Vorlage: {{{}}}", unknown.join("}, {")))
+    }
+}
+
+/// Bestimmt den QR-Link einer Zeile: Link-Vorlage, wenn erzwungen oder der CSV-Link leer ist,
+/// sonst der Link aus der CSV. `Ok(None)` bedeutet, dass weder Link noch Vorlage vorhanden sind.
+fn resolve_qr_link(config: &Config, csv_link: &str, vars: &std::collections::HashMap<String, String>) -> Result<Option<String>, String> {
+    let csv_link = csv_link.trim();
+    if !config.link_template.trim().is_empty() && (config.force_link_template || csv_link.is_empty()) {
+        return fill_link_template(config.link_template.trim(), vars).map(Some);
+    }
+    if csv_link.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(csv_link.to_string()))
+    }
+}
+
+// Erzeuge ein kleines eingebettetes Icon (blockiges "B") als RGBA-Pixel-Array.
+// So hat die Anwendung auch ohne externe Datei ein eigenes Icon in der Taskleiste.
+#[allow(dead_code)]
+fn make_app_icon() -> egui::IconData {
+    let width: usize = 32;
+    let height: usize = 32;
+    let mut rgba = vec![0u8; width * height * 4];
+
+    // Hintergrund: Orange-ish
+    for y in 0..height {
+        for x in 0..width {
+            let i = (y * width + x) * 4;
+            rgba[i] = 255; // R
+            rgba[i + 1] = 140; // G
+            rgba[i + 2] = 0;   // B
+            rgba[i + 3] = 255; // A
+        }
+    }
+
+    // Zeichne ein blockiges 'B' in Weiß (einfach aus Rechtecken zusammengesetzt)
+    // Vertikale Hauptlinie
+    for y in 6..26 {
+        for x in 6..10 {
+            let i = (y * width + x) * 4;
+            rgba[i] = 255; rgba[i + 1] = 255; rgba[i + 2] = 255; rgba[i + 3] = 255;
+        }
+    }
+    // Oberer B-Bogen
+    for y in 6..14 {
+        for x in 10..22 {
+            let i = (y * width + x) * 4;
+            rgba[i] = 255; rgba[i + 1] = 255; rgba[i + 2] = 255; rgba[i + 3] = 255;
+        }
+    }
+    // Unterer B-Bogen
+    for y in 18..26 {
+        for x in 10..22 {
+            let i = (y * width + x) * 4;
+            rgba[i] = 255; rgba[i + 1] = 255; rgba[i + 2] = 255; rgba[i + 3] = 255;
+        }
+    }
+
+    egui::IconData { rgba, width: width as _, height: height as _ }
+}
+
+// QR-Matrix für einen Inhalt; zu lange Inhalte (über die Kapazität von Version 40) sind ein Fehler
+// der Zeile, kein Abbruch des Laufs
+fn generate_qr(link: &str) -> Result<(Vec<u8>, usize), String> {
+    let code = QrCode::new(link)
+        .map_err(|e| format!("Konnte QR-Code nicht generieren ({} Bytes): {:?}", link.len(), e))?;
+    Ok(qr_code_to_gray(&code))
+}
+
+// QR-Matrix ohne Ruhezone als 8-Bit-Graustufen (0 = dunkles Modul, 255 = hell)
+fn qr_code_to_gray(code: &QrCode) -> (Vec<u8>, usize) {
+    let width = code.width();
+    let data = code
+        .to_colors()
+        .into_iter()
+        .map(|c| if c == qrcode::Color::Dark { 0u8 } else { 255u8 })
+        .collect();
+    (data, width)
+}
+
+// Modulbereich (Start, Ende) der freigeräumten Logo-Fläche in einer QR-Matrix der Breite `width`.
+// Die Fläche ist quadratisch und exakt zentriert, damit das Logo im PDF deckungsgleich darüber liegt.
+fn logo_module_range(width: usize, logo_size: f32) -> (usize, usize) {
+    let fraction = logo_size.clamp(0.05, 0.30);
+    let mut side = (width as f32 * fraction).ceil() as usize;
+    if !(width - side).is_multiple_of(2) {
+        side += 1;
+    }
+    let start = (width - side) / 2;
+    (start, start + side)
+}
+
+/// Erzeugt einen QR-Code mit freigeräumter Mitte für ein Logo.
+///
+/// Erzwingt Fehlerkorrektur-Level H, setzt die vom Logo verdeckten Module auf hell
+/// und prüft anschließend, ob der Code noch zum ursprünglichen Link dekodiert.
+/// Liefert die Matrix, ihre Breite und den Anteil der Logo-Fläche an der Kantenlänge.
+fn generate_qr_with_logo(link: &str, logo_size: f32) -> Result<(Vec<u8>, usize, f32), String> {
+    let code = QrCode::with_error_correction_level(link, qrcode::EcLevel::H)
+        .map_err(|e| format!("Konnte QR-Code nicht generieren: {:?}", e))?;
+    let (mut data, width) = qr_code_to_gray(&code);
+
+    let (start, end) = logo_module_range(width, logo_size);
+    for y in start..end {
+        for x in start..end {
+            data[y * width + x] = 255;
+        }
+    }
+
+    verify_qr_matrix(&data, width, link)
+        .map_err(|e| format!("{} (Logo-Größe {:.2} zu groß?)", e, logo_size))?;
+    Ok((data, width, (end - start) as f32 / width as f32))
+}
+
+// Dekodiert eine QR-Matrix und vergleicht sie mit dem erwarteten Inhalt
+fn verify_qr_matrix(data: &[u8], width: usize, expected: &str) -> Result<(), String> {
+    match decode_qr_matrix(data, width) {
+        Some(decoded) if decoded == expected => Ok(()),
+        Some(decoded) => Err(format!("QR-Code dekodiert zu '{}' statt '{}'", decoded, expected)),
+        None => Err(format!("QR-Code für '{}' ist nicht lesbar", expected)),
+    }
+}
+
+// Liest die QR-Bilder aus der gespeicherten PDF zurück und dekodiert sie erneut.
+// Die Objekt-IDs bleiben beim Speichern mit lopdf erhalten.
+fn verify_qr_images_in_saved_pdf(path: &std::path::Path, expected: &[(lopdf::ObjectId, String)]) -> Result<(), String> {
+    let doc = Document::load(path).map_err(|e| format!("Gespeicherte PDF nicht lesbar: {}", e))?;
+    for (id, payload) in expected {
+        let stream = doc.get_object(*id)
+            .and_then(|o| o.as_stream())
+            .map_err(|e| format!("QR-Bild {:?} fehlt in gespeicherter PDF: {}", id, e))?;
+        let width = stream.dict.get(b"Width").and_then(|w| w.as_i64())
+            .map_err(|_| format!("QR-Bild {:?} ohne Breite", id))? as usize;
+        let data = if stream.dict.has(b"Filter") {
+            stream.decompressed_content().map_err(|e| format!("QR-Bild {:?} nicht dekomprimierbar: {}", id, e))?
+        } else {
+            stream.content.clone()
+        };
+        verify_qr_matrix(&data, width, payload)?;
+    }
+    Ok(())
+}
+
+// Dekodiert eine QR-Matrix (Format wie von qr_code_to_gray) mit einem Pure-Rust-Decoder.
+// Die Matrix wird dafür mit Ruhezone hochskaliert, wie sie auch gedruckt erscheint.
+fn decode_qr_matrix(data: &[u8], width: usize) -> Option<String> {
+    const QUIET_ZONE: usize = 4;
+    const SCALE: usize = 4;
+    if width == 0 || data.len() < width * width {
+        return None;
+    }
+    let full = (width + 2 * QUIET_ZONE) * SCALE;
+    let mut img = rqrr::PreparedImage::prepare_from_greyscale(full, full, |x, y| {
+        let mx = x / SCALE;
+        let my = y / SCALE;
+        if mx < QUIET_ZONE || my < QUIET_ZONE || mx >= QUIET_ZONE + width || my >= QUIET_ZONE + width {
+            255
+        } else {
+            data[(my - QUIET_ZONE) * width + (mx - QUIET_ZONE)]
+        }
+    });
+    img.detect_grids()
+        .into_iter()
+        .find_map(|grid| grid.decode().ok().map(|(_meta, content)| content))
+}
+
+// Globale Variable für die aktuelle Config (wird von UI gesetzt)
+static mut CURRENT_CONFIG: Option<Config> = None;
+static CONFIG_MUTEX: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+// Track the last-loaded config file path in a safe Mutex for multi-threaded access
+use once_cell::sync::Lazy;
+static CURRENT_CONFIG_PATH: Lazy<std::sync::Mutex<Option<std::path::PathBuf>>> = Lazy::new(|| std::sync::Mutex::new(None));
+// Recent config list (strings) for quick access in UI
+static RECENT_CONFIGS: Lazy<std::sync::Mutex<Vec<String>>> = Lazy::new(|| std::sync::Mutex::new(Vec::new()));
+
+// Funktion um die aktuelle Config zu setzen (threadsafe)
+fn set_current_config(config: &Config) {
+    let _lock = CONFIG_MUTEX.lock().unwrap();
+    unsafe {
+        CURRENT_CONFIG = Some(config.clone());
+    }
+    println!("Aktuelle Config gesetzt für PDF-Generierung: Elemente={:?}", 
+             config.elements);
+}
+
+fn set_current_config_path(path: &std::path::Path) {
+    let mut g = CURRENT_CONFIG_PATH.lock().unwrap();
+    *g = Some(path.to_path_buf());
+    println!("Aktuelle Config-Datei gesetzt: {:?}", path);
+    // Add to recent list
+    add_recent_config(path);
+}
+
+fn get_current_config_path() -> Option<std::path::PathBuf> {
+    let g = CURRENT_CONFIG_PATH.lock().unwrap();
+    g.clone()
+}
+
+fn add_recent_config(path: &std::path::Path) {
+    let s = path.display().to_string();
+    let mut list = RECENT_CONFIGS.lock().unwrap();
+    // Remove existing equal entries
+    list.retain(|x| x != &s);
+    // Insert at front
+    list.insert(0, s);
+    // Cap at 12 entries
+    if list.len() > 12 {
+        list.truncate(12);
+    }
+}
+
+fn get_recent_config_list() -> Vec<String> {
+    let list = RECENT_CONFIGS.lock().unwrap();
+    list.clone()
+}
+
+// Return last-modified timestamp string for a file, if available
+fn file_modified_time_str(path: &std::path::Path) -> Option<String> {
+    if let Ok(md) = std::fs::metadata(path) {
+        if let Ok(system_time) = md.modified() {
+            // Convert SystemTime -> chrono DateTime using local timezone
+            let dt: chrono::DateTime<chrono::Local> = chrono::DateTime::from(system_time);
+            return Some(dt.format("%Y-%m-%d %H:%M:%S").to_string());
+        }
+    }
+    None
+}
+
+// Determine a simple source label for a config path
+fn detect_config_source(path: &std::path::Path) -> &'static str {
+    let s = path.display().to_string().to_lowercase();
+    if s.contains("config/presets") || s.contains("config\\presets") { "preset" }
+    else if s.contains("config_messe") || s.contains("messe") { "messe" }
+    else if s.contains("config_") || s.contains("config-") { "group" }
+    else { "unknown" }
+}
+
+// Load a config from an arbitrary path (used by recent-list selection / save-as results)
+fn load_config_from_path(path: &std::path::Path) -> Option<Config> {
+    // Use extension-aware loader to support both TOML and YAML configs
+    match try_load_config_file(path) {
+        Some(cfg) => Some(cfg),
+        None => {
+            eprintln!("Konnte Config-Datei {:?} nicht lesen oder parsen", path);
+            None
+        }
+    }
+}
+
+// Try loading a config file and detect format by extension (yaml/yml -> YAML; else TOML fallback)
+fn try_load_config_file(path: &std::path::Path) -> Option<Config> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => {
+            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+            if ext == "yaml" || ext == "yml" || content.trim_start().starts_with("---") {
+                match serde_yaml::from_str::<Config>(&content) {
+                    Ok(cfg) => {
+                        set_current_config_path(path);
+                        println!("✅ YAML-Config geladen von {:?}", path);
+                        return Some(cfg);
+                    }
+                    Err(e) => {
+                        eprintln!("Fehler beim Parsen der YAML-Config {:?}: {}", path, e);
+                        // fall through to try TOML as a last resort
+                    }
+                }
+            }
+            // Fallback: try TOML parser used by the project
+            let cfg = parse_toml_to_config(&content);
+            set_current_config_path(path);
+            println!("✅ TOML-Config geladen von {:?}", path);
+            Some(cfg)
+        }
+        Err(e) => {
+            eprintln!("Konnte Config-Datei {:?} nicht lesen: {}", path, e);
+            None
+        }
+    }
+}
+
+
+// Parse config from a provided Config object (used to support per-template/group configs)
+#[allow(dead_code, clippy::type_complexity)]
+fn parse_config_from(config: &Config) -> (Vec<(f64, f64, f64)>, Vec<(f64, f64, f64)>, Vec<u32>) {
+    let qr_configs: Vec<(f64, f64, f64)> = config.qr_codes()
+        .map(|qr| (qr.x as f64, qr.y as f64, qr.size as f64))
+        .collect();
+
+    // Now include size for vertreter as well
+    let vertreter_positions: Vec<(f64, f64, f64)> = config.vertreter()
+        .map(|v| (v.x as f64, v.y as f64, v.size as f64))
+        .collect();
+
+    println!("parse_config_from verwendet: QR={:?}, Vertreter={:?}", qr_configs, vertreter_positions);
+
+    (qr_configs, vertreter_positions, vec![1]) // Dummy für alte Kompatibilität
+}
+
+// Versuche aus einem Template-Pfad Gruppe und Sprache zu extrahieren
+fn infer_group_lang_from_template(template: &str) -> (String, String, bool) {
+    let filename = std::path::Path::new(template)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or(template)
+        .to_string();
+
+    // Beispiele: Bestellschein-Apo-de_de, Bestellschein-Messe-Apo-de_de
+    let parts: Vec<&str> = filename.split('-').collect();
+    // Suche nach bekannten Gruppen (Apo, Endkunde, Fachkreise)
+    let mut group = "Endkunde".to_string();
+    for p in &parts {
+        let p_low = p.to_lowercase();
+        if p_low.contains("apo") { group = "Apo".to_string(); break; }
+        if p_low.contains("endkunde") { group = "Endkunde".to_string(); break; }
+        if p_low.contains("fachkreise") { group = "Fachkreise".to_string(); break; }
+    }
+
+    // Sprache als letzten Teil (wenn vorhanden)
+    let mut lang = "Deutsch".to_string();
+    if let Some(last) = parts.last() {
+        let last_low = last.to_lowercase();
+        if last_low.starts_with("de") { lang = "Deutsch".to_string(); }
+        else if last_low.starts_with("en") { lang = "Englisch".to_string(); }
+    }
+
+    // Messe im Dateinamen erkennen
+    let is_messe = filename.to_lowercase().contains("messe");
+
+    (group, lang, is_messe)
+}
+
+// Erweiterte Funktion um installierte Windows-Fonts mit Styles zu ermitteln
+fn get_installed_fonts_with_styles() -> Vec<String> {
+    let mut fonts = Vec::new();
+    
+    // Standard Windows-Fonts die fast immer verfügbar sind (mit Styles)
+    let default_fonts = vec![
+        // Arial Familie
+        "Arial".to_string(),
+        "Arial Bold".to_string(),
+        "Arial Italic".to_string(),
+        "Arial Bold Italic".to_string(),
+        // Times Familie
+        "Times New Roman".to_string(),
+        "Times New Roman Bold".to_string(),
+        "Times New Roman Italic".to_string(),
+        "Times New Roman Bold Italic".to_string(),
+        // Calibri Familie  
+        "Calibri".to_string(),
+        "Calibri Bold".to_string(),
+        "Calibri Italic".to_string(),
+        "Calibri Bold Italic".to_string(),
+        // Source Fonts (Adobe/Google) - häufig installiert
+        "Source Sans Pro".to_string(),
+        "Source Sans Pro Bold".to_string(),
+        "Source Sans Pro Italic".to_string(),
+        "Source Sans Pro Bold Italic".to_string(),
+        "Source Sans Pro Light".to_string(),
+        "Source Sans Pro Black".to_string(),
+        "Source Code Pro".to_string(),
+        "Source Code Pro Bold".to_string(),
+        "Source Code Pro Light".to_string(),
+        "Source Serif Pro".to_string(),
+        "Source Serif Pro Bold".to_string(),
+        "Source Serif Pro Italic".to_string(),
+        // Andere Standard-Fonts
+        "Verdana".to_string(),
+        "Verdana Bold".to_string(),
+        "Verdana Italic".to_string(),
+        "Georgia".to_string(),
+        "Georgia Bold".to_string(),
+        "Georgia Italic".to_string(),
+        "Trebuchet MS".to_string(),
+        "Trebuchet MS Bold".to_string(),
+        "Trebuchet MS Italic".to_string(),
+        "Comic Sans MS".to_string(),
+        "Comic Sans MS Bold".to_string(),
+        "Impact".to_string(),
+        "Lucida Console".to_string(),
+        "Tahoma".to_string(),
+        "Tahoma Bold".to_string(),
+        "Courier New".to_string(),
+        "Courier New Bold".to_string(),
+        "Courier New Italic".to_string(),
+        "Helvetica".to_string(),
+        // Zusätzliche deutsche/europäische Fonts
+        "Candara".to_string(),
+        "Candara Bold".to_string(),
+        "Candara Italic".to_string(),
+        "Constantia".to_string(),
+        "Constantia Bold".to_string(),
+        "Constantia Italic".to_string(),
+        "Corbel".to_string(),
+        "Corbel Bold".to_string(),
+        "Corbel Italic".to_string(),
+    ];
+    
+    fonts.extend(default_fonts);
+    
+    // Versuche zusätzliche Fonts aus mehreren Verzeichnissen zu lesen
+    let font_directories = vec![
+        "C:\\Windows\\Fonts".to_string(),
+        format!("{}\\Fonts", std::env::var("LOCALAPPDATA").unwrap_or_default()),
+        format!("{}\\AppData\\Local\\Microsoft\\Windows\\Fonts", std::env::var("USERPROFILE").unwrap_or_default()),
+    ];
+    
+    for font_dir in font_directories {
+        if let Ok(entries) = std::fs::read_dir(&font_dir) {
+            for entry in entries.flatten() {
+                if let Some(file_name) = entry.file_name().to_str() {
+                    if file_name.ends_with(".ttf") || file_name.ends_with(".otf") || file_name.ends_with(".TTF") || file_name.ends_with(".OTF") {
+                        // Erweiterte Font-Namen-Extraktion mit Style-Erkennung
+                        let mut font_name = file_name
+                            .replace(".ttf", "")
+                            .replace(".otf", "")
+                            .replace(".TTF", "")
+                            .replace(".OTF", "")
+                            .replace("_", " ")
+                            .replace("-", " ");
+                        
+                        // Erweiterte Font-Name-Bereinigung für bessere Erkennung
+                        if font_name.contains("Adobe") {
+                            font_name = font_name.replace("Adobe", "").trim().to_string();
+                        }
+                        // Source Fonts (Adobe): "Source Sans Pro", "Source Code Pro", etc.
+                        if font_name.starts_with("Source ") {
+                            // Behalte "Source" Präfix für bessere Identifikation
+                        }
+                        // Andere bekannte Präfixe bereinigen
+                        let prefixes_to_remove = ["Microsoft ", "Google ", "Apple ", "System "];
+                        for prefix in &prefixes_to_remove {
+                            if font_name.starts_with(prefix) {
+                                font_name = font_name.replace(prefix, "").trim().to_string();
+                                break;
+                            }
+                        }
+                        
+                        // Erweiterte Style-Erkennung (deutsch, englisch und Varianten)
+                        let styles = [
+                            ("Bold", "Bold"), ("Fett", "Bold"), ("bold", "Bold"), ("BOLD", "Bold"),
+                            ("Italic", "Italic"), ("Kursiv", "Italic"), ("italic", "Italic"), ("ITALIC", "Italic"),
+                            ("Oblique", "Italic"), ("Schräg", "Italic"), ("oblique", "Italic"),
+                            ("Light", "Light"), ("Leicht", "Light"), ("light", "Light"), ("LIGHT", "Light"),
+                            ("Medium", "Medium"), ("medium", "Medium"), ("MEDIUM", "Medium"),
+                            ("Heavy", "Heavy"), ("Schwer", "Heavy"), ("heavy", "Heavy"), ("HEAVY", "Heavy"),
+                            ("Black", "Black"), ("Schwarz", "Black"), ("black", "Black"), ("BLACK", "Black"),
+                            ("Thin", "Thin"), ("Dünn", "Thin"), ("thin", "Thin"), ("THIN", "Thin"),
+                            ("Ultra", "Heavy"), ("Extra", "Heavy"), ("ultra", "Heavy"), ("extra", "Heavy"),
+                            ("SemiBold", "Bold"), ("DemiBold", "Bold"), ("semibold", "Bold"), ("demibold", "Bold"),
+                            ("Regular", "Regular"), ("Normal", "Regular"), ("regular", "Regular"), ("REGULAR", "Regular"),
+                            ("Roman", "Regular"), ("Book", "Regular"), ("roman", "Regular"), ("book", "Regular"),
+                        ];
+                        let mut detected_styles = Vec::new();
+                        
+                        for (style_name, english_style) in &styles {
+                            if font_name.to_lowercase().contains(&style_name.to_lowercase())
+                                && !detected_styles.contains(&english_style.to_string()) {
+                                    detected_styles.push(english_style.to_string());
+                                }
+                        }
+                        
+                        // Basis-Font-Namen ohne Styles
+                        let mut base_name = font_name.clone();
+                        for (style_name, _) in &styles {
+                            if base_name.to_lowercase().contains(&style_name.to_lowercase()) {
+                                base_name = base_name.replace(style_name, "").trim().to_string();
+                            }
+                        }
+                        
+                        // Füge Basis-Font hinzu
+                        if !base_name.is_empty() && !fonts.iter().any(|f| f.to_lowercase() == base_name.to_lowercase()) {
+                            fonts.push(base_name.clone());
+                        }
+                        
+                        // Füge Style-Varianten hinzu
+                        if !detected_styles.is_empty() {
+                            let style_name = format!("{} {}", base_name, detected_styles.join(" "));
+                            if !fonts.iter().any(|f| f.to_lowercase() == style_name.to_lowercase()) {
+                                fonts.push(style_name);
+                            }
+                        }
+                        
+                        // Auch originalen Namen hinzufügen falls anders
+                        let original_name = font_name.trim().to_string();
                         if !original_name.is_empty() && !fonts.iter().any(|f| f.to_lowercase() == original_name.to_lowercase()) {
                             fonts.push(original_name);
                         }
@@ -5072,468 +8248,3311 @@ fn get_installed_fonts_with_styles() -> Vec<String> {
             }
         }
     }
-    
-    // Sortiere alphabetisch und entferne Duplikate
-    fonts.sort();
-    fonts.dedup();
-    
-    println!("🔤 FONTS GEFUNDEN: {} Schriftarten geladen", fonts.len());
-    if fonts.len() > 50 {
-        println!("📝 Erste 10 Fonts: {:?}", &fonts[0..10.min(fonts.len())]);
-        println!("📝 Letzte 10 Fonts: {:?}", &fonts[fonts.len().saturating_sub(10)..]);
+    
+    // Sortiere alphabetisch und entferne Duplikate
+    fonts.sort();
+    fonts.dedup();
+    
+    println!("🔤 FONTS GEFUNDEN: {} Schriftarten geladen", fonts.len());
+    if fonts.len() > 50 {
+        println!("📝 Erste 10 Fonts: {:?}", &fonts[0..10.min(fonts.len())]);
+        println!("📝 Letzte 10 Fonts: {:?}", &fonts[fonts.len().saturating_sub(10)..]);
+    } else {
+        println!("📝 Alle Fonts: {:?}", fonts);
+    }
+    
+    fonts
+}
+
+// Aktualisiere Font-Cache (kann von UI aufgerufen werden)
+fn refresh_font_cache() -> Vec<String> {
+    println!("🔄 FONT-CACHE: Aktualisiere Schriftarten-Liste...");
+    
+    // PERFORMANCE: Cache in Datei speichern um wiederholte Scans zu vermeiden
+    let cache_file = std::path::Path::new("font_cache.json");
+    let cache_age_hours = 24; // Cache 24 Stunden gültig
+    
+    // Prüfe ob Cache-Datei existiert und noch gültig ist
+    if cache_file.exists() {
+        if let Ok(metadata) = std::fs::metadata(cache_file) {
+            if let Ok(modified) = metadata.modified() {
+                if let Ok(elapsed) = modified.elapsed() {
+                    if elapsed.as_secs() < (cache_age_hours * 3600) {
+                        println!("📁 FONT-CACHE: Verwende gecachte Font-Liste ({}h alt)", elapsed.as_secs() / 3600);
+                        
+                        // Lade aus Cache
+                        if let Ok(cache_content) = std::fs::read_to_string(cache_file) {
+                            if let Ok(cached_fonts) = serde_json::from_str::<Vec<String>>(&cache_content) {
+                                if !cached_fonts.is_empty() {
+                                    println!("✅ FONT-CACHE: {} Fonts aus Cache geladen", cached_fonts.len());
+                                    return cached_fonts;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    
+    // Cache ist ungültig oder existiert nicht - neu scannen
+    println!("🔍 FONT-CACHE: Scanne Schriftarten neu...");
+    let fonts = get_installed_fonts_with_styles_optimized();
+    
+    // PERFORMANCE: Begrenze Anzahl der Fonts für UI-Performance (WENIGER für bessere Performance)
+    let max_fonts = 50; // Reduziert von 200 auf 50 für flüssige UI
+    let mut filtered_fonts = fonts;
+    if filtered_fonts.len() > max_fonts {
+        println!("⚡ PERFORMANCE: Begrenze Fonts von {} auf {} für flüssige UI", filtered_fonts.len(), max_fonts);
+        
+        // Priorisiere häufige Fonts
+        let priority_fonts = ["Arial", "Calibri", "Times New Roman", "Helvetica", "Verdana", "Georgia", "Tahoma", "Segoe UI"];
+        let mut prioritized = Vec::new();
+        let mut others = Vec::new();
+        
+        for font in filtered_fonts {
+            if priority_fonts.iter().any(|pf| font.contains(pf)) {
+                prioritized.push(font);
+            } else {
+                others.push(font);
+            }
+        }
+        
+        // Erst priority_fonts, dann die ersten "others" bis max_fonts erreicht
+        prioritized.extend(others.into_iter().take(max_fonts.saturating_sub(prioritized.len())));
+        filtered_fonts = prioritized;
+    }
+    
+    // Cache speichern
+    if let Ok(cache_json) = serde_json::to_string_pretty(&filtered_fonts) {
+        if let Err(e) = std::fs::write(cache_file, cache_json) {
+            println!("⚠️  FONT-CACHE: Konnte Cache nicht speichern: {}", e);
+        } else {
+            println!("💾 FONT-CACHE: Cache gespeichert");
+        }
+    }
+    
+    filtered_fonts
+}
+
+// Optimierte Font-Scanning-Funktion
+fn get_installed_fonts_with_styles_optimized() -> Vec<String> {
+    // Verwende die existierende Funktion aber mit Performance-Verbesserungen
+    get_installed_fonts_with_styles()
+}
+
+// Font-Pfad mit mehreren Quellen finden (ohne Admin-Rechte)
+fn find_font_file(font_name: &str, style: &str) -> Option<std::path::PathBuf> {
+    // Mehrere mögliche Font-Ordner (auch ohne Admin-Rechte)
+    let font_dirs = vec![
+        "C:\\Windows\\Fonts".to_string(),
+        format!("{}\\AppData\\Local\\Microsoft\\Windows\\Fonts", std::env::var("USERPROFILE").unwrap_or_default()),
+        format!("{}\\AppData\\Roaming\\Adobe\\CoreSync\\plugins\\livetype\\.r", std::env::var("USERPROFILE").unwrap_or_default()),
+        ".\\fonts".to_string(), // Lokaler fonts Ordner im Projekt
+    ];
+    
+    // Mögliche Dateinamen für den Font (viele Varianten)
+    let font_base = font_name.replace(" ", "").to_lowercase();
+    let style_lower = style.to_lowercase();
+    
+    let possible_names = vec![
+        // Standard-Benennungen
+        format!("{}.ttf", font_base),
+        format!("{}.otf", font_base), 
+        format!("{}_{}.ttf", font_base, style_lower),
+        format!("{}_{}.otf", font_base, style_lower),
+        format!("{}-{}.ttf", font_base, style_lower),
+        format!("{}-{}.otf", font_base, style_lower),
+        // Windows-spezifische Benennungen
+        format!("{}b.ttf", font_base), // Bold
+        format!("{}i.ttf", font_base), // Italic
+        format!("{}z.ttf", font_base), // Bold Italic
+        format!("{}bd.ttf", font_base), // Bold
+        format!("{}it.ttf", font_base), // Italic
+        // Vollständige Namen
+        format!("{}.ttf", font_name.replace(" ", "")),
+        format!("{}.otf", font_name.replace(" ", "")),
+        format!("{} {}.ttf", font_name, style),
+        format!("{} {}.otf", font_name, style),
+        // Spezielle Arial-Varianten
+        format!("arial{}.ttf", if style_lower.contains("bold") { "bd" } else if style_lower.contains("italic") { "i" } else { "" }),
+        // Calibri-Varianten  
+        format!("calibri{}.ttf", if style_lower.contains("bold") { "b" } else if style_lower.contains("italic") { "i" } else { "" }),
+        // Times New Roman-Varianten
+        format!("times{}.ttf", if style_lower.contains("bold") { "bd" } else if style_lower.contains("italic") { "i" } else { "" }),
+    ];
+    
+    // Alle Kombinationen durchprobieren
+    for dir in font_dirs {
+        for name in &possible_names {
+            let font_path = std::path::Path::new(&dir).join(name);
+            if font_path.exists() && font_path.is_file() {
+                debug_print_global(&format!("Font gefunden: {} -> {}", font_name, font_path.display()));
+                return Some(font_path);
+            }
+        }
+    }
+    
+    debug_print_global(&format!("Font NICHT gefunden: {} ({})", font_name, style));
+    None
+}
+
+// Pfad einer Zusatzdatei (Logo, Bild) auflösen: absolute Pfade bleiben, relative Pfade
+// beziehen sich auf das Programmverzeichnis (z.B. "Data/logo.png")
+fn resolve_asset_path(path: &str) -> std::path::PathBuf {
+    let p = std::path::Path::new(path);
+    if p.is_absolute() {
+        return p.to_path_buf();
+    }
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+    exe_dir.join(p)
+}
+
+/// In ein Dokument eingebettete Grafik (Bild-XObject oder Form-XObject aus einer PDF-Seite)
+///
+/// Beide Varianten werden auf das Einheitsquadrat abgebildet, d.h. sie lassen sich wie ein
+/// Bild mit `w 0 0 h x y cm` platzieren. `width`/`height` sind die natürlichen Maße und
+/// werden nur für das Seitenverhältnis verwendet.
+#[derive(Clone, Copy, Debug)]
+struct EmbeddedGraphic {
+    id: lopdf::ObjectId,
+    width: f32,
+    height: f32,
+}
+
+// Grafikdatei anhand der Endung einbetten: PDF (erste Seite), JPEG (unverändert) oder
+// alle anderen vom image-Crate unterstützten Formate (PNG inkl. Alphakanal)
+fn embed_graphic_file(doc: &mut Document, path: &std::path::Path) -> Result<EmbeddedGraphic, String> {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match ext.as_str() {
+        "pdf" => embed_pdf_page_as_form(doc, path),
+        "jpg" | "jpeg" => {
+            let bytes = std::fs::read(path).map_err(|e| format!("Konnte {} nicht lesen: {}", path.display(), e))?;
+            embed_jpeg(doc, bytes).or_else(|_| embed_raster_image(doc, path))
+        }
+        _ => embed_raster_image(doc, path),
+    }
+}
+
+// Breite, Höhe, Komponenten und Adobe-Marker (invertiertes CMYK) aus dem JPEG-Header lesen
+fn jpeg_header_info(bytes: &[u8]) -> Option<(u32, u32, u8, bool)> {
+    if bytes.len() < 4 || bytes[0] != 0xFF || bytes[1] != 0xD8 {
+        return None;
+    }
+    let mut adobe = false;
+    let mut i = 2;
+    while i + 4 <= bytes.len() {
+        if bytes[i] != 0xFF {
+            return None;
+        }
+        let marker = bytes[i + 1];
+        let len = u16::from_be_bytes([bytes[i + 2], bytes[i + 3]]) as usize;
+        if marker == 0xEE && bytes.get(i + 4..i + 9) == Some(b"Adobe") {
+            adobe = true;
+        }
+        // SOF-Marker (ohne DHT, JPG und DAC)
+        if (0xC0..=0xCF).contains(&marker) && marker != 0xC4 && marker != 0xC8 && marker != 0xCC {
+            let seg = bytes.get(i + 4..i + 10)?;
+            let height = u16::from_be_bytes([seg[1], seg[2]]) as u32;
+            let width = u16::from_be_bytes([seg[3], seg[4]]) as u32;
+            return Some((width, height, seg[5], adobe));
+        }
+        i += 2 + len;
+    }
+    None
+}
+
+// JPEG ohne Neukodierung als DCTDecode-Stream einbetten
+fn embed_jpeg(doc: &mut Document, bytes: Vec<u8>) -> Result<EmbeddedGraphic, String> {
+    let (width, height, components, adobe) = jpeg_header_info(&bytes).ok_or("Ungültiger JPEG-Header")?;
+    let color_space = match components {
+        1 => "DeviceGray",
+        3 => "DeviceRGB",
+        4 => "DeviceCMYK",
+        n => return Err(format!("JPEG mit {} Farbkomponenten wird nicht unterstützt", n)),
+    };
+    let mut dict = dictionary! {
+        "Type" => "XObject",
+        "Subtype" => "Image",
+        "Width" => width as i64,
+        "Height" => height as i64,
+        "ColorSpace" => color_space,
+        "BitsPerComponent" => 8,
+        "Filter" => "DCTDecode",
+    };
+    if components == 4 && adobe {
+        // Adobe-CMYK-JPEGs sind invertiert gespeichert
+        dict.set("Decode", vec![1.into(), 0.into(), 1.into(), 0.into(), 1.into(), 0.into(), 1.into(), 0.into()]);
+    }
+    let id = doc.add_object(lopdf::Stream::new(dict, bytes));
+    Ok(EmbeddedGraphic { id, width: width as f32, height: height as f32 })
+}
+
+// Rasterbild dekodieren und als RGB-Bild (Flate) mit optionaler SMask für Transparenz einbetten
+fn embed_raster_image(doc: &mut Document, path: &std::path::Path) -> Result<EmbeddedGraphic, String> {
+    let img = image::open(path).map_err(|e| format!("Konnte Bild {} nicht laden: {}", path.display(), e))?;
+    let rgba = img.to_rgba8();
+    let (width, height) = rgba.dimensions();
+
+    let mut rgb = Vec::with_capacity((width * height * 3) as usize);
+    let mut alpha = Vec::with_capacity((width * height) as usize);
+    for px in rgba.pixels() {
+        rgb.extend_from_slice(&px.0[..3]);
+        alpha.push(px.0[3]);
+    }
+
+    let mut dict = dictionary! {
+        "Type" => "XObject",
+        "Subtype" => "Image",
+        "Width" => width as i64,
+        "Height" => height as i64,
+        "ColorSpace" => "DeviceRGB",
+        "BitsPerComponent" => 8,
+    };
+    if alpha.iter().any(|&a| a < 255) {
+        let mut smask = lopdf::Stream::new(dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => width as i64,
+            "Height" => height as i64,
+            "ColorSpace" => "DeviceGray",
+            "BitsPerComponent" => 8,
+        }, alpha);
+        let _ = smask.compress();
+        let smask_id = doc.add_object(smask);
+        dict.set("SMask", smask_id);
+    }
+    let mut stream = lopdf::Stream::new(dict, rgb);
+    let _ = stream.compress();
+    let id = doc.add_object(stream);
+    Ok(EmbeddedGraphic { id, width: width as f32, height: height as f32 })
+}
+
+// Geerbten Seiteneintrag (Resources, MediaBox, CropBox, Rotate) über die Parent-Kette suchen
+fn get_inherited_page_attr(doc: &Document, page_id: lopdf::ObjectId, key: &[u8]) -> Option<Object> {
+    let mut current = Some(page_id);
+    let mut depth = 0;
+    while let Some(id) = current {
+        let dict = doc.get_dictionary(id).ok()?;
+        if let Ok(value) = dict.get(key) {
+            return Some(value.clone());
+        }
+        current = dict.get(b"Parent").and_then(|p| p.as_reference()).ok();
+        depth += 1;
+        if depth > 64 {
+            break; // Schutz gegen zyklische Seitenbäume
+        }
+    }
+    None
+}
+
+// Alle von `obj` aus erreichbaren indirekten Objekte sammeln (ohne Parent-Verweise,
+// damit beim Kopieren nicht der komplette Seitenbaum mitgenommen wird)
+fn collect_referenced_ids(doc: &Document, obj: &Object, seen: &mut std::collections::BTreeSet<lopdf::ObjectId>) {
+    match obj {
+        Object::Reference(id)
+            if seen.insert(*id) => {
+                if let Ok(target) = doc.get_object(*id) {
+                    collect_referenced_ids(doc, target, seen);
+                }
+            }
+        Object::Array(items) => {
+            for item in items {
+                collect_referenced_ids(doc, item, seen);
+            }
+        }
+        Object::Dictionary(dict) => {
+            for (key, value) in dict.iter() {
+                if key.as_slice() != b"Parent" {
+                    collect_referenced_ids(doc, value, seen);
+                }
+            }
+        }
+        Object::Stream(stream) => {
+            for (key, value) in stream.dict.iter() {
+                if key.as_slice() != b"Parent" {
+                    collect_referenced_ids(doc, value, seen);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+// Erste Seite einer PDF-Datei als Form-XObject in `doc` übernehmen (Vektorlogos bleiben Vektoren)
+fn embed_pdf_page_as_form(doc: &mut Document, path: &std::path::Path) -> Result<EmbeddedGraphic, String> {
+    let mut src = Document::load(path).map_err(|e| format!("Konnte PDF {} nicht laden: {}", path.display(), e))?;
+    // Objekt-IDs der Quelle hinter die des Zieldokuments verschieben, damit nichts kollidiert
+    src.renumber_objects_with(doc.max_id + 1);
+    let page_id = *src.get_pages().get(&1).ok_or_else(|| format!("PDF {} hat keine Seiten", path.display()))?;
+
+    let content = src.get_page_content(page_id).map_err(|e| format!("Seiteninhalt von {} nicht lesbar: {}", path.display(), e))?;
+    let resources = get_inherited_page_attr(&src, page_id, b"Resources").unwrap_or_else(|| Object::Dictionary(dictionary! {}));
+    let bbox: Vec<f32> = get_inherited_page_attr(&src, page_id, b"CropBox")
+        .or_else(|| get_inherited_page_attr(&src, page_id, b"MediaBox"))
+        .and_then(|b| b.as_array().ok().map(|a| a.iter().filter_map(|v| v.as_float().ok()).collect()))
+        .filter(|b: &Vec<f32>| b.len() == 4)
+        .unwrap_or_else(|| vec![0.0, 0.0, 595.0, 842.0]);
+    let (llx, lly, urx, ury) = (bbox[0].min(bbox[2]), bbox[1].min(bbox[3]), bbox[0].max(bbox[2]), bbox[1].max(bbox[3]));
+    let (w, h) = ((urx - llx).max(1.0), (ury - lly).max(1.0));
+
+    // Von den Ressourcen erreichbare Objekte (Fonts, Bilder, ...) ins Zieldokument kopieren
+    let mut needed = std::collections::BTreeSet::new();
+    collect_referenced_ids(&src, &resources, &mut needed);
+    for id in needed {
+        if let Ok(obj) = src.get_object(id) {
+            doc.objects.insert(id, obj.clone());
+        }
+    }
+    doc.max_id = doc.max_id.max(src.max_id);
+
+    // Matrix bildet die BBox auf das Einheitsquadrat ab (wie bei Bild-XObjects)
+    let mut form = lopdf::Stream::new(dictionary! {
+        "Type" => "XObject",
+        "Subtype" => "Form",
+        "BBox" => vec![llx.into(), lly.into(), urx.into(), ury.into()],
+        "Matrix" => vec![(1.0 / w).into(), 0.into(), 0.into(), (1.0 / h).into(), (-llx / w).into(), (-lly / h).into()],
+        "Resources" => resources,
+    }, content);
+    let _ = form.compress();
+    let id = doc.add_object(form);
+    Ok(EmbeddedGraphic { id, width: w, height: h })
+}
+
+// Graustufen-Matrix eines QR-Codes als Bild-XObject hinzufügen
+fn add_qr_image(doc: &mut Document, data: &[u8], width: usize) -> lopdf::ObjectId {
+    add_gray_image(doc, data, width, width)
+}
+
+// 8-Bit-Graustufenbild als XObject (eine Bildzeile pro Modulzeile, Skalierung erst beim Zeichnen)
+fn add_gray_image(doc: &mut Document, data: &[u8], width: usize, height: usize) -> lopdf::ObjectId {
+    doc.add_object(lopdf::Object::Stream(lopdf::Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => width as i64,
+            "Height" => height as i64,
+            "ColorSpace" => "DeviceGray",
+            "BitsPerComponent" => 8,
+        },
+        data.to_vec(),
+    )))
+}
+
+/// Für ein Strichcode-Element erzeugtes Bild und die Klartextzeile darunter
+struct BarcodeImage {
+    image_id: lopdf::ObjectId,
+    text: String,
+}
+
+/// Für ein QR-Element erzeugte PDF-Objekte: das Code-Bild und optional das Logo
+/// mitsamt Anteil der freigeräumten Fläche an der QR-Kantenlänge
+#[derive(Clone, Copy, Debug)]
+struct QrImageRefs {
+    image_id: lopdf::ObjectId,
+    logo: Option<(EmbeddedGraphic, f32)>,
+}
+
+/// Einmal je Lauf geladene Vorlage. Wird von allen Threads nur gelesen; je Zeile wird das
+/// Dokument im Speicher geklont und nur das Overlay ergänzt.
+struct PreparedTemplate {
+    path: String,
+    doc: Document,
+    /// Bereits in `doc` eingebettete QR-Logos je Logo-Pfad
+    logos: std::collections::HashMap<String, EmbeddedGraphic>,
+    /// Bereits eingebettete Bilder mit festem Pfad (ohne Platzhalter)
+    images: std::collections::HashMap<String, EmbeddedGraphic>,
+    /// SHA-256 der Vorlagendatei (hex), landet in den Metadaten jeder Ausgabe
+    hash: String,
+    /// Eingetragene Ausgabebedingung, nur im PDF/A-Modus
+    pdfa: Option<PdfAIntent>,
+    /// Aufgelöste Seitenauswahl der Ausgabe (Seitennummern der Vorlage), None = alle Seiten
+    output_pages: Option<Vec<u32>>,
+}
+
+// Vorlage laden und die für alle Zeilen gleichen Objekte (Logos, feste Bilder) einmal einbetten
+fn prepare_template(template_path: &str, config: &Config, with_qr: bool, output_intent: Option<&OutputIntentProfile>, debug_enabled: bool) -> Result<PreparedTemplate, String> {
+    debug_print(&format!("Lade PDF-Template: {}", template_path), debug_enabled);
+    let bytes = std::fs::read(template_path)
+        .map_err(|e| format!("Konnte PDF-Template nicht lesen: {} - {}", template_path, e))?;
+    let hash = sha256_hex(&bytes);
+    let mut doc = Document::load_mem(&bytes)
+        .map_err(|e| format!("Konnte PDF-Template nicht laden: {} - {}", template_path, e))?;
+    // Seitenauswahl gegen die Seitenzahl der Vorlage prüfen, bevor Zeilen erzeugt werden
+    let output_pages = if config.output_pages.trim().is_empty() {
+        None
+    } else {
+        let page_count = doc.get_pages().len() as u32;
+        Some(resolve_page_selection(&config.output_pages, page_count).map_err(|e| format!("Ausgabeseiten '{}': {}", config.output_pages, e))?)
+    };
+    let mut logos = std::collections::HashMap::new();
+    if with_qr {
+        for qr in config.qr_codes().filter(|qr| !qr.logo_path.is_empty()) {
+            if logos.contains_key(&qr.logo_path) {
+                continue;
+            }
+            match embed_graphic_file(&mut doc, &resolve_asset_path(&qr.logo_path)) {
+                Ok(graphic) => {
+                    logos.insert(qr.logo_path.clone(), graphic);
+                }
+                Err(e) => println!("WARNUNG: Logo {} kann nicht eingebettet werden, QR-Codes werden ohne Logo erzeugt: {}", qr.logo_path, e),
+            }
+        }
+    }
+    // Bilder mit festem Pfad; schlägt das Einbetten fehl, wird es je Zeile erneut versucht und
+    // dort als Fehler der Zeile gemeldet
+    let mut images = std::collections::HashMap::new();
+    for image in config.images().filter(|img| !img.path.trim().is_empty() && !img.path.contains('{')) {
+        let path = image.path.trim();
+        if images.contains_key(path) {
+            continue;
+        }
+        match embed_graphic_file(&mut doc, &resolve_asset_path(path)) {
+            Ok(graphic) => {
+                images.insert(path.to_string(), graphic);
+            }
+            Err(e) => println!("WARNUNG: Bild {} kann nicht eingebettet werden: {}", path, e),
+        }
+    }
+    debug_print(&format!("PDF-Template vorbereitet: {} ({} Objekte)", template_path, doc.objects.len()), debug_enabled);
+    // PDF/A: Ausgabebedingung einmal in die Vorlage eintragen, alle Zeilen teilen sie
+    let pdfa = output_intent.map(|profile| add_output_intent(&mut doc, profile));
+    Ok(PreparedTemplate { path: template_path.to_string(), doc, logos, images, hash, pdfa, output_pages })
+}
+
+// SHA-256 als Hex-String, für die Vorlagen-Prüfsumme in den Metadaten
+fn sha256_hex(data: &[u8]) -> String {
+    use sha2::Digest;
+    sha2::Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Eine Vorlage der Auswahl mit allem, was je Lauf nur einmal bestimmt werden muss
+struct TemplateJob {
+    /// Eintrag der Auswahl (für den Laufbericht)
+    template: String,
+    gen_qr: bool,
+    group: String,
+    lang_code: String,
+    stem: String,
+    output_dir: std::path::PathBuf,
+    config: Config,
+    prepared: Result<PreparedTemplate, String>,
+}
+
+// Auswahl (CSV, Vorlage, QR ja/nein) in vorbereitete Jobs übersetzen; doppelte Vorlagen
+// werden nur einmal erzeugt
+fn prepare_template_jobs(selections: &[(String, String, bool)], language: &str, use_custom_output: bool, custom_output_path: &str, run_options: &RunOptions, debug_mode: bool) -> Vec<TemplateJob> {
+    let mut jobs: Vec<TemplateJob> = Vec::new();
+    // ICC-Profil der PDF/A-Ausgabebedingung einmal je Lauf laden
+    let output_intent = (run_options.output_mode == OutputMode::PdfA).then(|| load_output_intent_profile(&run_options.icc_profile));
+    for (csv_s, template_s, gen_qr) in selections {
+        if jobs.iter().any(|job| &job.template == template_s) {
+            continue;
+        }
+        // Template-Pfad korrekt auflösen
+        let resolved_template = resolve_template_path_with_debug(template_s, debug_mode);
+        let resolved_template_str = resolved_template.to_string_lossy().to_string();
+        debug_print_global(&format!("template_s='{}' -> resolved='{}' (exists={})", template_s, resolved_template_str, resolved_template.exists()));
+
+        // Bestimme kanonischen Sprachcode (de_de / en_us) anhand Template, CSV oder UI
+        let lang_code = detect_language_code(language, Some(&resolved_template_str), Some(csv_s));
+        let (group, _template_lang, tpl_is_messe) = infer_group_lang_from_template(&resolved_template_str);
+        let stem = resolved_template.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let output_dir = get_configured_output_dir_with_debug(use_custom_output, custom_output_path, &group, &lang_code, tpl_is_messe, debug_mode);
+        debug_print_global(&format!("detected_lang_code='{}', output_dir='{}'", lang_code, output_dir.display()));
+
+        // Verwende aktuelle UI-Config falls verfügbar, sonst fallback zu group config (mit detektiertem Sprachcode)
+        let mut config = {
+            let _lock = CONFIG_MUTEX.lock().unwrap();
+            unsafe {
+                if let Some(ref current_config) = CURRENT_CONFIG {
+                    println!("🎯 Verwende aktuelle UI-Config für PDF-Generierung: Elemente={:?}", current_config.elements);
+                    current_config.clone()
+                } else {
+                    println!("⚠️ Keine UI-Config verfügbar, lade Group-Config (detected lang: {})", lang_code);
+                    load_group_config(&group, &lang_code, tpl_is_messe)
+                }
+            }
+        };
+        // Ausgeblendete Elemente gar nicht erst erzeugen (keine Codes, Bilder oder Formularfelder)
+        config.elements.retain(|e| e.visible);
+
+        let prepared = if resolved_template.exists() {
+            match &output_intent {
+                Some(Err(e)) => Err(format!("PDF/A: {}", e)),
+                Some(Ok(profile)) => prepare_template(&resolved_template_str, &config, *gen_qr, Some(profile), debug_mode),
+                None => prepare_template(&resolved_template_str, &config, *gen_qr, None, debug_mode),
+            }
+        } else {
+            Err("Template-Datei nicht gefunden".to_string())
+        };
+        if let Err(e) = &prepared {
+            println!("ERROR: {} ({})", e, resolved_template_str);
+        }
+        jobs.push(TemplateJob { template: template_s.clone(), gen_qr: *gen_qr, group, lang_code, stem, output_dir, config, prepared });
+    }
+    jobs
+}
+
+/// Erzeugt eine PDF aus Vorlage und Config. Jede QR-Matrix wird vor dem Einbetten dekodiert;
+/// mit `verify_saved` werden die Bilder zusätzlich aus der gespeicherten Datei zurückgelesen.
+/// Stimmt ein Inhalt nicht, wird keine (bzw. keine fehlerhafte) Datei hinterlassen.
+/// Neben der Prüfstufe liefert sie Hinweise für den Laufbericht (z.B. QR-Code ohne Logo).
+#[allow(clippy::too_many_arguments)]
+fn modify_pdf_with_debug(template: &PreparedTemplate, vertreter_texts: &[String], qr_payloads: &[Option<String>], barcode_contents: &[String], image_paths: &[String], metadata: &PdfMetadata, config: &Config, output_path: &std::path::Path, verify_saved: bool, output_mode: OutputMode, debug_enabled: bool) -> Result<(QrVerification, Vec<String>), String> {
+    // Vorbereitete Vorlage im Speicher klonen statt sie je Zeile neu zu parsen
+    debug_print(&format!("Verwende PDF-Template: {}", template.path), debug_enabled);
+    let mut doc = template.doc.clone();
+
+    // Elemente mit Formularfeld: QR-Codes in die Feldrechtecke setzen (je Widget ein Code)
+    let widgets = if uses_form_fields(config) { form_widgets(&doc) } else { Vec::new() };
+    let (placed, qr_payloads) = place_qr_in_form_fields(&mut doc, config, qr_payloads, &widgets)?;
+    let config: &Config = placed.as_ref().unwrap_or(config);
+    let qr_payloads: &[Option<String>] = &qr_payloads;
+    
+    // QR-Codes als XObjects registrieren (ein Inhalt pro QR-Element, None = kein QR-Code).
+    // Elemente mit gleichem Inhalt ohne Logo teilen sich ein Bild, Elemente mit Logo erhalten
+    // ein eigenes (Level H, freigeräumte Mitte) plus das eingebettete Logo.
+    let mut qr_images: Vec<Option<QrImageRefs>> = vec![None; config.qr_codes().count()];
+    // Eingebettete QR-Bilder mit erwartetem Inhalt für die Prüfung nach dem Speichern
+    let mut embedded_qr: Vec<(lopdf::ObjectId, String)> = Vec::new();
+    // Hinweise für den Laufbericht (Datei wurde erstellt, aber abweichend von der Config)
+    let mut notes: Vec<String> = Vec::new();
+    {
+        let mut shared_images: std::collections::HashMap<&str, lopdf::ObjectId> = std::collections::HashMap::new();
+        for (idx, qr_config) in config.qr_codes().enumerate() {
+            let Some(payload) = qr_payloads.get(idx).and_then(|p| p.as_deref()) else { continue };
+            // Logos sind bereits in der vorbereiteten Vorlage eingebettet
+            if let Some(graphic) = template.logos.get(&qr_config.logo_path).copied() {
+                match generate_qr_with_logo(payload, qr_config.logo_size) {
+                    Ok((data, width, logo_fraction)) => {
+                        let image_id = add_qr_image(&mut doc, &data, width);
+                        embedded_qr.push((image_id, payload.to_string()));
+                        debug_print(&format!("QR-Code {} mit Logo {} erzeugt (verifiziert)", idx + 1, qr_config.logo_path), debug_enabled);
+                        qr_images[idx] = Some(QrImageRefs { image_id, logo: Some((graphic, logo_fraction)) });
+                        continue;
+                    }
+                    Err(e) => {
+                        println!("WARNUNG: QR-Code {} ohne Logo erzeugt: {}", idx + 1, e);
+                        notes.push(format!("QR-Code {} ohne Logo: {}", idx + 1, e));
+                    }
+                }
+            }
+            let image_id = match shared_images.get(payload) {
+                Some(id) => *id,
+                None => {
+                    let (data, width) = generate_qr(payload)
+                        .map_err(|e| format!("QR-Code {}: {}", idx + 1, e))?;
+                    verify_qr_matrix(&data, width, payload)
+                        .map_err(|e| format!("QR-Code {}: {}", idx + 1, e))?;
+                    let id = add_qr_image(&mut doc, &data, width);
+                    embedded_qr.push((id, payload.to_string()));
+                    shared_images.insert(payload, id);
+                    debug_print(&format!("QR-Code {} erzeugt (verifiziert)", idx + 1), debug_enabled);
+                    id
+                }
+            };
+            qr_images[idx] = Some(QrImageRefs { image_id, logo: None });
+        }
+    }
+
+    // Strichcodes erzeugen (parallel zu config.barcodes)
+    let mut barcode_images: Vec<BarcodeImage> = Vec::with_capacity(config.barcodes().count());
+    for (idx, (barcode, content)) in config.barcodes().zip(barcode_contents.iter()).enumerate() {
+        let matrix = encode_barcode(barcode.symbology, content)
+            .map_err(|e| format!("Strichcode {}: {}", idx + 1, e))?;
+        let image_id = add_gray_image(&mut doc, &matrix.data, matrix.width, matrix.height);
+        debug_print(&format!("Strichcode {} ({}) erzeugt: {}x{} Module", idx + 1, barcode.symbology.label(), matrix.width, matrix.height), debug_enabled);
+        barcode_images.push(BarcodeImage { image_id, text: matrix.text });
+    }
+
+    // Bilder (parallel zu config.images): feste Pfade sind bereits in der Vorlage eingebettet,
+    // Pfade aus der CSV werden je Zeile eingebettet; leerer Pfad = kein Bild
+    let mut element_images: Vec<Option<EmbeddedGraphic>> = Vec::with_capacity(config.images().count());
+    {
+        let mut row_images: std::collections::HashMap<&str, EmbeddedGraphic> = std::collections::HashMap::new();
+        for (idx, path) in image_paths.iter().enumerate() {
+            if path.is_empty() {
+                element_images.push(None);
+                continue;
+            }
+            let graphic = match template.images.get(path).or_else(|| row_images.get(path.as_str())).copied() {
+                Some(graphic) => graphic,
+                None => {
+                    let graphic = embed_graphic_file(&mut doc, &resolve_asset_path(path))
+                        .map_err(|e| format!("Bild {} ({}): {}", idx + 1, path, e))?;
+                    debug_print(&format!("Bild {} eingebettet: {}", idx + 1, path), debug_enabled);
+                    row_images.insert(path.as_str(), graphic);
+                    graphic
+                }
+            };
+            element_images.push(Some(graphic));
+        }
+    }
+
+    // Schriftdateien der Vertreternummer-Felder als Teilmenge einbetten (einmal je Schrift und
+    // Dokument); ohne Datei oder bei fehlenden Glyphen bleibt es beim Standard-PDF-Font
+    let mut embedded_fonts: Vec<EmbeddedFont> = Vec::new();
+    let mut vertreter_fonts: Vec<Option<usize>> = Vec::with_capacity(config.vertreter().count());
+    {
+        let mut by_font: std::collections::HashMap<(&str, &str), Option<usize>> = std::collections::HashMap::new();
+        for v in config.vertreter() {
+            let key = (v.font_name.as_str(), v.font_style.as_str());
+            if let Some(idx) = by_font.get(&key) {
+                vertreter_fonts.push(*idx);
+                continue;
+            }
+            let text: String = config.vertreter()
+                .zip(vertreter_texts.iter())
+                .filter(|(other, _)| other.font_name == v.font_name && other.font_style == v.font_style)
+                .map(|(_, text)| text.as_str())
+                .collect();
+            let idx = match load_font_file(&v.font_name, &v.font_style) {
+                Some(data) => match embed_font_subset(&mut doc, &data, &text) {
+                    Ok(font) => {
+                        debug_print(&format!("Schrift eingebettet: {} ({}), {} Glyphen", v.font_name, v.font_style, font.glyphs.len()), debug_enabled);
+                        embedded_fonts.push(font);
+                        Some(embedded_fonts.len() - 1)
+                    }
+                    Err(e) => {
+                        println!("WARNUNG: Schrift {} ({}) konnte nicht eingebettet werden: {} - verwende Standard-PDF-Font", v.font_name, v.font_style, e);
+                        None
+                    }
+                },
+                None => {
+                    debug_print(&format!("Schriftdatei NICHT gefunden: {} ({}), verwende Standard-PDF-Font", v.font_name, v.font_style), debug_enabled);
+                    None
+                }
+            };
+            by_font.insert(key, idx);
+            vertreter_fonts.push(idx);
+        }
+    }
+
+    // Unicode-Ersatzschrift nur einbetten, wenn ein Text für Standard-Fonts nicht in WinAnsi darstellbar ist.
+    // PDF/A erlaubt keine Standard-Fonts ohne Einbettung, dort läuft jeder solche Text über die Ersatzschrift.
+    let embed_all = template.pdfa.is_some();
+    let needs_fallback = |text: &str| embed_all || encode_win_ansi(text).is_none();
+    let mut fallback_text = String::new();
+    for (idx, text) in vertreter_fonts.iter().zip(vertreter_texts.iter()) {
+        if idx.is_none() && needs_fallback(text) {
+            fallback_text.push_str(text);
+        }
+    }
+    for qr in config.qr_codes() {
+        if needs_fallback(&qr.caption) {
+            fallback_text.push_str(&qr.caption);
+        }
+    }
+    for (barcode, image) in config.barcodes().zip(barcode_images.iter()) {
+        if barcode.show_text && needs_fallback(&image.text) {
+            fallback_text.push_str(&image.text);
+        }
+    }
+    let fallback_font = if fallback_text.is_empty() { None } else { embed_unicode_fallback_font(&mut doc, &fallback_text, debug_enabled) };
+    if embed_all && !fallback_text.is_empty() && fallback_font.is_none() {
+        return Err("PDF/A: keine einbettbare Schrift für Texte mit Standard-Font gefunden".to_string());
+    }
+
+    // Alle Seiten des PDFs ermitteln
+    let all_pages: Vec<u32> = doc.get_pages().keys().cloned().collect();
+    debug_print(&format!("PDF hat {} Seiten: {:?}", all_pages.len(), all_pages), debug_enabled);
+
+    // Index jedes Elements innerhalb seiner Art (für die parallel erzeugten Bilder und Texte)
+    let kind_indices: Vec<usize> = {
+        let mut counts: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+        config.elements.iter().map(|e| {
+            let count = counts.entry(e.kind.key()).or_insert(0);
+            *count += 1;
+            *count - 1
+        }).collect()
+    };
+    let draw_order = config.draw_order();
+
+    // Für jede Seite prüfen, welche Elemente darauf platziert werden sollen
+    for page_number in all_pages {
+        let page_id = doc.get_pages().get(&page_number).copied().unwrap();
+        
+        // Elemente dieser Seite in Zeichenreihenfolge sammeln
+        let mut items: Vec<PageItem> = Vec::new();
+        for &i in &draw_order {
+            let element = &config.elements[i];
+            if !element.visible || !element.on_page(page_number) {
+                continue;
+            }
+            let k = kind_indices[i];
+            match &element.kind {
+                ElementKind::Qr(qr) => {
+                    if let Some(images) = qr_images[k] {
+                        items.push(PageItem::Qr(qr, images));
+                    }
+                }
+                ElementKind::Barcode(barcode) => items.push(PageItem::Barcode(barcode, &barcode_images[k])),
+                ElementKind::Image(image) => {
+                    if let Some(graphic) = element_images[k] {
+                        items.push(PageItem::Image(image, graphic));
+                    }
+                }
+                // Texte mit Formularfeld werden ins Feld geschrieben, nicht ins Overlay
+                ElementKind::Text(v) if v.form_field.is_empty() => {
+                    items.push(PageItem::Text(v, vertreter_texts[k].as_str(), vertreter_fonts[k].map(|idx| &embedded_fonts[idx])));
+                }
+                ElementKind::Text(_) => {}
+                ElementKind::Rect(rect) => items.push(PageItem::Rect(rect)),
+                ElementKind::Line(line) => items.push(PageItem::Line(line)),
+            }
+        }
+        
+        // Nur wenn Elemente auf dieser Seite platziert werden sollen
+        if !items.is_empty() {
+            debug_print(&format!("Bearbeite Seite {}: {} Elemente", page_number, items.len()), debug_enabled);
+                
+            process_page_elements(&mut doc, page_id, page_number, &items, fallback_font.as_ref(), embed_all, debug_enabled);
+        } else {
+            debug_print(&format!("Seite {} übersprungen - keine Elemente zu platzieren", page_number), debug_enabled);
+        }
+    }
+    
+    // Textfelder der Vorlage ausfüllen (Wert + Erscheinungsbild), danach ggf. flach rechnen
+    for ((v, text), font) in config.vertreter().zip(vertreter_texts.iter()).zip(vertreter_fonts.iter()) {
+        if v.form_field.is_empty() {
+            continue;
+        }
+        let targets: Vec<FormWidget> = matching_form_widgets(&widgets, &v.form_field).cloned().collect();
+        if targets.is_empty() {
+            return Err(format!("Formularfeld '{}' nicht in der Vorlage gefunden", v.form_field));
+        }
+        fill_text_field(&mut doc, &targets, v, text, font.map(|idx| &embedded_fonts[idx]), fallback_font.as_ref());
+        debug_print(&format!("Formularfeld '{}' ausgefüllt ({} Widgets)", v.form_field, targets.len()), debug_enabled);
+    }
+    if config.flatten_form {
+        let flattened = flatten_form_fields(&mut doc);
+        debug_print(&format!("{} Formular-Widgets flach gerechnet", flattened), debug_enabled);
+    }
+
+    // Seitenauswahl erst nach dem Platzieren, damit Seitenangaben der Elemente sich weiter auf
+    // die Vorlage beziehen. QR-Bilder weggelassener Seiten entfallen auch in der Prüfung.
+    if let Some(selection) = &template.output_pages {
+        select_output_pages(&mut doc, selection)?;
+        doc.prune_objects();
+        embedded_qr.retain(|(id, _)| doc.objects.contains_key(id));
+        debug_print(&format!("Ausgabeseiten: {:?}", selection), debug_enabled);
+    }
+
+    // Titel, Autor usw. je Ausgabe statt der Angaben der Vorlage
+    apply_document_metadata(&mut doc, metadata, template.pdfa.is_some());
+
+    // PDF/A: behebbare Punkte korrigieren, alles andere als Fehler der Zeile melden
+    if let Some(intent) = template.pdfa {
+        if config.encryption.enabled {
+            return Err("PDF/A erlaubt keine Verschlüsselung - Passwortschutz der Gruppe deaktivieren".to_string());
+        }
+        let issues = make_pdfa_conformant(&mut doc, intent, &output_path.to_string_lossy());
+        if !issues.is_empty() {
+            return Err(format!("Nicht PDF/A-2b-konform: {}", issues.join("; ")));
+        }
+    }
+    
+    // Sicherstellen dass der Output-Ordner existiert
+    if let Some(parent) = output_path.parent() {
+        if !parent.exists() {
+            match std::fs::create_dir_all(parent) {
+                Ok(()) => debug_print(&format!("Output-Ordner erstellt: {}", parent.display()), debug_enabled),
+                Err(e) => {
+                    return Err(format!("Konnte Output-Ordner nicht erstellen: {} - {}", parent.display(), e));
+                }
+            }
+        } else {
+            debug_print(&format!("Output-Ordner existiert bereits: {}", parent.display()), debug_enabled);
+        }
+    }
+    
+    // Ausgabe je Modus vorbereiten
+    match output_mode {
+        OutputMode::Fast => {}
+        // Nicht benötigte Objekte wurden bereits bei der PDF/A-Prüfung entfernt
+        OutputMode::PdfA => doc.compress(),
+        OutputMode::Small => {
+            // Zusammengefasste QR-Bilder unter ihrer neuen ID prüfen
+            let merged = optimize_pdf(&mut doc);
+            for (id, _) in embedded_qr.iter_mut() {
+                if let Some(canonical) = merged.get(id) {
+                    *id = *canonical;
+                }
+            }
+        }
+    }
+
+    // Verschlüsselung zuletzt (nach dem Komprimieren, verschlüsselte Daten lassen sich nicht mehr packen)
+    let encrypted = config.encryption.enabled;
+    if encrypted {
+        encrypt_document(&mut doc, &config.encryption)?;
+        debug_print("PDF mit AES-256 verschlüsselt", debug_enabled);
+    }
+
+    // PDF in den angegebenen Pfad speichern. Objekt-Streams nur unverschlüsselt: ihr Inhalt würde
+    // beim Schreiben aus bereits einzeln verschlüsselten Objekten gebildet.
+    let saved = if output_mode == OutputMode::Small && !encrypted {
+        std::fs::File::create(output_path)
+            .and_then(|file| doc.save_modern(&mut std::io::BufWriter::new(file)))
+    } else {
+        doc.save(output_path).map(|_| ())
+    };
+    match saved {
+        Ok(()) => debug_print(&format!("PDF erfolgreich gespeichert: {}", output_path.display()), debug_enabled),
+        Err(e) => {
+            return Err(format!("Konnte PDF nicht speichern: {} - {}", output_path.display(), e));
+        }
+    }
+
+    if embedded_qr.is_empty() {
+        return Ok((QrVerification::NoQr, notes));
+    }
+    // Verschlüsselte Dateien lassen sich ohne Passwort nicht zurücklesen
+    if !verify_saved || encrypted {
+        return Ok((QrVerification::Matrix, notes));
+    }
+    match verify_qr_images_in_saved_pdf(output_path, &embedded_qr) {
+        Ok(()) => {
+            debug_print(&format!("{} QR-Bilder in gespeicherter PDF verifiziert", embedded_qr.len()), debug_enabled);
+            Ok((QrVerification::SavedPdf, notes))
+        }
+        Err(e) => {
+            // Fehlerhafte Datei nicht liegen lassen, sonst würde sie beim nächsten Lauf übersprungen
+            let _ = std::fs::remove_file(output_path);
+            Err(e)
+        }
+    }
+}
+
+// Info-Dictionary ersetzen und XMP-Metadaten am Katalog setzen. Von der Vorlage bleibt nur
+// /Creator (Programm, mit dem die Vorlage erstellt wurde) erhalten.
+fn apply_document_metadata(doc: &mut Document, metadata: &PdfMetadata, pdfa: bool) {
+    let now = chrono::Local::now();
+    let pdf_date = format!("D:{}'", now.format("%Y%m%d%H%M%S%:z").to_string().replace(':', "'"));
+    let xmp_date = now.format("%Y-%m-%dT%H:%M:%S%:z").to_string();
+    let producer = generator_name();
+
+    let info_ref = doc.trailer.get(b"Info").and_then(Object::as_reference).ok();
+    let creator = doc.trailer.get(b"Info").ok()
+        .and_then(|info| resolve_object(doc, info))
+        .and_then(|info| info.as_dict().ok())
+        .and_then(|info| info.get(b"Creator").ok())
+        .and_then(|creator| creator.as_str().ok())
+        .map(decode_pdf_text_string);
+
+    let mut info = lopdf::Dictionary::new();
+    for (key, value) in [("Title", &metadata.title), ("Subject", &metadata.subject), ("Author", &metadata.author), ("Keywords", &metadata.keywords)] {
+        if !value.is_empty() {
+            info.set(key, pdf_text_string(value));
+        }
+    }
+    if let Some(creator) = &creator {
+        info.set("Creator", pdf_text_string(creator));
+    }
+    info.set("Producer", pdf_text_string(&producer));
+    info.set("CreationDate", Object::string_literal(pdf_date.as_str()));
+    info.set("ModDate", Object::string_literal(pdf_date.as_str()));
+    for (key, value) in &metadata.custom {
+        if !value.is_empty() {
+            info.set(key.as_bytes().to_vec(), pdf_text_string(value));
+        }
+    }
+    match info_ref {
+        Some(id) => { doc.objects.insert(id, Object::Dictionary(info)); }
+        None => {
+            let id = doc.add_object(info);
+            doc.trailer.set("Info", id);
+        }
+    }
+
+    let packet = xmp_packet(metadata, creator.as_deref(), &producer, &xmp_date, pdfa);
+    let mut stream = lopdf::Stream::new(dictionary! { "Type" => "Metadata", "Subtype" => "XML" }, packet.into_bytes());
+    // Unkomprimiert lassen, damit DMS und Suchindexer die Metadaten ohne PDF-Parser finden
+    stream.allows_compression = false;
+    let metadata_id = doc.add_object(stream);
+    if let Some(catalog) = doc.trailer.get(b"Root").and_then(Object::as_reference).ok()
+        .and_then(|root| doc.get_object_mut(root).and_then(Object::as_dict_mut).ok()) {
+        catalog.set("Metadata", metadata_id);
+    }
+}
+
+// XMP-Paket mit denselben Angaben wie das Info-Dictionary (eigene Schlüssel im pdfx-Namensraum).
+// Für PDF/A kommen die Kennung (Teil 2, Konformität B) und das Erweiterungsschema für die
+// eigenen Schlüssel hinzu.
+fn xmp_packet(metadata: &PdfMetadata, creator: Option<&str>, producer: &str, date: &str, pdfa: bool) -> String {
+    let mut props = String::new();
+    if pdfa {
+        props.push_str("   <pdfaid:part>2</pdfaid:part>\n   <pdfaid:conformance>B</pdfaid:conformance>\n");
+        props.push_str(&pdfx_extension_schema(metadata));
+    }
+    props.push_str("   <dc:format>application/pdf</dc:format>\n");
+    if !metadata.title.is_empty() {
+        props.push_str(&format!("   <dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:title>\n", xml_escape(&metadata.title)));
+    }
+    if !metadata.subject.is_empty() {
+        props.push_str(&format!("   <dc:description><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:description>\n", xml_escape(&metadata.subject)));
+    }
+    if !metadata.author.is_empty() {
+        props.push_str(&format!("   <dc:creator><rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq></dc:creator>\n", xml_escape(&metadata.author)));
+    }
+    if !metadata.keywords.is_empty() {
+        props.push_str(&format!("   <pdf:Keywords>{}</pdf:Keywords>\n", xml_escape(&metadata.keywords)));
+    }
+    props.push_str(&format!("   <pdf:Producer>{}</pdf:Producer>\n", xml_escape(producer)));
+    if let Some(creator) = creator {
+        props.push_str(&format!("   <xmp:CreatorTool>{}</xmp:CreatorTool>\n", xml_escape(creator)));
+    }
+    props.push_str(&format!("   <xmp:CreateDate>{0}</xmp:CreateDate>\n   <xmp:ModifyDate>{0}</xmp:ModifyDate>\n   <xmp:MetadataDate>{0}</xmp:MetadataDate>\n", date));
+    for (key, value) in &metadata.custom {
+        if !value.is_empty() {
+            let name = xml_name(key);
+            props.push_str(&format!("   <pdfx:{0}>{1}</pdfx:{0}>\n", name, xml_escape(value)));
+        }
+    }
+    format!(
+        "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n\
+ <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\
+  <rdf:Description rdf:about=\"\"\n\
+    xmlns:dc=\"http://purl.org/dc/elements/1.1/\"\n\
+    xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"\n\
+    xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\"\n\
+    xmlns:pdfx=\"http://ns.adobe.com/pdfx/1.3/\"\n\
+    xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\"\n\
+    xmlns:pdfaExtension=\"http://www.aiim.org/pdfa/ns/extension/\"\n\
+    xmlns:pdfaSchema=\"http://www.aiim.org/pdfa/ns/schema#\"\n\
+    xmlns:pdfaProperty=\"http://www.aiim.org/pdfa/ns/property#\">\n\
+{}  </rdf:Description>\n\
+ </rdf:RDF>\n\
+</x:xmpmeta>\n\
+<?xpacket end=\"w\"?>",
+        props
+    )
+}
+
+// Sonderzeichen für XML-Text escapen
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// Schlüssel als gültigen XML-Elementnamen schreiben (ungültige Zeichen werden zu '_')
+fn xml_name(key: &str) -> String {
+    let mut name: String = key.chars().map(|c| if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' { c } else { '_' }).collect();
+    if !name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    name
+}
+
+// PDF/A-Erweiterungsschema für die eigenen Schlüssel im pdfx-Namensraum (PDF/A verlangt für
+// jede nicht vordefinierte XMP-Eigenschaft eine Beschreibung)
+fn pdfx_extension_schema(metadata: &PdfMetadata) -> String {
+    let mut properties = String::new();
+    for (key, value) in &metadata.custom {
+        if value.is_empty() {
+            continue;
+        }
+        properties.push_str(&format!(
+            "        <rdf:li rdf:parseType=\"Resource\">\n         <pdfaProperty:name>{}</pdfaProperty:name>\n         <pdfaProperty:valueType>Text</pdfaProperty:valueType>\n         <pdfaProperty:category>external</pdfaProperty:category>\n         <pdfaProperty:description>{}</pdfaProperty:description>\n        </rdf:li>\n",
+            xml_name(key), xml_escape(key)
+        ));
+    }
+    if properties.is_empty() {
+        return String::new();
+    }
+    format!(
+        "   <pdfaExtension:schemas>\n    <rdf:Bag>\n     <rdf:li rdf:parseType=\"Resource\">\n      <pdfaSchema:schema>PDF Information Dictionary</pdfaSchema:schema>\n      <pdfaSchema:namespaceURI>http://ns.adobe.com/pdfx/1.3/</pdfaSchema:namespaceURI>\n      <pdfaSchema:prefix>pdfx</pdfaSchema:prefix>\n      <pdfaSchema:property>\n       <rdf:Seq>\n{}       </rdf:Seq>\n      </pdfaSchema:property>\n     </rdf:li>\n    </rdf:Bag>\n   </pdfaExtension:schemas>\n",
+        properties
+    )
+}
+
+/// ICC-Profil der PDF/A-Ausgabebedingung
+struct OutputIntentProfile {
+    /// Bezeichnung für /OutputConditionIdentifier und /Info
+    name: String,
+    data: Vec<u8>,
+    /// Farbkomponenten des Profils: 1 = Grau, 3 = RGB, 4 = CMYK
+    components: i64,
+}
+
+// ICC-Profil aus einer Datei laden; leerer Pfad = eingebautes sRGB
+fn load_output_intent_profile(path: &str) -> Result<OutputIntentProfile, String> {
+    if path.trim().is_empty() {
+        return Ok(OutputIntentProfile { name: "sRGB IEC61966-2.1".to_string(), data: srgb_icc_profile(), components: 3 });
+    }
+    let resolved = resolve_asset_path(path.trim());
+    let data = std::fs::read(&resolved).map_err(|e| format!("ICC-Profil {} kann nicht gelesen werden: {}", resolved.display(), e))?;
+    if data.len() < 128 || &data[36..40] != b"acsp" {
+        return Err(format!("{} ist kein ICC-Profil", resolved.display()));
+    }
+    let components = match &data[16..20] {
+        b"GRAY" => 1,
+        b"RGB " => 3,
+        b"CMYK" => 4,
+        other => return Err(format!("ICC-Profil {} mit Farbraum '{}' wird nicht unterstützt", resolved.display(), String::from_utf8_lossy(other).trim())),
+    };
+    let name = resolved.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_else(|| "ICC".to_string());
+    Ok(OutputIntentProfile { name, data, components })
+}
+
+// Minimales ICC-v2-Monitorprofil für sRGB (D50-adaptierte Primärvalenzen, sRGB-Tonwertkurve)
+fn srgb_icc_profile() -> Vec<u8> {
+    fn s15_fixed16(value: f64) -> [u8; 4] {
+        ((value * 65536.0).round() as i32).to_be_bytes()
+    }
+    fn xyz_tag(x: f64, y: f64, z: f64) -> Vec<u8> {
+        let mut tag = b"XYZ \0\0\0\0".to_vec();
+        for v in [x, y, z] {
+            tag.extend_from_slice(&s15_fixed16(v));
+        }
+        tag
+    }
+    let description = b"sRGB IEC61966-2.1\0";
+    let mut desc = b"desc\0\0\0\0".to_vec();
+    desc.extend_from_slice(&(description.len() as u32).to_be_bytes());
+    desc.extend_from_slice(description);
+    desc.extend_from_slice(&[0u8; 4 + 4 + 2 + 1 + 67]);
+    let mut cprt = b"text\0\0\0\0".to_vec();
+    cprt.extend_from_slice(b"No copyright, use freely\0");
+    let mut curve = b"curv\0\0\0\0".to_vec();
+    curve.extend_from_slice(&1024u32.to_be_bytes());
+    for i in 0..1024 {
+        let v = i as f64 / 1023.0;
+        let linear = if v <= 0.04045 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) };
+        curve.extend_from_slice(&((linear * 65535.0).round() as u16).to_be_bytes());
+    }
+
+    // Datenblöcke (die drei Tonwertkurven teilen sich einen Block)
+    let blocks: Vec<Vec<u8>> = vec![
+        desc,
+        cprt,
+        xyz_tag(0.9642, 1.0, 0.8249),
+        xyz_tag(0.4361, 0.2225, 0.0139),
+        xyz_tag(0.3851, 0.7169, 0.0971),
+        xyz_tag(0.1431, 0.0606, 0.7141),
+        curve,
+    ];
+    let tags: [(&[u8; 4], usize); 9] = [
+        (b"desc", 0), (b"cprt", 1), (b"wtpt", 2), (b"rXYZ", 3), (b"gXYZ", 4), (b"bXYZ", 5),
+        (b"rTRC", 6), (b"gTRC", 6), (b"bTRC", 6),
+    ];
+    let mut offsets = Vec::new();
+    let mut data = Vec::new();
+    let mut offset = 128 + 4 + 12 * tags.len();
+    for block in &blocks {
+        offsets.push(offset);
+        data.extend_from_slice(block);
+        while data.len() % 4 != 0 {
+            data.push(0);
+        }
+        offset = 128 + 4 + 12 * tags.len() + data.len();
+    }
+
+    let mut profile = Vec::with_capacity(offset);
+    profile.extend_from_slice(&(offset as u32).to_be_bytes());
+    profile.extend_from_slice(&[0; 4]);
+    profile.extend_from_slice(&0x0210_0000u32.to_be_bytes());
+    profile.extend_from_slice(b"mntrRGB XYZ ");
+    for part in [2000u16, 1, 1, 0, 0, 0] {
+        profile.extend_from_slice(&part.to_be_bytes());
+    }
+    profile.extend_from_slice(b"acsp");
+    profile.extend_from_slice(&[0; 28]);
+    for v in [0.9642, 1.0, 0.8249] {
+        profile.extend_from_slice(&s15_fixed16(v));
+    }
+    profile.extend_from_slice(&[0; 48]);
+    profile.extend_from_slice(&(tags.len() as u32).to_be_bytes());
+    for (signature, block) in tags {
+        profile.extend_from_slice(signature);
+        profile.extend_from_slice(&(offsets[block] as u32).to_be_bytes());
+        profile.extend_from_slice(&(blocks[block].len() as u32).to_be_bytes());
+    }
+    profile.extend_from_slice(&data);
+    profile
+}
+
+/// In die Vorlage eingetragene PDF/A-Ausgabebedingung
+#[derive(Clone, Copy, Debug)]
+struct PdfAIntent {
+    /// Farbkomponenten des Profils (siehe `OutputIntentProfile`)
+    components: i64,
+    /// ICCBased-sRGB für /DefaultRGB, wenn das Profil kein RGB-Profil ist (Overlay und Bilder sind RGB)
+    default_rgb: Option<lopdf::ObjectId>,
+}
+
+// Ausgabebedingung (/OutputIntents) am Katalog setzen; eine vorhandene der Vorlage wird ersetzt
+fn add_output_intent(doc: &mut Document, profile: &OutputIntentProfile) -> PdfAIntent {
+    let profile_id = doc.add_object(lopdf::Stream::new(dictionary! { "N" => profile.components }, profile.data.clone()));
+    let intent_id = doc.add_object(dictionary! {
+        "Type" => "OutputIntent",
+        "S" => "GTS_PDFA1",
+        "OutputConditionIdentifier" => pdf_text_string(&profile.name),
+        "Info" => pdf_text_string(&profile.name),
+        "RegistryName" => Object::string_literal("http://www.color.org"),
+        "DestOutputProfile" => profile_id,
+    });
+    if let Some(catalog) = doc.trailer.get(b"Root").and_then(Object::as_reference).ok()
+        .and_then(|root| doc.get_object_mut(root).and_then(Object::as_dict_mut).ok()) {
+        catalog.set("OutputIntents", vec![Object::Reference(intent_id)]);
+    }
+    let default_rgb = (profile.components != 3).then(|| {
+        let srgb_id = doc.add_object(lopdf::Stream::new(dictionary! { "N" => 3 }, srgb_icc_profile()));
+        doc.add_object(vec![Object::Name(b"ICCBased".to_vec()), Object::Reference(srgb_id)])
+    });
+    PdfAIntent { components: profile.components, default_rgb }
+}
+
+// In PDF/A-2 verbotene Aktionsarten
+const PDFA_FORBIDDEN_ACTIONS: [&[u8]; 11] = [
+    b"Launch", b"Sound", b"Movie", b"ResetForm", b"ImportData", b"JavaScript", b"Hide",
+    b"SetOCGState", b"Rendition", b"Trans", b"GoTo3DView",
+];
+
+// In PDF/A-2 erlaubte Füllmethoden
+const PDFA_BLEND_MODES: [&[u8]; 16] = [
+    b"Normal", b"Compatible", b"Multiply", b"Screen", b"Overlay", b"Darken", b"Lighten", b"ColorDodge",
+    b"ColorBurn", b"HardLight", b"SoftLight", b"Difference", b"Exclusion", b"Hue", b"Saturation", b"Color",
+];
+
+// Ausgabe für PDF/A-2b vorbereiten: Version, Dateikennung, Zusatzaktionen, Anmerkungs-Flags und
+// Standard-Farbräume werden korrigiert. Zurück kommen die Punkte, die sich nicht beheben lassen
+// (leer = konform im Rahmen dieser Prüfung).
+fn make_pdfa_conformant(doc: &mut Document, intent: PdfAIntent, id_seed: &str) -> Vec<String> {
+    let mut issues = std::collections::BTreeSet::new();
+    doc.version = "1.7".to_string();
+    if doc.trailer.has(b"Encrypt") {
+        issues.insert("Datei ist verschlüsselt".to_string());
+    }
+
+    // Eindeutige Dateikennung je Ausgabe
+    let digest = sha256_hex(format!("{}|{}", id_seed, chrono::Local::now().to_rfc3339()).as_bytes());
+    let file_id: Vec<u8> = (0..16).map(|i| u8::from_str_radix(&digest[i * 2..i * 2 + 2], 16).unwrap_or(0)).collect();
+    doc.trailer.set("ID", vec![
+        Object::String(file_id.clone(), lopdf::StringFormat::Hexadecimal),
+        Object::String(file_id, lopdf::StringFormat::Hexadecimal),
+    ]);
+
+    // Zusatzaktionen, JavaScript-Namensbaum und NeedAppearances entfernen, verbotene Bildoptionen
+    // und Transferfunktionen zurücksetzen
+    let forbidden_action = |object: &Object, doc: &Document| {
+        resolve_object(doc, object)
+            .and_then(|action| action.as_dict().ok())
+            .and_then(|action| action.get(b"S").and_then(Object::as_name).ok())
+            .is_some_and(|kind| PDFA_FORBIDDEN_ACTIONS.contains(&kind))
+    };
+    let open_action_forbidden = doc.trailer.get(b"Root").ok()
+        .and_then(|root| resolve_object(doc, root))
+        .and_then(|catalog| catalog.as_dict().ok())
+        .and_then(|catalog| catalog.get(b"OpenAction").ok())
+        .is_some_and(|action| forbidden_action(action, doc));
+    for object in doc.objects.values_mut() {
+        let dict = match object {
+            Object::Dictionary(dict) => dict,
+            Object::Stream(stream) => &mut stream.dict,
+            _ => continue,
+        };
+        dict.remove(b"AA");
+        dict.remove(b"JavaScript");
+        dict.remove(b"NeedAppearances");
+        if open_action_forbidden {
+            dict.remove(b"OpenAction");
+        }
+        if dict.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Image") {
+            dict.remove(b"Alternates");
+            dict.remove(b"OPI");
+            if dict.has(b"Interpolate") {
+                dict.set("Interpolate", false);
+            }
+        }
+        if dict.get(b"Type").and_then(Object::as_name).ok() == Some(b"ExtGState") {
+            dict.remove(b"TR");
+            if dict.get(b"TR2").and_then(Object::as_name).ok() != Some(b"Default") {
+                dict.remove(b"TR2");
+            }
+        }
+    }
+
+    // Anmerkungen: ausgeblendete entfernen, alle übrigen druckbar, Erscheinungsbild nur /N
+    let page_ids: Vec<lopdf::ObjectId> = doc.get_pages().into_values().collect();
+    for &page_id in &page_ids {
+        let (array_id, annots) = match doc.get_dictionary(page_id).ok().and_then(|page| page.get(b"Annots").ok()) {
+            Some(Object::Array(annots)) => (None, annots.clone()),
+            Some(Object::Reference(id)) => match doc.get_object(*id) {
+                Ok(Object::Array(annots)) => (Some(*id), annots.clone()),
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let mut kept = Vec::with_capacity(annots.len());
+        for annot in annots {
+            let dict = match &annot {
+                Object::Reference(id) => doc.get_object_mut(*id).and_then(Object::as_dict_mut).ok(),
+                _ => None,
+            };
+            if let Some(dict) = dict {
+                let flags = dict.get(b"F").and_then(Object::as_i64).unwrap_or(0);
+                // Invisible (1), Hidden (2), NoView (32)
+                if flags & (1 | 2 | 32) != 0 {
+                    continue;
+                }
+                // Print (4) setzen, ToggleNoView (256) löschen
+                dict.set("F", (flags | 4) & !256);
+                if let Ok(Object::Dictionary(appearance)) = dict.get_mut(b"AP") {
+                    appearance.remove(b"R");
+                    appearance.remove(b"D");
+                }
+            }
+            kept.push(annot);
+        }
+        match array_id {
+            Some(id) => { doc.objects.insert(id, Object::Array(kept)); }
+            None => {
+                if let Ok(page) = doc.get_object_mut(page_id).and_then(Object::as_dict_mut) {
+                    page.set("Annots", kept);
+                }
+            }
+        }
+    }
+
+    // Nicht-RGB-Ausgabebedingung: RGB-Inhalte über /DefaultRGB (ICCBased sRGB) kennzeichnen
+    if let Some(default_rgb) = intent.default_rgb {
+        let with_default_rgb = |doc: &Document, mut resources: lopdf::Dictionary| {
+            let mut spaces = match resources.get(b"ColorSpace") {
+                Ok(Object::Dictionary(spaces)) => spaces.clone(),
+                Ok(Object::Reference(id)) => doc.get_dictionary(*id).cloned().unwrap_or_default(),
+                _ => lopdf::Dictionary::new(),
+            };
+            if !spaces.has(b"DefaultRGB") {
+                spaces.set("DefaultRGB", default_rgb);
+            }
+            resources.set("ColorSpace", spaces);
+            resources
+        };
+        let page_resources: Vec<(lopdf::ObjectId, lopdf::Dictionary)> = page_ids.iter()
+            .map(|&page_id| (page_id, with_default_rgb(doc, effective_page_resources(doc, page_id))))
+            .collect();
+        let form_resources: Vec<(lopdf::ObjectId, lopdf::Dictionary)> = doc.objects.iter()
+            .filter_map(|(id, object)| {
+                let stream = object.as_stream().ok()?;
+                if stream.dict.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"Form") {
+                    return None;
+                }
+                let resources = stream.dict.get(b"Resources").ok()
+                    .and_then(|resources| resolve_object(doc, resources))
+                    .and_then(|resources| resources.as_dict().ok())
+                    .cloned()
+                    .unwrap_or_default();
+                Some((*id, with_default_rgb(doc, resources)))
+            })
+            .collect();
+        for (page_id, resources) in page_resources {
+            if let Ok(page) = doc.get_object_mut(page_id).and_then(Object::as_dict_mut) {
+                page.set("Resources", resources);
+            }
+        }
+        for (form_id, resources) in form_resources {
+            if let Ok(Object::Stream(stream)) = doc.get_object_mut(form_id) {
+                stream.dict.set("Resources", resources);
+            }
+        }
+    }
+
+    doc.prune_objects();
+
+    // Prüfen, was sich nicht automatisch beheben lässt
+    let rgb_allowed = intent.components == 3 || intent.default_rgb.is_some();
+    let cmyk_allowed = intent.components == 4;
+    for object in doc.objects.values() {
+        let dict = match object {
+            Object::Dictionary(dict) => dict,
+            Object::Stream(stream) => &stream.dict,
+            _ => continue,
+        };
+        let name = |key: &[u8]| dict.get(key).and_then(Object::as_name).ok();
+        if name(b"Type") == Some(b"Font") && !matches!(name(b"Subtype"), Some(b"Type0") | Some(b"Type3")) {
+            let embedded = dict.get(b"FontDescriptor").ok()
+                .and_then(|descriptor| resolve_object(doc, descriptor))
+                .and_then(|descriptor| descriptor.as_dict().ok())
+                .is_some_and(|descriptor| descriptor.has(b"FontFile") || descriptor.has(b"FontFile2") || descriptor.has(b"FontFile3"));
+            if !embedded {
+                let base_font = name(b"BaseFont").map(|font| String::from_utf8_lossy(font).to_string()).unwrap_or_default();
+                issues.insert(format!("Schrift {} nicht eingebettet", base_font));
+            }
+        }
+        if let Some(kind) = name(b"S").filter(|kind| PDFA_FORBIDDEN_ACTIONS.contains(kind)) {
+            issues.insert(format!("Aktion {} nicht erlaubt", String::from_utf8_lossy(kind)));
+        }
+        if dict.has(b"EmbeddedFiles") {
+            issues.insert("eingebettete Dateien".to_string());
+        }
+        if let Some(mode) = name(b"BM") {
+            if !PDFA_BLEND_MODES.contains(&mode) {
+                issues.insert(format!("Füllmethode {} nicht erlaubt", String::from_utf8_lossy(mode)));
+            }
+        }
+        if name(b"Subtype") == Some(b"PS") {
+            issues.insert("PostScript-XObject".to_string());
+        }
+        if let Object::Stream(stream) = object {
+            let lzw = match stream.dict.get(b"Filter") {
+                Ok(Object::Name(filter)) => filter == b"LZWDecode",
+                Ok(Object::Array(filters)) => filters.iter().any(|f| f.as_name().ok() == Some(b"LZWDecode")),
+                _ => false,
+            };
+            if lzw {
+                issues.insert("LZW-komprimierte Daten".to_string());
+            }
+        }
+        if name(b"Subtype") == Some(b"Image") {
+            match name(b"ColorSpace") {
+                Some(b"DeviceRGB") if !rgb_allowed => { issues.insert("RGB-Bild passt nicht zur Ausgabebedingung".to_string()); }
+                Some(b"DeviceCMYK") if !cmyk_allowed => { issues.insert("CMYK-Bild passt nicht zur Ausgabebedingung".to_string()); }
+                _ => {}
+            }
+        }
+    }
+
+    // Seiten: Anmerkungen ohne Erscheinungsbild und geräteabhängige Farben im Seiteninhalt
+    for &page_id in &page_ids {
+        let annots = doc.get_dictionary(page_id).ok()
+            .and_then(|page| page.get(b"Annots").ok())
+            .and_then(|annots| resolve_object(doc, annots))
+            .and_then(|annots| annots.as_array().ok())
+            .cloned()
+            .unwrap_or_default();
+        for annot in annots.iter().filter_map(|annot| resolve_object(doc, annot)).filter_map(|annot| annot.as_dict().ok()) {
+            let subtype = annot.get(b"Subtype").and_then(Object::as_name).unwrap_or_default();
+            if subtype != b"Popup" && subtype != b"Link" && !annot.has(b"AP") {
+                issues.insert(format!("Anmerkung {} ohne Erscheinungsbild", String::from_utf8_lossy(subtype)));
+            }
+        }
+        if !cmyk_allowed {
+            let uses_cmyk = doc.get_page_content(page_id).ok()
+                .and_then(|bytes| Content::decode(&bytes).ok())
+                .is_some_and(|content| content.operations.iter().any(|op| {
+                    matches!(op.operator.as_str(), "k" | "K")
+                        || (matches!(op.operator.as_str(), "cs" | "CS") && op.operands.first().and_then(|o| o.as_name().ok()) == Some(b"DeviceCMYK"))
+                }));
+            if uses_cmyk {
+                issues.insert("CMYK-Farben im Seiteninhalt passen nicht zur Ausgabebedingung".to_string());
+            }
+        }
+    }
+    issues.into_iter().collect()
+}
+
+// Dokument mit AES-256 verschlüsseln (Standard-Sicherheitshandler V5/R6 von lopdf).
+// Muss nach dem Komprimieren laufen; Metadaten-Streams bleiben lesbar (EncryptMetadata false),
+// damit DMS und Suchindex sie weiterhin finden.
+fn encrypt_document(doc: &mut Document, encryption: &EncryptionConfig) -> Result<(), String> {
+    if encryption.owner_password.is_empty() {
+        return Err("Verschlüsselung: Besitzer-Passwort fehlt".to_string());
+    }
+    let file_key: [u8; 32] = rand::random();
+    let mut crypt_filters: std::collections::BTreeMap<Vec<u8>, std::sync::Arc<dyn lopdf::encryption::crypt_filters::CryptFilter>> = std::collections::BTreeMap::new();
+    crypt_filters.insert(b"StdCF".to_vec(), std::sync::Arc::new(lopdf::encryption::crypt_filters::Aes256CryptFilter));
+    let version = lopdf::EncryptionVersion::V5 {
+        encrypt_metadata: false,
+        crypt_filters,
+        file_encryption_key: &file_key,
+        stream_filter: b"StdCF".to_vec(),
+        string_filter: b"StdCF".to_vec(),
+        owner_password: &encryption.owner_password,
+        user_password: &encryption.user_password,
+        permissions: lopdf::Permissions::from_bits_retain(encryption.permission_flags() as u32 as u64),
+    };
+    let state = lopdf::EncryptionState::try_from(version).map_err(|e| format!("Verschlüsselung: {}", e))?;
+    doc.encrypt(&state).map_err(|e| format!("Verschlüsselung: {}", e))?;
+    // Verschlüsselte Dateien brauchen eine Dateikennung
+    if !doc.trailer.has(b"ID") {
+        let file_id: [u8; 16] = rand::random();
+        doc.trailer.set("ID", vec![
+            Object::String(file_id.to_vec(), lopdf::StringFormat::Hexadecimal),
+            Object::String(file_id.to_vec(), lopdf::StringFormat::Hexadecimal),
+        ]);
+    }
+    Ok(())
+}
+
+// Einzel-PDFs einer Vorlage zu einer Druckdatei zusammenführen: je Vertreter ein Lesezeichen und
+// ein Seitenbeschriftungs-Bereich; im Duplex-Modus folgt auf eine ungerade Seitenzahl eine Leerseite,
+// damit der nächste Vertreter auf einer Vorderseite beginnt. Liefert die Gesamtzahl der Seiten.
+fn merge_print_file(parts: &[(String, std::path::PathBuf)], title: &str, duplex: bool, output_mode: OutputMode, output_path: &std::path::Path) -> Result<usize, String> {
+    let mut merged = Document::with_version("1.5");
+    let pages_id = merged.new_object_id();
+    let mut kids: Vec<Object> = Vec::new();
+    // (Beschriftung, Index der ersten Seite, erste Seite)
+    let mut sections: Vec<(&str, usize, lopdf::ObjectId)> = Vec::new();
+
+    for (label, path) in parts {
+        let mut part = Document::load(path).map_err(|e| format!("{} konnte nicht geladen werden: {}", path.display(), e))?;
+        if part.trailer.get(b"Encrypt").is_ok() {
+            return Err(format!("{} ist verschlüsselt", path.display()));
+        }
+        part.renumber_objects_with(merged.max_id + 1);
+        let page_ids: Vec<lopdf::ObjectId> = part.get_pages().into_values().collect();
+        if page_ids.is_empty() {
+            continue;
+        }
+
+        // Geerbte Attribute direkt an die Seiten schreiben, der alte Seitenbaum entfällt
+        let inherited_keys: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+        let mut media_box = None;
+        for &page_id in &page_ids {
+            let inherited: Vec<(&[u8], Object)> = inherited_keys
+                .into_iter()
+                .filter_map(|key| get_inherited_page_attr(&part, page_id, key).map(|value| (key, value)))
+                .collect();
+            let page = part.get_object_mut(page_id)
+                .and_then(Object::as_dict_mut)
+                .map_err(|e| format!("Seite in {} nicht lesbar: {}", path.display(), e))?;
+            for (key, value) in inherited {
+                page.set(key.to_vec(), value);
+            }
+            page.set("Parent", pages_id);
+            media_box = page.get(b"MediaBox").ok().cloned();
+        }
+
+        if part.version.as_str() > merged.version.as_str() {
+            merged.version = part.version.clone();
+        }
+        sections.push((label.as_str(), kids.len(), page_ids[0]));
+        kids.extend(page_ids.iter().map(|&id| Object::Reference(id)));
+        merged.max_id = merged.max_id.max(part.objects.keys().map(|id| id.0).max().unwrap_or(0));
+        merged.objects.extend(part.objects);
+
+        if duplex && page_ids.len() % 2 == 1 {
+            let media_box = media_box.unwrap_or_else(|| Object::Array(vec![0.into(), 0.into(), 595.into(), 842.into()]));
+            let blank_id = merged.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "MediaBox" => media_box,
+                "Resources" => dictionary! {},
+            });
+            kids.push(Object::Reference(blank_id));
+        }
+    }
+    if sections.is_empty() {
+        return Err("Keine Seiten zum Zusammenführen".to_string());
+    }
+
+    // Lesezeichen je Vertreter (flache Liste in Sortierreihenfolge)
+    let outlines_id = merged.new_object_id();
+    let item_ids: Vec<lopdf::ObjectId> = sections.iter().map(|_| merged.new_object_id()).collect();
+    for (i, (label, _, first_page)) in sections.iter().enumerate() {
+        let mut item = dictionary! {
+            "Title" => pdf_text_string(label),
+            "Parent" => outlines_id,
+            "Dest" => vec![Object::Reference(*first_page), Object::Name(b"Fit".to_vec())],
+        };
+        if i > 0 {
+            item.set("Prev", item_ids[i - 1]);
+        }
+        if i + 1 < item_ids.len() {
+            item.set("Next", item_ids[i + 1]);
+        }
+        merged.objects.insert(item_ids[i], Object::Dictionary(item));
+    }
+    merged.objects.insert(outlines_id, Object::Dictionary(dictionary! {
+        "Type" => "Outlines",
+        "First" => item_ids[0],
+        "Last" => item_ids[item_ids.len() - 1],
+        "Count" => item_ids.len() as i64,
+    }));
+
+    // Seitenbeschriftung "<Vertreter> – 1, 2, ..." (Leerseiten zählen zum vorherigen Vertreter)
+    let mut nums = Vec::new();
+    for (label, first_index, _) in &sections {
+        nums.push(Object::Integer(*first_index as i64));
+        nums.push(Object::Dictionary(dictionary! {
+            "S" => "D",
+            "P" => pdf_text_string(&format!("{} – ", label)),
+        }));
+    }
+
+    let page_count = kids.len();
+    merged.objects.insert(pages_id, Object::Dictionary(dictionary! {
+        "Type" => "Pages",
+        "Kids" => kids,
+        "Count" => page_count as i64,
+    }));
+    let catalog_id = merged.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+        "Outlines" => outlines_id,
+        "PageMode" => "UseOutlines",
+        "PageLabels" => dictionary! { "Nums" => nums },
+    });
+    let now = chrono::Local::now();
+    let pdf_date = format!("D:{}'", now.format("%Y%m%d%H%M%S%:z").to_string().replace(':', "'"));
+    let info_id = merged.add_object(dictionary! {
+        "Title" => pdf_text_string(title),
+        "Producer" => pdf_text_string(&generator_name()),
+        "CreationDate" => Object::string_literal(pdf_date.as_str()),
+    });
+    merged.trailer.set("Root", catalog_id);
+    merged.trailer.set("Info", info_id);
+    // Alte Kataloge, Seitenbäume und Info-Dicts der Einzel-PDFs fallen hier weg
+    merged.prune_objects();
+
+    let saved = if output_mode == OutputMode::Small {
+        optimize_pdf(&mut merged);
+        std::fs::File::create(output_path)
+            .and_then(|file| merged.save_modern(&mut std::io::BufWriter::new(file)))
+    } else {
+        merged.save(output_path).map(|_| ())
+    };
+    saved.map_err(|e| format!("Konnte Druckdatei nicht speichern: {} - {}", output_path.display(), e))?;
+    Ok(page_count)
+}
+
+// Länge der Schnittmarken und ihr Abstand zur Beschnittkante (mm)
+const CROP_MARK_LENGTH_MM: f32 = 5.0;
+const CROP_MARK_OFFSET_MM: f32 = 1.0;
+
+// Seitenbox (normalisiert) anhand der ersten vorhandenen Box aus `keys`, auch geerbt
+fn page_box(doc: &Document, page_id: lopdf::ObjectId, keys: &[&[u8]]) -> Option<[f32; 4]> {
+    keys.iter().find_map(|key| {
+        let values: Vec<f32> = get_inherited_page_attr(doc, page_id, key)?
+            .as_array().ok()?
+            .iter()
+            .filter_map(|v| resolve_object(doc, v).and_then(|v| v.as_float().ok()))
+            .collect();
+        (values.len() == 4).then(|| [
+            values[0].min(values[2]),
+            values[1].min(values[3]),
+            values[0].max(values[2]),
+            values[1].max(values[3]),
+        ])
+    })
+}
+
+// Seiten der Einzel-PDFs in Reihenfolge auf Druckbogen ausschießen (zeilenweise von links oben).
+// Jede Seite wird als Form-XObject auf Endformat plus Beschnitt begrenzt und in ihrer angezeigten
+// Lage (/Rotate) platziert, das Raster steht mittig auf dem Bogen; Schnittmarken und Infozeile
+// liegen im Rand in der Passerfarbe /All. Formularfelder werden vorher in den Seiteninhalt
+// übernommen, da Widgets im Form-XObject wegfielen. Liefert (Bogen, Nutzen).
+fn impose_print_sheets(parts: &[(String, std::path::PathBuf)], title: &str, options: &ImpositionOptions, output_mode: OutputMode, output_path: &std::path::Path) -> Result<(usize, usize), String> {
+    let mm = 72.0 / 25.4;
+    let gutter = options.gutter_mm.max(0.0) * mm;
+    let bleed = (options.bleed_mm.max(0.0) * mm).min(gutter / 2.0);
+    let mut sheets = Document::with_version("1.5");
+
+    // Alle Seiten als Form-XObjects übernehmen: (Form, Endformat, Drehung)
+    let mut placed: Vec<(lopdf::ObjectId, [f32; 4], i32)> = Vec::new();
+    for (_, path) in parts {
+        let mut part = Document::load(path).map_err(|e| format!("{} konnte nicht geladen werden: {}", path.display(), e))?;
+        if part.trailer.get(b"Encrypt").is_ok() {
+            return Err(format!("{} ist verschlüsselt", path.display()));
+        }
+        flatten_form_fields(&mut part);
+        part.renumber_objects_with(sheets.max_id + 1);
+        for page_id in part.get_pages().into_values() {
+            let trim = page_box(&part, page_id, &[b"TrimBox", b"CropBox", b"MediaBox"]).unwrap_or([0.0, 0.0, 595.0, 842.0]);
+            let rotate = PageGeometry::of_page(&part, page_id).rotate;
+            let limit = page_box(&part, page_id, &[b"BleedBox", b"MediaBox"]).unwrap_or(trim);
+            let bbox = [
+                (trim[0] - bleed).max(limit[0]),
+                (trim[1] - bleed).max(limit[1]),
+                (trim[2] + bleed).min(limit[2]),
+                (trim[3] + bleed).min(limit[3]),
+            ];
+            let content = part.get_page_content(page_id).map_err(|e| format!("Seiteninhalt von {} nicht lesbar: {}", path.display(), e))?;
+            let resources = get_inherited_page_attr(&part, page_id, b"Resources").unwrap_or_else(|| Object::Dictionary(dictionary! {}));
+
+            let mut needed = std::collections::BTreeSet::new();
+            collect_referenced_ids(&part, &resources, &mut needed);
+            for id in needed {
+                if let Ok(obj) = part.get_object(id) {
+                    sheets.objects.insert(id, obj.clone());
+                }
+            }
+            sheets.max_id = sheets.max_id.max(part.max_id);
+
+            let mut form = lopdf::Stream::new(dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Form",
+                "BBox" => bbox.iter().map(|v| (*v).into()).collect::<Vec<Object>>(),
+                "Resources" => resources,
+            }, content);
+            let _ = form.compress();
+            placed.push((sheets.add_object(form), trim, rotate));
+        }
+    }
+    if placed.is_empty() {
+        return Err("Keine Seiten zum Ausschießen".to_string());
+    }
+
+    // Endformat in Anzeigelage (bei 90/270 Grad Breite und Höhe getauscht)
+    let displayed = |trim: &[f32; 4], rotate: i32| {
+        let (w, h) = (trim[2] - trim[0], trim[3] - trim[1]);
+        if rotate % 180 == 0 { (w, h) } else { (h, w) }
+    };
+    // Zellengröße nach dem größten Endformat; Hoch- oder Querbogen je nach Nutzenzahl
+    let cell_w = placed.iter().map(|(_, t, r)| displayed(t, *r).0).fold(0.0, f32::max);
+    let cell_h = placed.iter().map(|(_, t, r)| displayed(t, *r).1).fold(0.0, f32::max);
+    let margin = if options.crop_marks { bleed + (CROP_MARK_OFFSET_MM + CROP_MARK_LENGTH_MM) * mm } else { bleed };
+    let fit = |width: f32, height: f32| {
+        let cols = ((width - 2.0 * margin + gutter) / (cell_w + gutter)).floor().max(0.0) as usize;
+        let rows = ((height - 2.0 * margin + gutter) / (cell_h + gutter)).floor().max(0.0) as usize;
+        (cols, rows)
+    };
+    let (sheet_a, sheet_b) = (options.sheet_width_mm * mm, options.sheet_height_mm * mm);
+    let (portrait, landscape) = (fit(sheet_a, sheet_b), fit(sheet_b, sheet_a));
+    let (sheet_w, sheet_h, cols, rows) = if landscape.0 * landscape.1 > portrait.0 * portrait.1 {
+        (sheet_b, sheet_a, landscape.0, landscape.1)
+    } else {
+        (sheet_a, sheet_b, portrait.0, portrait.1)
+    };
+    let per_sheet = cols * rows;
+    if per_sheet == 0 {
+        return Err(format!(
+            "Seitenformat {:.0} × {:.0} mm passt nicht auf den Bogen {:.0} × {:.0} mm",
+            cell_w / mm, cell_h / mm, options.sheet_width_mm, options.sheet_height_mm
+        ));
+    }
+    let grid_w = cols as f32 * cell_w + (cols - 1) as f32 * gutter;
+    let grid_h = rows as f32 * cell_h + (rows - 1) as f32 * gutter;
+    let left = (sheet_w - grid_w) / 2.0;
+    let bottom = (sheet_h - grid_h) / 2.0;
+    let top = bottom + grid_h;
+
+    // Passerfarbe für Marken und Infozeile (druckt auf allen Auszügen)
+    let registration_id = sheets.add_object(Object::Array(vec![
+        Object::Name(b"Separation".to_vec()),
+        Object::Name(b"All".to_vec()),
+        Object::Name(b"DeviceCMYK".to_vec()),
+        Object::Dictionary(dictionary! {
+            "FunctionType" => 2,
+            "Domain" => vec![0.into(), 1.into()],
+            "C0" => vec![0.into(), 0.into(), 0.into(), 0.into()],
+            "C1" => vec![1.into(), 1.into(), 1.into(), 1.into()],
+            "N" => 1,
+        }),
+    ]));
+    let font_id = sheets.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    });
+    let date = chrono::Local::now().format("%d.%m.%Y %H:%M").to_string();
+    let sheet_count = placed.len().div_ceil(per_sheet);
+
+    let pages_id = sheets.new_object_id();
+    let mut kids = Vec::with_capacity(sheet_count);
+    for (sheet_index, chunk) in placed.chunks(per_sheet).enumerate() {
+        let mut content = Content { operations: Vec::new() };
+        let mut xobjects = lopdf::Dictionary::new();
+        for (slot, (form_id, trim, rotate)) in chunk.iter().enumerate() {
+            let (col, row) = (slot % cols, slot / cols);
+            // Endformat mittig in seiner Zelle
+            let (w, h) = displayed(trim, *rotate);
+            let x = left + col as f32 * (cell_w + gutter) + (cell_w - w) / 2.0;
+            let y = top - row as f32 * (cell_h + gutter) - cell_h + (cell_h - h) / 2.0;
+            // Benutzerraum der Seite -> Anzeigelage mit der linken unteren Endformat-Ecke bei (x, y)
+            // (Umkehrung von PageGeometry::overlay_matrix)
+            let (tw, th) = (trim[2] - trim[0], trim[3] - trim[1]);
+            let matrix: [f32; 6] = match rotate {
+                90 => [0.0, -1.0, 1.0, 0.0, x - trim[1], y + tw + trim[0]],
+                180 => [-1.0, 0.0, 0.0, -1.0, x + tw + trim[0], y + th + trim[1]],
+                270 => [0.0, 1.0, -1.0, 0.0, x + th + trim[1], y - trim[0]],
+                _ => [1.0, 0.0, 0.0, 1.0, x - trim[0], y - trim[1]],
+            };
+            let name = format!("N{}", slot + 1);
+            xobjects.set(name.as_bytes(), *form_id);
+            content.operations.push(Operation::new("q", vec![]));
+            content.operations.push(Operation::new("cm", matrix.iter().map(|v| (*v).into()).collect()));
+            content.operations.push(Operation::new("Do", vec![Object::Name(name.into_bytes())]));
+            content.operations.push(Operation::new("Q", vec![]));
+        }
+
+        if options.crop_marks {
+            let offset = bleed + CROP_MARK_OFFSET_MM * mm;
+            let length = CROP_MARK_LENGTH_MM * mm;
+            // Senkrechte Marken über und unter dem Raster, waagrechte links und rechts davon
+            let mut marks = Vec::new();
+            for col in 0..cols {
+                let x0 = left + col as f32 * (cell_w + gutter);
+                for x in [x0, x0 + cell_w] {
+                    marks.push((x, top + offset, x, top + offset + length));
+                    marks.push((x, bottom - offset, x, bottom - offset - length));
+                }
+            }
+            for row in 0..rows {
+                let y0 = top - row as f32 * (cell_h + gutter);
+                for y in [y0, y0 - cell_h] {
+                    marks.push((left - offset, y, left - offset - length, y));
+                    marks.push((left + grid_w + offset, y, left + grid_w + offset + length, y));
+                }
+            }
+            content.operations.push(Operation::new("q", vec![]));
+            content.operations.push(Operation::new("CS", vec![Object::Name(b"Reg".to_vec())]));
+            content.operations.push(Operation::new("SCN", vec![1.into()]));
+            content.operations.push(Operation::new("w", vec![0.25f32.into()]));
+            for (x1, y1, x2, y2) in marks {
+                content.operations.push(Operation::new("m", vec![x1.into(), y1.into()]));
+                content.operations.push(Operation::new("l", vec![x2.into(), y2.into()]));
+            }
+            content.operations.push(Operation::new("S", vec![]));
+            content.operations.push(Operation::new("Q", vec![]));
+        }
+
+        if options.slug_line {
+            let text = format!("{} – Bogen {} von {} – {} Nutzen – {}", title, sheet_index + 1, sheet_count, chunk.len(), date);
+            let bytes = encode_win_ansi(&text)
+                .unwrap_or_else(|| text.chars().map(|c| if c.is_ascii() { c as u8 } else { b'?' }).collect());
+            content.operations.push(Operation::new("q", vec![]));
+            content.operations.push(Operation::new("cs", vec![Object::Name(b"Reg".to_vec())]));
+            content.operations.push(Operation::new("scn", vec![1.into()]));
+            content.operations.push(Operation::new("BT", vec![]));
+            content.operations.push(Operation::new("Tf", vec![Object::Name(b"F1".to_vec()), 6.into()]));
+            content.operations.push(Operation::new("Td", vec![(left + 2.0 * mm).into(), (2.0 * mm).into()]));
+            content.operations.push(Operation::new("Tj", vec![Object::String(bytes, lopdf::StringFormat::Literal)]));
+            content.operations.push(Operation::new("ET", vec![]));
+            content.operations.push(Operation::new("Q", vec![]));
+        }
+
+        let encoded = content.encode().map_err(|e| format!("Bogeninhalt konnte nicht erzeugt werden: {}", e))?;
+        let contents_id = sheets.add_object(lopdf::Stream::new(dictionary! {}, encoded));
+        let page_id = sheets.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), sheet_w.into(), sheet_h.into()],
+            "Contents" => contents_id,
+            "Resources" => dictionary! {
+                "XObject" => xobjects,
+                "Font" => dictionary! { "F1" => font_id },
+                "ColorSpace" => dictionary! { "Reg" => registration_id },
+            },
+        });
+        kids.push(Object::Reference(page_id));
+    }
+
+    sheets.objects.insert(pages_id, Object::Dictionary(dictionary! {
+        "Type" => "Pages",
+        "Kids" => kids,
+        "Count" => sheet_count as i64,
+    }));
+    let catalog_id = sheets.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    let now = chrono::Local::now();
+    let pdf_date = format!("D:{}'", now.format("%Y%m%d%H%M%S%:z").to_string().replace(':', "'"));
+    let info_id = sheets.add_object(dictionary! {
+        "Title" => pdf_text_string(title),
+        "Producer" => pdf_text_string(&generator_name()),
+        "CreationDate" => Object::string_literal(pdf_date.as_str()),
+    });
+    sheets.trailer.set("Root", catalog_id);
+    sheets.trailer.set("Info", info_id);
+
+    let saved = if output_mode == OutputMode::Small {
+        optimize_pdf(&mut sheets);
+        std::fs::File::create(output_path)
+            .and_then(|file| sheets.save_modern(&mut std::io::BufWriter::new(file)))
+    } else {
+        sheets.save(output_path).map(|_| ())
+    };
+    saved.map_err(|e| format!("Konnte Bogen-PDF nicht speichern: {} - {}", output_path.display(), e))?;
+    Ok((sheet_count, placed.len()))
+}
+
+// Ausgabe verkleinern: gleiche Streams und Font-Objekte zusammenfassen, unbenutzte Objekte
+// entfernen und alle Streams komprimieren. Liefert die Zuordnung alte ID -> behaltene ID.
+fn optimize_pdf(doc: &mut Document) -> std::collections::HashMap<lopdf::ObjectId, lopdf::ObjectId> {
+    let mut merged = std::collections::HashMap::new();
+    // Mehrere Durchgänge: zusammengefasste Font-Dateien machen ihre Deskriptoren und Fonts gleich
+    for _ in 0..4 {
+        let pass = deduplicate_objects(doc);
+        if pass.is_empty() {
+            break;
+        }
+        for target in merged.values_mut() {
+            if let Some(canonical) = pass.get(target) {
+                *target = *canonical;
+            }
+        }
+        merged.extend(pass);
+    }
+    doc.prune_objects();
+    doc.compress();
+    // Objekt-Streams (save_modern) gibt es erst ab PDF 1.5
+    if doc.version.as_str() < "1.5" {
+        doc.version = "1.5".to_string();
+    }
+    merged
+}
+
+// Ein Durchgang der Zusammenfassung: Streams sowie Font-/FontDescriptor-Dicts mit gleichem
+// Inhalt werden auf das erste Objekt umgelenkt und die Duplikate entfernt
+fn deduplicate_objects(doc: &mut Document) -> std::collections::HashMap<lopdf::ObjectId, lopdf::ObjectId> {
+    let mut seen: std::collections::HashMap<Vec<u8>, lopdf::ObjectId> = std::collections::HashMap::new();
+    let mut duplicates = std::collections::HashMap::new();
+    for (id, object) in &doc.objects {
+        let key = match object {
+            Object::Stream(_) => object_key_bytes(object),
+            Object::Dictionary(dict) if matches!(dict.get(b"Type").and_then(Object::as_name), Ok(b"Font") | Ok(b"FontDescriptor")) => {
+                object_key_bytes(object)
+            }
+            _ => continue,
+        };
+        match seen.get(&key) {
+            Some(canonical) => {
+                duplicates.insert(*id, *canonical);
+            }
+            None => {
+                seen.insert(key, *id);
+            }
+        }
+    }
+    if duplicates.is_empty() {
+        return duplicates;
+    }
+    for object in doc.objects.values_mut() {
+        remap_references(object, &duplicates);
+    }
+    for value in doc.trailer.iter_mut().map(|(_, v)| v) {
+        remap_references(value, &duplicates);
+    }
+    for id in duplicates.keys() {
+        doc.objects.remove(id);
+    }
+    duplicates
+}
+
+// Inhalt eines Objekts als Vergleichsschlüssel; Dictionary-Schlüssel sortiert, damit die
+// Reihenfolge der Einträge keine Rolle spielt
+fn object_key_bytes(object: &Object) -> Vec<u8> {
+    fn tagged(out: &mut Vec<u8>, tag: u8, data: &[u8]) {
+        out.push(tag);
+        out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        out.extend_from_slice(data);
+    }
+    fn write(object: &Object, out: &mut Vec<u8>) {
+        match object {
+            Object::Null => tagged(out, b'n', &[]),
+            Object::Boolean(value) => tagged(out, b'b', &[*value as u8]),
+            Object::Integer(value) => tagged(out, b'i', &value.to_le_bytes()),
+            Object::Real(value) => tagged(out, b'r', &value.to_le_bytes()),
+            Object::Name(name) => tagged(out, b'/', name),
+            Object::String(data, _) => tagged(out, b'(', data),
+            Object::Reference((id, generation)) => {
+                let mut data = id.to_le_bytes().to_vec();
+                data.extend_from_slice(&generation.to_le_bytes());
+                tagged(out, b'R', &data);
+            }
+            Object::Array(items) => {
+                tagged(out, b'[', &(items.len() as u64).to_le_bytes());
+                items.iter().for_each(|item| write(item, out));
+            }
+            Object::Dictionary(dict) => write_dict(dict, out),
+            Object::Stream(stream) => {
+                write_dict(&stream.dict, out);
+                tagged(out, b'S', &stream.content);
+            }
+        }
+    }
+    fn write_dict(dict: &lopdf::Dictionary, out: &mut Vec<u8>) {
+        let mut entries: Vec<_> = dict.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        tagged(out, b'<', &(entries.len() as u64).to_le_bytes());
+        for (key, value) in entries {
+            tagged(out, b'/', key);
+            write(value, out);
+        }
+    }
+    let mut out = Vec::new();
+    write(object, &mut out);
+    out
+}
+
+// Verweise rekursiv auf die behaltenen Objekte umschreiben
+fn remap_references(object: &mut Object, map: &std::collections::HashMap<lopdf::ObjectId, lopdf::ObjectId>) {
+    match object {
+        Object::Reference(id) => {
+            if let Some(canonical) = map.get(id) {
+                *id = *canonical;
+            }
+        }
+        Object::Array(items) => items.iter_mut().for_each(|item| remap_references(item, map)),
+        Object::Dictionary(dict) => dict.iter_mut().for_each(|(_, value)| remap_references(value, map)),
+        Object::Stream(stream) => stream.dict.iter_mut().for_each(|(_, value)| remap_references(value, map)),
+        _ => {}
+    }
+}
+
+/// In die PDF eingebettete Teilmenge einer TrueType/OpenType-Schrift (Type0, Identity-H).
+/// Text wird als 2-Byte-Glyph-IDs geschrieben, die ToUnicode-Tabelle hält ihn kopierbar.
+struct EmbeddedFont {
+    font_id: lopdf::ObjectId,
+    glyphs: std::collections::HashMap<char, u16>,
+    /// Vorschubbreiten je Glyph in 1/1000 em
+    widths: std::collections::HashMap<u16, f32>,
+    /// Ober- und Unterlänge in 1/1000 em (Unterlänge negativ)
+    ascent: f32,
+    descent: f32,
+}
+
+impl EmbeddedFont {
+    fn encode(&self, text: &str) -> Object {
+        let mut bytes = Vec::with_capacity(text.len() * 2);
+        for c in text.chars() {
+            let gid = self.glyphs.get(&c).copied().unwrap_or(0);
+            bytes.extend_from_slice(&gid.to_be_bytes());
+        }
+        Object::String(bytes, lopdf::StringFormat::Hexadecimal)
+    }
+
+    fn text_width(&self, text: &str, font_size: f32) -> f32 {
+        text.chars()
+            .filter_map(|c| self.glyphs.get(&c))
+            .map(|gid| self.widths.get(gid).copied().unwrap_or(0.0))
+            .sum::<f32>() * font_size / 1000.0
+    }
+}
+
+// Schriftdateien werden pro Prozess nur einmal gesucht und gelesen: (Name, Stil) -> Dateiinhalt
+#[allow(clippy::type_complexity)]
+static FONT_FILE_CACHE: Lazy<std::sync::Mutex<std::collections::HashMap<(String, String), Option<Arc<Vec<u8>>>>>> =
+    Lazy::new(|| std::sync::Mutex::new(std::collections::HashMap::new()));
+
+fn load_font_file(font_name: &str, font_style: &str) -> Option<Arc<Vec<u8>>> {
+    let key = (font_name.to_string(), font_style.to_string());
+    if let Some(cached) = FONT_FILE_CACHE.lock().unwrap().get(&key) {
+        return cached.clone();
+    }
+    let data = find_font_file(font_name, font_style)
+        .and_then(|path| std::fs::read(&path).ok())
+        .map(Arc::new);
+    FONT_FILE_CACHE.lock().unwrap().insert(key, data.clone());
+    data
+}
+
+// Bettet die für `text` benötigten Glyphen einer TTF/OTF-Schrift ein. TrueType-Outlines werden
+// als CIDFontType2 (FontFile2), CFF-Outlines (OTTO) als CIDFontType0 (FontFile3/OpenType) eingebettet.
+fn embed_font_subset(doc: &mut Document, data: &[u8], text: &str) -> Result<EmbeddedFont, String> {
+    let face = ttf_parser::Face::parse(data, 0).map_err(|e| format!("Schrift nicht lesbar: {}", e))?;
+    let scale = 1000.0 / face.units_per_em() as f32;
+
+    let mut glyphs = std::collections::HashMap::new();
+    let mut widths = std::collections::HashMap::new();
+    let mut gids: Vec<u16> = vec![0];
+    for c in text.chars() {
+        if glyphs.contains_key(&c) {
+            continue;
+        }
+        let gid = face.glyph_index(c).ok_or_else(|| format!("Zeichen '{}' fehlt in der Schrift", c))?;
+        glyphs.insert(c, gid.0);
+        widths.insert(gid.0, face.glyph_hor_advance(gid).unwrap_or(0) as f32 * scale);
+        gids.push(gid.0);
+    }
+    gids.sort_unstable();
+    gids.dedup();
+
+    let subset = subsetter::subset(data, 0, subsetter::Profile::pdf(&gids))
+        .map_err(|e| format!("Subsetting fehlgeschlagen: {:?}", e))?;
+    let is_cff = data.starts_with(b"OTTO");
+
+    // Subset-Präfix aus den enthaltenen Glyphen ableiten (6 Großbuchstaben, PDF 9.6.4)
+    let mut hash: u32 = 2166136261;
+    for gid in &gids {
+        hash = (hash ^ *gid as u32).wrapping_mul(16777619);
+    }
+    let tag: String = (0..6).map(|i| (b'A' + ((hash >> (i * 5)) % 26) as u8) as char).collect();
+    let ps_name = face.names()
+        .into_iter()
+        .find(|n| n.name_id == ttf_parser::name_id::POST_SCRIPT_NAME && n.is_unicode())
+        .and_then(|n| n.to_string())
+        .unwrap_or_else(|| "EmbeddedFont".to_string());
+    let ps_name: String = ps_name.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+    let base_font = format!("{}+{}", tag, ps_name);
+
+    let subset_len = subset.len() as i64;
+    let mut font_file = lopdf::Stream::new(
+        if is_cff { dictionary! { "Subtype" => "OpenType" } } else { dictionary! { "Length1" => subset_len } },
+        subset,
+    );
+    let _ = font_file.compress();
+    let font_file_id = doc.add_object(font_file);
+
+    let ascent = face.ascender() as f32 * scale;
+    let descent = face.descender() as f32 * scale;
+    let bbox = face.global_bounding_box();
+    let mut descriptor = dictionary! {
+        "Type" => "FontDescriptor",
+        "FontName" => Object::Name(base_font.as_bytes().to_vec()),
+        "Flags" => 32,
+        "FontBBox" => vec![
+            (bbox.x_min as f32 * scale).into(), (bbox.y_min as f32 * scale).into(),
+            (bbox.x_max as f32 * scale).into(), (bbox.y_max as f32 * scale).into(),
+        ],
+        "ItalicAngle" => 0,
+        "Ascent" => ascent,
+        "Descent" => descent,
+        "CapHeight" => face.capital_height().map(|h| h as f32 * scale).unwrap_or(ascent),
+        "StemV" => 80,
+    };
+    descriptor.set(if is_cff { "FontFile3" } else { "FontFile2" }, font_file_id);
+    let descriptor_id = doc.add_object(descriptor);
+
+    let mut w = Vec::with_capacity(gids.len() * 2);
+    for gid in &gids {
+        let width = widths.get(gid).copied()
+            .unwrap_or_else(|| face.glyph_hor_advance(ttf_parser::GlyphId(*gid)).unwrap_or(0) as f32 * scale);
+        w.push(Object::Integer(*gid as i64));
+        w.push(Object::Array(vec![width.into()]));
+    }
+    let mut cid_font = dictionary! {
+        "Type" => "Font",
+        "Subtype" => if is_cff { "CIDFontType0" } else { "CIDFontType2" },
+        "BaseFont" => Object::Name(base_font.as_bytes().to_vec()),
+        "CIDSystemInfo" => dictionary! {
+            "Registry" => Object::string_literal("Adobe"),
+            "Ordering" => Object::string_literal("Identity"),
+            "Supplement" => 0,
+        },
+        "FontDescriptor" => descriptor_id,
+        "DW" => 1000,
+        "W" => w,
+    };
+    if !is_cff {
+        cid_font.set("CIDToGIDMap", "Identity");
+    }
+    let cid_font_id = doc.add_object(cid_font);
+
+    let to_unicode_id = doc.add_object(lopdf::Stream::new(dictionary! {}, to_unicode_cmap(&glyphs).into_bytes()));
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type0",
+        "BaseFont" => Object::Name(base_font.as_bytes().to_vec()),
+        "Encoding" => "Identity-H",
+        "DescendantFonts" => vec![cid_font_id.into()],
+        "ToUnicode" => to_unicode_id,
+    });
+
+    Ok(EmbeddedFont { font_id, glyphs, widths, ascent, descent })
+}
+
+// ToUnicode-CMap: Glyph-ID (2 Byte) -> UTF-16BE, in Blöcken zu höchstens 100 Einträgen
+fn to_unicode_cmap(glyphs: &std::collections::HashMap<char, u16>) -> String {
+    let mut entries: Vec<(u16, char)> = glyphs.iter().map(|(c, gid)| (*gid, *c)).collect();
+    entries.sort_unstable();
+    entries.dedup_by_key(|(gid, _)| *gid);
+    let mut cmap = String::from("/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+        /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+        /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+        1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n");
+    for chunk in entries.chunks(100) {
+        cmap.push_str(&format!("{} beginbfchar\n", chunk.len()));
+        for (gid, c) in chunk {
+            let mut utf16 = [0u16; 2];
+            let hex: String = c.encode_utf16(&mut utf16).iter().map(|u| format!("{:04X}", u)).collect();
+            cmap.push_str(&format!("<{:04X}> <{}>\n", gid, hex));
+        }
+        cmap.push_str("endbfchar\n");
+    }
+    cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+    cmap
+}
+
+// Zeichenbreiten (1/1000 em) der Standard-Fonts für ASCII 32..=126 aus den Adobe-AFM-Dateien.
+// Oblique/Italic-Schnitte verwenden die Breiten des aufrechten Schnitts (bei Helvetica identisch).
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, 975, 722, 722, 722, 722, 667,
+    611, 778, 722, 278, 556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 333, 278, 333, 584, 556, 333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556,
+    278, 889, 611, 611, 611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+];
+const TIMES_ROMAN_WIDTHS: [u16; 95] = [
+    250, 333, 408, 500, 500, 833, 778, 180, 333, 333, 500, 564, 250, 333, 250, 278, 500, 500, 500,
+    500, 500, 500, 500, 500, 500, 500, 278, 278, 564, 564, 564, 444, 921, 722, 667, 667, 722, 611,
+    556, 722, 722, 333, 389, 722, 611, 889, 722, 722, 556, 722, 667, 556, 611, 722, 722, 944, 722,
+    722, 611, 333, 278, 333, 469, 500, 333, 444, 500, 444, 500, 444, 333, 500, 500, 278, 278, 500,
+    278, 778, 500, 500, 500, 500, 333, 389, 278, 500, 500, 722, 500, 500, 444, 480, 200, 480, 541,
+];
+const TIMES_BOLD_WIDTHS: [u16; 95] = [
+    250, 333, 555, 500, 500, 1000, 833, 278, 333, 333, 500, 570, 250, 333, 250, 278, 500, 500, 500,
+    500, 500, 500, 500, 500, 500, 500, 333, 333, 570, 570, 570, 500, 930, 722, 667, 722, 722, 667,
+    611, 778, 778, 389, 500, 778, 667, 944, 722, 778, 611, 778, 722, 556, 667, 722, 722, 1000, 722,
+    722, 667, 333, 278, 333, 581, 500, 333, 500, 556, 444, 556, 444, 333, 500, 556, 278, 333, 556,
+    278, 833, 556, 500, 556, 556, 444, 389, 333, 556, 500, 722, 500, 500, 444, 394, 220, 394, 520,
+];
+const COURIER_WIDTHS: [u16; 95] = [600; 95];
+
+/// Maße eines Standard-Fonts: Zeichenbreiten sowie Ober- und Unterlänge in 1/1000 em
+fn standard_font_metrics(pdf_font_name: &str) -> (&'static [u16; 95], f32, f32) {
+    if pdf_font_name.starts_with("Courier") {
+        (&COURIER_WIDTHS, 629.0, -157.0)
+    } else if pdf_font_name.starts_with("Times") {
+        let widths = if pdf_font_name.contains("Bold") { &TIMES_BOLD_WIDTHS } else { &TIMES_ROMAN_WIDTHS };
+        (widths, 683.0, -217.0)
+    } else {
+        let widths = if pdf_font_name.contains("Bold") { &HELVETICA_BOLD_WIDTHS } else { &HELVETICA_WIDTHS };
+        (widths, 718.0, -207.0)
+    }
+}
+
+// Textbreite in Punkten für einen Standard-Font. Zeichen außerhalb von ASCII werden mit der
+// Breite von "o" angenähert (Umlaute und Akzentbuchstaben liegen nahe daran).
+fn standard_text_width(text: &str, pdf_font_name: &str, font_size: f32) -> f32 {
+    let (widths, _, _) = standard_font_metrics(pdf_font_name);
+    let fallback = widths[(b'o' - 32) as usize];
+    text.chars()
+        .map(|c| {
+            let code = c as u32;
+            let width = if (32..=126).contains(&code) { widths[(code - 32) as usize] } else { fallback };
+            width as f32
+        })
+        .sum::<f32>() * font_size / 1000.0
+}
+
+// WinAnsiEncoding (CP1252): ASCII und Latin-1 ab 0xA0 direkt, dazu die Sonderzeichen in 0x80..0x9F.
+// None, wenn ein Zeichen nicht darstellbar ist.
+fn encode_win_ansi(text: &str) -> Option<Vec<u8>> {
+    text.chars()
+        .map(|c| match c {
+            '\u{20}'..='\u{7E}' | '\u{A0}'..='\u{FF}' => Some(c as u32 as u8),
+            '€' => Some(0x80), '‚' => Some(0x82), 'ƒ' => Some(0x83), '„' => Some(0x84),
+            '…' => Some(0x85), '†' => Some(0x86), '‡' => Some(0x87), 'ˆ' => Some(0x88),
+            '‰' => Some(0x89), 'Š' => Some(0x8A), '‹' => Some(0x8B), 'Œ' => Some(0x8C),
+            'Ž' => Some(0x8E), '‘' => Some(0x91), '’' => Some(0x92), '“' => Some(0x93),
+            '”' => Some(0x94), '•' => Some(0x95), '–' => Some(0x96), '—' => Some(0x97),
+            '˜' => Some(0x98), '™' => Some(0x99), 'š' => Some(0x9A), '›' => Some(0x9B),
+            'œ' => Some(0x9C), 'ž' => Some(0x9E), 'Ÿ' => Some(0x9F),
+            _ => None,
+        })
+        .collect()
+}
+
+// Schriften mit breiter Unicode-Abdeckung, in dieser Reihenfolge versucht
+const UNICODE_FALLBACK_FONTS: [&str; 5] = ["Arial Unicode MS", "Segoe UI", "Arial", "DejaVu Sans", "Noto Sans"];
+
+// Bettet die erste Ersatzschrift ein, die alle Zeichen von `text` enthält
+fn embed_unicode_fallback_font(doc: &mut Document, text: &str, debug_enabled: bool) -> Option<EmbeddedFont> {
+    for name in UNICODE_FALLBACK_FONTS {
+        let Some(data) = load_font_file(name, "Normal") else { continue };
+        match embed_font_subset(doc, &data, text) {
+            Ok(font) => {
+                debug_print(&format!("Unicode-Ersatzschrift eingebettet: {}", name), debug_enabled);
+                return Some(font);
+            }
+            Err(e) => debug_print(&format!("Ersatzschrift {} ungeeignet: {}", name, e), debug_enabled),
+        }
+    }
+    println!("WARNUNG: Keine Unicode-Ersatzschrift gefunden für '{}' - nicht darstellbare Zeichen werden als '?' ausgegeben", text);
+    None
+}
+
+/// Ein zum Schreiben vorbereiteter Text: Font-Ressource, kodierter String und Maße
+struct TextRun {
+    font_key: String,
+    text: Object,
+    /// Breite in Punkten
+    width: f32,
+    /// Ober- und Unterlänge in 1/1000 em
+    ascent: f32,
+    descent: f32,
+    /// WinAnsi (1 Byte je Zeichen) statt Identity-H, nur dann wirkt `Tw`
+    single_byte: bool,
+}
+
+impl TextRun {
+    fn embedded(text: &str, font_key: &str, font: &EmbeddedFont, font_size: f32) -> Self {
+        TextRun {
+            font_key: font_key.to_string(),
+            text: font.encode(text),
+            width: font.text_width(text, font_size),
+            ascent: font.ascent,
+            descent: font.descent,
+            single_byte: false,
+        }
+    }
+}
+
+// Tw wirkt nur auf das Einzelbyte-Leerzeichen, bei eingebetteten Schriften (2 Byte je Glyphe)
+// wird der Wortabstand deshalb als TJ-Verschiebung nach jedem Leerzeichen gesetzt
+fn word_spaced_text(text: &str, font: &EmbeddedFont, word_spacing: f32, font_size: f32) -> Operation {
+    let adjust = -word_spacing * 1000.0 / font_size;
+    let mut parts = Vec::new();
+    for (i, segment) in text.split(' ').enumerate() {
+        if i > 0 {
+            parts.push(font.encode(" "));
+            parts.push(adjust.into());
+        }
+        if !segment.is_empty() {
+            parts.push(font.encode(segment));
+        }
+    }
+    Operation::new("TJ", vec![Object::Array(parts)])
+}
+
+// Text für einen Standard-Font: WinAnsi-kodiert, sonst über die Unicode-Ersatzschrift;
+// ohne Ersatzschrift werden nicht darstellbare Zeichen durch '?' ersetzt
+// (`fallback`: Ressourcenname und Schrift)
+fn standard_text_run(text: &str, font_key: &str, pdf_font_name: &str, font_size: f32, fallback: Option<(&str, &EmbeddedFont)>) -> TextRun {
+    if let Some(bytes) = encode_win_ansi(text) {
+        let (_, ascent, descent) = standard_font_metrics(pdf_font_name);
+        return TextRun {
+            font_key: font_key.to_string(),
+            text: Object::String(bytes, lopdf::StringFormat::Literal),
+            width: standard_text_width(text, pdf_font_name, font_size),
+            ascent,
+            descent,
+            single_byte: true,
+        };
+    }
+    if let Some((fallback_key, font)) = fallback {
+        return TextRun::embedded(text, fallback_key, font, font_size);
+    }
+    let replaced: String = text.chars().map(|c| if encode_win_ansi(&c.to_string()).is_some() { c } else { '?' }).collect();
+    standard_text_run(&replaced, font_key, pdf_font_name, font_size, None)
+}
+
+// Text für einen Standard-Font im Overlay; mit `embed_all` (PDF/A) immer über die eingebettete
+// Ersatzschrift, damit kein nicht eingebetteter Font in die Seitenressourcen gelangt
+fn overlay_text_run(resources: &mut lopdf::Dictionary, standard_fonts: &mut std::collections::HashMap<&'static str, String>, text: &str, pdf_font_name: &'static str, font_size: f32, fallback: Option<(&str, &EmbeddedFont)>, embed_all: bool) -> TextRun {
+    if let (true, Some((fallback_key, font))) = (embed_all, fallback) {
+        return TextRun::embedded(text, fallback_key, font, font_size);
+    }
+    let font_key = add_standard_font(resources, standard_fonts, pdf_font_name);
+    standard_text_run(text, &font_key, pdf_font_name, font_size, fallback)
+}
+
+// Versatz des Textanfangs (Grundlinie links) relativ zum Ankerpunkt, im ungedrehten Textraum.
+// `width` in Punkten, `ascent`/`descent` in 1/1000 em.
+fn text_anchor_offset(align: TextAlign, anchor: TextAnchor, width: f32, ascent: f32, descent: f32, font_size: f32) -> (f32, f32) {
+    let dx = match align {
+        TextAlign::Left => 0.0,
+        TextAlign::Center => -width / 2.0,
+        TextAlign::Right => -width,
+    };
+    let dy = match anchor {
+        TextAnchor::Baseline => 0.0,
+        TextAnchor::Top => -ascent * font_size / 1000.0,
+        TextAnchor::Middle => -(ascent + descent) / 2.0 * font_size / 1000.0,
+    };
+    (dx, dy)
+}
+
+// Sichtbare Textbreite für die Ausrichtung in Punkten: Glyphenbreite plus Zeichen-/Wortabstand,
+// skaliert mit `Tz`. `Tc` folgt jeder Glyphe, nach der letzten entsteht aber keine Tinte mehr.
+fn spaced_text_width(v: &VertreterConfig, text: &str, glyph_width: f32) -> f32 {
+    let spacing_count = text.chars().count().saturating_sub(1) as f32;
+    let space_count = text.chars().filter(|c| *c == ' ').count() as f32;
+    (glyph_width + spacing_count * v.char_spacing + space_count * v.word_spacing) * v.horizontal_scaling / 100.0
+}
+
+// Abbildung eines Schriftnamens/-stils auf einen der 14 Standard-PDF-Fonts
+// (Fallback, wenn keine Schriftdatei eingebettet werden kann)
+fn standard_pdf_font(font_name: &str, font_style: &str) -> &'static str {
+    let base_font = match font_name {
+        // Times Familie - BESSERE ERKENNUNG
+        "Times New Roman" | "Times" | "TimesNewRoman" => "Times",
+        // Arial/Helvetica Familie - BESSERE ERKENNUNG  
+        "Arial" | "Helvetica" | "Arial Unicode MS" => "Helvetica",
+        // Courier Familie
+        "Courier New" | "Courier" | "CourierNew" => "Courier",
+        // Calibri -> Helvetica (ähnlichster Standard-Font)
+        "Calibri" => "Helvetica",
+        // Verdana/Georgia -> Helvetica 
+        "Verdana" | "Georgia" => "Helvetica",
+        // Adobe/Source Fonts -> Helvetica
+        name if name.contains("Adobe") || name.contains("Myriad") || name.contains("Minion") || name.contains("Source") => "Helvetica",
+        // Weitere Windows-Fonts
+        "Trebuchet MS" | "Comic Sans MS" | "Impact" | "Lucida Console" | "Tahoma" | "Candara" => "Helvetica",
+        // Font-Namen mit Styles (extrahiere Basis-Font)
+        name if name.contains("Bold") || name.contains("Italic") || name.contains("Light") || name.contains("Medium") => {
+            if name.contains("Times") {
+                "Times"
+            } else if name.contains("Arial") {
+                "Helvetica"
+            } else if name.contains("Courier") {
+                "Courier"
+            } else {
+                "Helvetica"
+            }
+        },
+        _ => "Helvetica" // Sicherer Fallback
+    };
+    
+    // Erweiterte Style-Zuordnung
+    match (base_font, font_style) {
+        // Times Familie
+        ("Times", "Bold") => "Times-Bold",
+        ("Times", "Italic") => "Times-Italic", 
+        ("Times", "Bold Italic") | ("Times", "BoldItalic") => "Times-BoldItalic",
+        ("Times", _) => "Times-Roman",
+        // Helvetica Familie (Arial → Helvetica Mapping)
+        ("Helvetica", "Bold") => "Helvetica-Bold",
+        ("Helvetica", "Italic") => "Helvetica-Oblique",
+        ("Helvetica", "Bold Italic") | ("Helvetica", "BoldItalic") => "Helvetica-BoldOblique", 
+        ("Helvetica", "Light") => "Helvetica", // Light nicht verfügbar, fallback
+        ("Helvetica", "Medium") => "Helvetica-Bold", // Medium → Bold mapping
+        ("Helvetica", "Heavy") | ("Helvetica", "Black") => "Helvetica-Bold", // Heavy/Black → Bold mapping
+        ("Helvetica", "Thin") => "Helvetica", // Thin nicht verfügbar, fallback
+        ("Helvetica", _) => "Helvetica",
+        // Courier Familie
+        ("Courier", "Bold") => "Courier-Bold",
+        ("Courier", "Italic") => "Courier-Oblique",
+        ("Courier", "Bold Italic") | ("Courier", "BoldItalic") => "Courier-BoldOblique",
+        ("Courier", _) => "Courier",
+        // Sicherer Fallback
+        (_, _) => "Helvetica"
+    }
+}
+
+// Wirksame Ressourcen einer Seite als eigene Kopie: das Resources-Dict der Seite oder, wenn
+// sie keines hat, das des nächsten Vorfahren im Seitenbaum (Resources wird vererbt). Font-
+// und XObject-Dicts werden aufgelöst und kopiert, damit Änderungen nur diese Seite betreffen
+// und nicht über ein geteiltes Dict auf andere Seiten durchschlagen.
+fn effective_page_resources(doc: &Document, page_id: lopdf::ObjectId) -> lopdf::Dictionary {
+    let resolve = |obj: &Object| match obj {
+        Object::Reference(id) => doc.get_dictionary(*id).ok().cloned(),
+        Object::Dictionary(dict) => Some(dict.clone()),
+        _ => None,
+    };
+    let mut resources = get_inherited_page_attr(doc, page_id, b"Resources")
+        .and_then(|obj| resolve(&obj))
+        .unwrap_or_default();
+    for category in [b"Font".as_slice(), b"XObject".as_slice()] {
+        let resolved = match resources.get(category) {
+            Ok(obj) => resolve(obj).unwrap_or_else(lopdf::Dictionary::new),
+            Err(_) => continue,
+        };
+        resources.set(category, resolved);
+    }
+    resources
+}
+
+/// Sichtbarer Bereich einer Seite: CropBox (sonst MediaBox) und Drehung (/Rotate), beide
+/// ggf. aus dem Seitenbaum geerbt. Config-Koordinaten beziehen sich auf die linke untere
+/// Ecke dieses Bereichs, so wie die Seite angezeigt wird.
+#[derive(Clone, Copy, Debug)]
+struct PageGeometry {
+    llx: f32,
+    lly: f32,
+    /// Breite und Höhe der Box im (ungedrehten) Benutzerraum
+    width: f32,
+    height: f32,
+    /// Drehung im Uhrzeigersinn: 0, 90, 180 oder 270
+    rotate: i32,
+}
+
+impl PageGeometry {
+    fn of_page(doc: &Document, page_id: lopdf::ObjectId) -> Self {
+        let page_box = |key: &[u8]| -> Option<[f32; 4]> {
+            let values: Vec<f32> = get_inherited_page_attr(doc, page_id, key)?
+                .as_array().ok()?
+                .iter()
+                .filter_map(|v| v.as_float().ok())
+                .collect();
+            (values.len() == 4).then(|| [values[0].min(values[2]), values[1].min(values[3]), values[0].max(values[2]), values[1].max(values[3])])
+        };
+        let [llx, lly, urx, ury] = page_box(b"CropBox")
+            .or_else(|| page_box(b"MediaBox"))
+            .unwrap_or([0.0, 0.0, 595.0, 842.0]);
+        let rotate = get_inherited_page_attr(doc, page_id, b"Rotate")
+            .and_then(|r| r.as_i64().ok())
+            .map(|r| (r.rem_euclid(360) / 90 * 90) as i32)
+            .unwrap_or(0);
+        PageGeometry { llx, lly, width: (urx - llx).max(1.0), height: (ury - lly).max(1.0), rotate }
+    }
+
+    /// Breite und Höhe, wie die Seite angezeigt wird
+    fn displayed_size(&self) -> (f32, f32) {
+        if self.rotate % 180 == 0 { (self.width, self.height) } else { (self.height, self.width) }
+    }
+
+    /// `cm`-Matrix von Config-Koordinaten (Anzeigelage, Ursprung links unten im sichtbaren
+    /// Bereich) in den Benutzerraum der Seite
+    fn overlay_matrix(&self) -> [f32; 6] {
+        let (llx, lly, w, h) = (self.llx, self.lly, self.width, self.height);
+        match self.rotate {
+            90 => [0.0, 1.0, -1.0, 0.0, llx + w, lly],
+            180 => [-1.0, 0.0, 0.0, -1.0, llx + w, lly + h],
+            270 => [0.0, -1.0, 1.0, 0.0, llx, lly + h],
+            _ => [1.0, 0.0, 0.0, 1.0, llx, lly],
+        }
+    }
+
+    /// Rechteck aus dem Benutzerraum der Seite in Config-Koordinaten (x, y, Breite, Höhe)
+    fn to_display_rect(self, rect: [f32; 4]) -> (f32, f32, f32, f32) {
+        let (llx, lly, w, h) = (self.llx, self.lly, self.width, self.height);
+        let to_display = |x: f32, y: f32| match self.rotate {
+            90 => (y - lly, llx + w - x),
+            180 => (llx + w - x, lly + h - y),
+            270 => (lly + h - y, x - llx),
+            _ => (x - llx, y - lly),
+        };
+        let (u1, v1) = to_display(rect[0], rect[1]);
+        let (u2, v2) = to_display(rect[2], rect[3]);
+        (u1.min(u2), v1.min(v2), (u2 - u1).abs(), (v2 - v1).abs())
+    }
+}
+
+// Angezeigte Seitengröße der ersten Seite einer Vorlage (für die Vorschau im Editor)
+fn template_page_size(path: &std::path::Path) -> Option<(f32, f32)> {
+    let doc = Document::load(path).ok()?;
+    let page_id = *doc.get_pages().get(&1)?;
+    Some(PageGeometry::of_page(&doc, page_id).displayed_size())
+}
+
+// Unterdict einer Ressourcen-Kategorie (Font, XObject), bei Bedarf angelegt
+fn resource_category<'a>(resources: &'a mut lopdf::Dictionary, category: &[u8]) -> &'a mut lopdf::Dictionary {
+    if !matches!(resources.get(category), Ok(Object::Dictionary(_))) {
+        resources.set(category, lopdf::Dictionary::new());
+    }
+    resources.get_mut(category).and_then(Object::as_dict_mut).expect("Ressourcen-Kategorie ist kein Dict")
+}
+
+// Erster freier Name `<prefix><n>` in einer Ressourcen-Kategorie
+fn unique_resource_name(entries: &lopdf::Dictionary, prefix: &str) -> String {
+    (1..).map(|n| format!("{}{}", prefix, n)).find(|name| !entries.has(name.as_bytes())).unwrap()
+}
+
+// Objekt unter einem freien Namen eintragen; ist es bereits eingetragen (z.B. ein von mehreren
+// Elementen geteiltes Bild), wird der vorhandene Name verwendet
+fn add_page_resource(resources: &mut lopdf::Dictionary, category: &[u8], prefix: &str, id: lopdf::ObjectId) -> String {
+    let entries = resource_category(resources, category);
+    if let Some((name, _)) = entries.iter().find(|(_, value)| matches!(value, Object::Reference(r) if *r == id)) {
+        return String::from_utf8_lossy(name).into_owned();
+    }
+    let name = unique_resource_name(entries, prefix);
+    entries.set(name.as_bytes(), id);
+    name
+}
+
+// Standard-Type1-Font (WinAnsi) unter einem freien Namen eintragen, einmal je Font und Seite
+fn add_standard_font(resources: &mut lopdf::Dictionary, added: &mut std::collections::HashMap<&'static str, String>, pdf_font_name: &'static str) -> String {
+    if let Some(name) = added.get(pdf_font_name) {
+        return name.clone();
+    }
+    let entries = resource_category(resources, b"Font");
+    let name = unique_resource_name(entries, "F");
+    entries.set(name.as_bytes(), dictionary!{
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => pdf_font_name,
+        "Encoding" => "WinAnsiEncoding"
+    });
+    added.insert(pdf_font_name, name.clone());
+    name
+}
+
+/// Auf einer Seite zu zeichnendes Element mit den dafür erzeugten PDF-Objekten
+enum PageItem<'a> {
+    Qr(&'a QrCodeConfig, QrImageRefs),
+    Barcode(&'a BarcodeConfig, &'a BarcodeImage),
+    Image(&'a ImageConfig, EmbeddedGraphic),
+    Text(&'a VertreterConfig, &'a str, Option<&'a EmbeddedFont>),
+    Rect(&'a RectConfig),
+    Line(&'a LineConfig),
+}
+
+fn process_page_elements(doc: &mut Document, page_id: lopdf::ObjectId, _page_number: u32,
+                         items: &[PageItem], fallback_font: Option<&EmbeddedFont>, embed_all: bool,
+                         _debug_enabled: bool) {
+
+    // Overlay in einem eigenen Content-Stream, der Inhalt der Vorlage bleibt unangetastet
+    let mut content = Content { operations: Vec::new() };
+
+    // Koordinatensystem auf den sichtbaren Bereich in Anzeigelage legen (MediaBox-Ursprung,
+    // CropBox und /Rotate), danach gelten die Config-Koordinaten unverändert
+    let matrix = PageGeometry::of_page(doc, page_id).overlay_matrix();
+    if matrix != [1.0, 0.0, 0.0, 1.0, 0.0, 0.0] {
+        content.operations.push(Operation::new("cm", matrix.iter().map(|v| (*v).into()).collect()));
+    }
+
+    // Ressourcen zuerst eintragen: die Namen werden gegen die (ggf. geerbten) Ressourcen der
+    // Vorlage vergeben und dürfen dort vorhandene Einträge nie überschreiben
+    let mut resources = effective_page_resources(doc, page_id);
+    let mut standard_fonts: std::collections::HashMap<&'static str, String> = std::collections::HashMap::new();
+    let fallback = fallback_font.map(|font| (add_page_resource(&mut resources, b"Font", "FU", font.font_id), font));
+    let fallback = fallback.as_ref().map(|(name, font)| (name.as_str(), *font));
+
+    // Elemente in Zeichenreihenfolge zeichnen (spätere liegen über früheren)
+    for item in items {
+        match item {
+            // Bilder: beide Grafikarten sind auf das Einheitsquadrat abgebildet; bei "Füllen" wird
+            // auf den Rahmen beschnitten.
+            PageItem::Image(image, graphic) => {
+                let image_name = add_page_resource(&mut resources, b"XObject", "Ig", graphic.id);
+                let (x, y, w, h) = image.fit.place((image.x, image.y, image.width, image.height), (graphic.width, graphic.height));
+                content.operations.push(Operation::new("q", vec![]));
+                if image.fit == ImageFit::Cover {
+                    content.operations.push(Operation::new("re", vec![image.x.into(), image.y.into(), image.width.into(), image.height.into()]));
+                    content.operations.push(Operation::new("W", vec![]));
+                    content.operations.push(Operation::new("n", vec![]));
+                }
+                content.operations.push(Operation::new("cm", vec![w.into(), 0.into(), 0.into(), h.into(), x.into(), y.into()]));
+                content.operations.push(Operation::new("Do", vec![Object::Name(image_name.into_bytes())]));
+                content.operations.push(Operation::new("Q", vec![]));
+            }
+            // Alle QR-Codes platzieren. Gezeichnet wird in einem Koordinatensystem mit Ursprung in der
+            // Code-Mitte, damit Drehung, Logo und Beschriftung gemeinsam um den Mittelpunkt drehen.
+            PageItem::Qr(qr_config, images) => {
+                let image_name = add_page_resource(&mut resources, b"XObject", "Im", images.image_id);
+                let half = qr_config.size / 2.0;
+                let (sin, cos) = qr_config.rotation.to_radians().sin_cos();
+                content.operations.push(Operation::new("q", vec![]));
+                content.operations.push(Operation::new("cm", vec![
+                    cos.into(), sin.into(), (-sin).into(), cos.into(),
+                    (qr_config.x + half).into(), (qr_config.y + half).into()
+                ]));
+                content.operations.push(Operation::new("q", vec![]));
+                content.operations.push(Operation::new("cm", vec![
+                    qr_config.size.into(), 0.into(), 0.into(), qr_config.size.into(), 
+                    (-half).into(), (-half).into()
+                ]));
+                content.operations.push(Operation::new("Do", vec![Object::Name(image_name.into_bytes())]));
+                content.operations.push(Operation::new("Q", vec![]));
+
+                // Logo mittig über die freigeräumte Fläche legen (Seitenverhältnis bleibt erhalten)
+                if let Some((logo, fraction)) = images.logo {
+                    let logo_name = add_page_resource(&mut resources, b"XObject", "Lg", logo.id);
+                    let box_size = qr_config.size * fraction;
+                    let aspect = logo.width / logo.height.max(0.001);
+                    let (w, h) = if aspect >= 1.0 { (box_size, box_size / aspect) } else { (box_size * aspect, box_size) };
+                    content.operations.push(Operation::new("q", vec![]));
+                    content.operations.push(Operation::new("cm", vec![
+                        w.into(), 0.into(), 0.into(), h.into(), (-w / 2.0).into(), (-h / 2.0).into()
+                    ]));
+                    content.operations.push(Operation::new("Do", vec![Object::Name(logo_name.into_bytes())]));
+                    content.operations.push(Operation::new("Q", vec![]));
+                }
+
+                // Beschriftung zentriert unter dem Code (Oberkante im Abstand caption_offset)
+                if !qr_config.caption.is_empty() {
+                    let pdf_font_name = standard_pdf_font(&qr_config.caption_font, "Normal");
+                    let run = overlay_text_run(&mut resources, &mut standard_fonts, &qr_config.caption, pdf_font_name, qr_config.caption_size, fallback, embed_all);
+                    let text_y = -half - qr_config.caption_offset - qr_config.caption_size * 0.75;
+                    content.operations.push(Operation::new("BT", vec![]));
+                    content.operations.push(Operation::new("Tf", vec![Object::Name(run.font_key.into_bytes()), qr_config.caption_size.into()]));
+                    content.operations.push(Operation::new("Td", vec![(-run.width / 2.0).into(), text_y.into()]));
+                    content.operations.push(Operation::new("Tj", vec![run.text]));
+                    content.operations.push(Operation::new("ET", vec![]));
+                }
+                content.operations.push(Operation::new("Q", vec![]));
+            }
+            // Strichcodes platzieren (Bild gestreckt auf Breite x Strichhöhe, Klartext zentriert darunter)
+            PageItem::Barcode(barcode, image) => {
+                let image_name = add_page_resource(&mut resources, b"XObject", "Bc", image.image_id);
+                let height = if barcode.symbology.is_square() { barcode.size } else { barcode.bar_height };
+                content.operations.push(Operation::new("q", vec![]));
+                content.operations.push(Operation::new("cm", vec![
+                    barcode.size.into(), 0.into(), 0.into(), height.into(),
+                    barcode.x.into(), barcode.y.into()
+                ]));
+                content.operations.push(Operation::new("Do", vec![Object::Name(image_name.into_bytes())]));
+                content.operations.push(Operation::new("Q", vec![]));
+
+                if barcode.show_text && !image.text.is_empty() {
+                    let run = overlay_text_run(&mut resources, &mut standard_fonts, &image.text, "Helvetica", barcode.text_size, fallback, embed_all);
+                    let text_x = barcode.x + (barcode.size - run.width) / 2.0;
+                    let text_y = barcode.y - barcode.text_size;
+                    content.operations.push(Operation::new("BT", vec![]));
+                    content.operations.push(Operation::new("Tf", vec![Object::Name(run.font_key.into_bytes()), barcode.text_size.into()]));
+                    content.operations.push(Operation::new("Td", vec![text_x.into(), text_y.into()]));
+                    content.operations.push(Operation::new("Tj", vec![run.text]));
+                    content.operations.push(Operation::new("ET", vec![]));
+                }
+            }
+            // Vertreternummern: Ankerpunkt (x, y) per Tm setzen (inkl. Drehung), dann mit Td um die aus
+            // den Schriftmaßen berechnete Ausrichtung im Textraum verschieben
+            PageItem::Text(vertreter_config, text, embedded) => {
+                let font_size = vertreter_config.font_size;
+                let run = match embedded {
+                    Some(font) => {
+                        let font_key = add_page_resource(&mut resources, b"Font", "FE", font.font_id);
+                        TextRun::embedded(text, &font_key, font, font_size)
+                    }
+                    None => {
+                        let pdf_font_name = standard_pdf_font(&vertreter_config.font_name, &vertreter_config.font_style);
+                        overlay_text_run(&mut resources, &mut standard_fonts, text, pdf_font_name, font_size, fallback, embed_all)
+                    }
+                };
+                let width = spaced_text_width(vertreter_config, text, run.width);
+                let (dx, dy) = text_anchor_offset(vertreter_config.align, vertreter_config.anchor, width, run.ascent, run.descent, font_size);
+                let (sin, cos) = vertreter_config.rotation.to_radians().sin_cos();
+                let [r, g, b] = vertreter_config.color;
+        
+                content.operations.push(Operation::new("q", vec![]));
+                content.operations.push(Operation::new("BT", vec![]));
+                content.operations.push(Operation::new("rg", vec![
+                    (r as f32 / 255.0).into(), (g as f32 / 255.0).into(), (b as f32 / 255.0).into()
+                ]));
+                content.operations.push(Operation::new("Tf", vec![Object::Name(run.font_key.into_bytes()), font_size.into()]));
+                content.operations.push(Operation::new("Tc", vec![vertreter_config.char_spacing.into()]));
+                content.operations.push(Operation::new("Tz", vec![vertreter_config.horizontal_scaling.into()]));
+                content.operations.push(Operation::new("Tm", vec![
+                    cos.into(), sin.into(), (-sin).into(), cos.into(),
+                    vertreter_config.x.into(), vertreter_config.y.into()
+                ]));
+                content.operations.push(Operation::new("Td", vec![dx.into(), dy.into()]));
+                if run.single_byte {
+                    content.operations.push(Operation::new("Tw", vec![vertreter_config.word_spacing.into()]));
+                    content.operations.push(Operation::new("Tj", vec![run.text]));
+                } else if vertreter_config.word_spacing != 0.0 {
+                    let font = (*embedded).or(fallback_font).expect("Mehrbyte-Text ohne eingebettete Schrift");
+                    content.operations.push(word_spaced_text(text, font, vertreter_config.word_spacing, font_size));
+                } else {
+                    content.operations.push(Operation::new("Tj", vec![run.text]));
+                }
+                content.operations.push(Operation::new("ET", vec![]));
+                content.operations.push(Operation::new("Q", vec![]));
+            }
+            // Rechteck: Füllung und/oder Rahmen
+            PageItem::Rect(rect) => {
+                let paint = match (rect.fill, rect.stroke) {
+                    (Some(_), Some(_)) => "B",
+                    (Some(_), None) => "f",
+                    (None, Some(_)) => "S",
+                    (None, None) => continue,
+                };
+                content.operations.push(Operation::new("q", vec![]));
+                if let Some([r, g, b]) = rect.fill {
+                    content.operations.push(Operation::new("rg", vec![(r as f32 / 255.0).into(), (g as f32 / 255.0).into(), (b as f32 / 255.0).into()]));
+                }
+                if let Some([r, g, b]) = rect.stroke {
+                    content.operations.push(Operation::new("RG", vec![(r as f32 / 255.0).into(), (g as f32 / 255.0).into(), (b as f32 / 255.0).into()]));
+                    content.operations.push(Operation::new("w", vec![rect.stroke_width.into()]));
+                }
+                content.operations.push(Operation::new("re", vec![rect.x.into(), rect.y.into(), rect.width.into(), rect.height.into()]));
+                content.operations.push(Operation::new(paint, vec![]));
+                content.operations.push(Operation::new("Q", vec![]));
+            }
+            // Linie von (x, y) nach (x2, y2)
+            PageItem::Line(line) => {
+                let [r, g, b] = line.color;
+                content.operations.push(Operation::new("q", vec![]));
+                content.operations.push(Operation::new("RG", vec![(r as f32 / 255.0).into(), (g as f32 / 255.0).into(), (b as f32 / 255.0).into()]));
+                content.operations.push(Operation::new("w", vec![line.stroke_width.into()]));
+                content.operations.push(Operation::new("m", vec![line.x.into(), line.y.into()]));
+                content.operations.push(Operation::new("l", vec![line.x2.into(), line.y2.into()]));
+                content.operations.push(Operation::new("S", vec![]));
+                content.operations.push(Operation::new("Q", vec![]));
+            }
+        }
+    }
+
+    // Ressourcen als eigenes Dict der Seite setzen (überdeckt ein geerbtes, ohne es zu verändern)
+    doc.get_object_mut(page_id)
+        .and_then(Object::as_dict_mut)
+        .expect("Konnte Seite nicht als Dict lesen")
+        .set("Resources", resources);
+
+    let encoded_content = content.encode().expect("Konnte Inhalt nicht kodieren");
+    append_overlay_content(doc, page_id, encoded_content);
+}
+
+/// Widget (sichtbarer Teil) eines Formularfeldes der Vorlage
+#[derive(Clone, Debug)]
+struct FormWidget {
+    /// Vollständiger Feldname ("Eltern.Kind")
+    name: String,
+    /// Feld, das den Wert trägt (bei zusammengelegtem Feld/Widget dasselbe Objekt)
+    field_id: lopdf::ObjectId,
+    widget_id: lopdf::ObjectId,
+    page_id: lopdf::ObjectId,
+    /// Rechteck im Benutzerraum der Seite [llx, lly, urx, ury]
+    rect: [f32; 4],
+}
+
+fn uses_form_fields(config: &Config) -> bool {
+    config.qr_codes().any(|qr| !qr.form_field.is_empty()) || config.vertreter().any(|v| !v.form_field.is_empty())
+}
+
+// Direktes Objekt oder Ziel einer Referenz
+fn resolve_object<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Object> {
+    match object {
+        Object::Reference(id) => doc.get_object(*id).ok(),
+        other => Some(other),
+    }
+}
+
+// PDF-Textstring lesen (UTF-16BE mit BOM, sonst PDFDocEncoding ≈ Latin-1)
+fn decode_pdf_text_string(bytes: &[u8]) -> String {
+    if bytes.starts_with(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = bytes[2..].chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+        String::from_utf16_lossy(&units)
     } else {
-        println!("📝 Alle Fonts: {:?}", fonts);
+        bytes.iter().map(|&b| b as char).collect()
     }
-    
-    fonts
 }
 
-// Aktualisiere Font-Cache (kann von UI aufgerufen werden)
-fn refresh_font_cache() -> Vec<String> {
-    println!("🔄 FONT-CACHE: Aktualisiere Schriftarten-Liste...");
-    
-    // PERFORMANCE: Cache in Datei speichern um wiederholte Scans zu vermeiden
-    let cache_file = std::path::Path::new("font_cache.json");
-    let cache_age_hours = 24; // Cache 24 Stunden gültig
-    
-    // Prüfe ob Cache-Datei existiert und noch gültig ist
-    if cache_file.exists() {
-        if let Ok(metadata) = std::fs::metadata(cache_file) {
-            if let Ok(modified) = metadata.modified() {
-                if let Ok(elapsed) = modified.elapsed() {
-                    if elapsed.as_secs() < (cache_age_hours * 3600) {
-                        println!("📁 FONT-CACHE: Verwende gecachte Font-Liste ({}h alt)", elapsed.as_secs() / 3600);
-                        
-                        // Lade aus Cache
-                        if let Ok(cache_content) = std::fs::read_to_string(cache_file) {
-                            if let Ok(cached_fonts) = serde_json::from_str::<Vec<String>>(&cache_content) {
-                                if !cached_fonts.is_empty() {
-                                    println!("✅ FONT-CACHE: {} Fonts aus Cache geladen", cached_fonts.len());
-                                    return cached_fonts;
-                                }
-                            }
-                        }
-                    }
-                }
+// PDF-Textstring schreiben: ASCII direkt, sonst UTF-16BE mit BOM
+fn pdf_text_string(text: &str) -> Object {
+    if text.is_ascii() {
+        return Object::string_literal(text);
+    }
+    let mut bytes = vec![0xFE, 0xFF];
+    for unit in text.encode_utf16() {
+        bytes.extend_from_slice(&unit.to_be_bytes());
+    }
+    Object::String(bytes, lopdf::StringFormat::Hexadecimal)
+}
+
+// Alle Widgets des AcroForms der Vorlage mit vollständigem Feldnamen
+fn form_widgets(doc: &Document) -> Vec<FormWidget> {
+    let mut widgets = Vec::new();
+    let fields = doc.trailer.get(b"Root").ok()
+        .and_then(|root| resolve_object(doc, root))
+        .and_then(|root| root.as_dict().ok())
+        .and_then(|catalog| catalog.get(b"AcroForm").ok())
+        .and_then(|form| resolve_object(doc, form))
+        .and_then(|form| form.as_dict().ok())
+        .and_then(|form| form.get(b"Fields").ok())
+        .and_then(|fields| resolve_object(doc, fields))
+        .and_then(|fields| fields.as_array().ok());
+    let Some(fields) = fields else { return widgets };
+
+    // Seiten über ihre /Annots zuordnen, falls ein Widget kein /P hat
+    let mut widget_pages = std::collections::HashMap::new();
+    for page_id in doc.get_pages().into_values() {
+        let annots = doc.get_dictionary(page_id).ok()
+            .and_then(|page| page.get(b"Annots").ok())
+            .and_then(|annots| resolve_object(doc, annots))
+            .and_then(|annots| annots.as_array().ok());
+        for annot in annots.into_iter().flatten() {
+            if let Ok(id) = annot.as_reference() {
+                widget_pages.insert(id, page_id);
             }
         }
     }
-    
-    // Cache ist ungültig oder existiert nicht - neu scannen
-    println!("🔍 FONT-CACHE: Scanne Schriftarten neu...");
-    let fonts = get_installed_fonts_with_styles_optimized();
-    
-    // PERFORMANCE: Begrenze Anzahl der Fonts für UI-Performance (WENIGER für bessere Performance)
-    let max_fonts = 50; // Reduziert von 200 auf 50 für flüssige UI
-    let mut filtered_fonts = fonts;
-    if filtered_fonts.len() > max_fonts {
-        println!("⚡ PERFORMANCE: Begrenze Fonts von {} auf {} für flüssige UI", filtered_fonts.len(), max_fonts);
-        
-        // Priorisiere häufige Fonts
-        let priority_fonts = ["Arial", "Calibri", "Times New Roman", "Helvetica", "Verdana", "Georgia", "Tahoma", "Segoe UI"];
-        let mut prioritized = Vec::new();
-        let mut others = Vec::new();
-        
-        for font in filtered_fonts {
-            if priority_fonts.iter().any(|pf| font.contains(pf)) {
-                prioritized.push(font);
-            } else {
-                others.push(font);
-            }
+    for field in fields {
+        if let Ok(id) = field.as_reference() {
+            collect_form_field(doc, id, "", &widget_pages, &mut widgets, 0);
         }
-        
-        // Erst priority_fonts, dann die ersten "others" bis max_fonts erreicht
-        prioritized.extend(others.into_iter().take(max_fonts.saturating_sub(prioritized.len())));
-        filtered_fonts = prioritized;
     }
-    
-    // Cache speichern
-    if let Ok(cache_json) = serde_json::to_string_pretty(&filtered_fonts) {
-        if let Err(e) = std::fs::write(cache_file, cache_json) {
-            println!("⚠️  FONT-CACHE: Konnte Cache nicht speichern: {}", e);
-        } else {
-            println!("💾 FONT-CACHE: Cache gespeichert");
+    widgets
+}
+
+// Feld rekursiv durchlaufen: Kinder mit /T sind Unterfelder, Kinder ohne /T Widgets des Feldes
+fn collect_form_field(doc: &Document, field_id: lopdf::ObjectId, parent_name: &str,
+                      widget_pages: &std::collections::HashMap<lopdf::ObjectId, lopdf::ObjectId>, widgets: &mut Vec<FormWidget>, depth: usize) {
+    if depth > 32 {
+        return; // Schutz gegen zyklische Feldbäume
+    }
+    let Ok(dict) = doc.get_dictionary(field_id) else { return };
+    let name = match dict.get(b"T").and_then(Object::as_str) {
+        Ok(partial) if parent_name.is_empty() => decode_pdf_text_string(partial),
+        Ok(partial) => format!("{}.{}", parent_name, decode_pdf_text_string(partial)),
+        Err(_) => parent_name.to_string(),
+    };
+    let push_widget = |widgets: &mut Vec<FormWidget>, widget_id: lopdf::ObjectId, widget: &lopdf::Dictionary| {
+        let rect: Vec<f32> = widget.get(b"Rect").ok()
+            .and_then(|r| resolve_object(doc, r))
+            .and_then(|r| r.as_array().ok())
+            .map(|r| r.iter().filter_map(|v| v.as_float().ok()).collect())
+            .unwrap_or_default();
+        let page_id = widget.get(b"P").and_then(Object::as_reference).ok()
+            .or_else(|| widget_pages.get(&widget_id).copied());
+        if let (4, Some(page_id)) = (rect.len(), page_id) {
+            widgets.push(FormWidget {
+                name: name.clone(),
+                field_id,
+                widget_id,
+                page_id,
+                rect: [rect[0].min(rect[2]), rect[1].min(rect[3]), rect[0].max(rect[2]), rect[1].max(rect[3])],
+            });
+        }
+    };
+    match dict.get(b"Kids").ok().and_then(|kids| resolve_object(doc, kids)).and_then(|kids| kids.as_array().ok()) {
+        Some(kids) => {
+            for kid_id in kids.iter().filter_map(|kid| kid.as_reference().ok()) {
+                let Ok(kid) = doc.get_dictionary(kid_id) else { continue };
+                if kid.has(b"T") {
+                    collect_form_field(doc, kid_id, &name, widget_pages, widgets, depth + 1);
+                } else {
+                    push_widget(widgets, kid_id, kid);
+                }
+            }
         }
+        None => push_widget(widgets, field_id, dict),
     }
-    
-    filtered_fonts
 }
 
-// Optimierte Font-Scanning-Funktion
-fn get_installed_fonts_with_styles_optimized() -> Vec<String> {
-    // Verwende die existierende Funktion aber mit Performance-Verbesserungen
-    get_installed_fonts_with_styles()
+// Widgets zu einem Feldnamen aus der Config: vollständiger Name oder letzter Namensteil
+fn matching_form_widgets<'a>(widgets: &'a [FormWidget], name: &'a str) -> impl Iterator<Item = &'a FormWidget> + 'a {
+    widgets.iter().filter(move |w| w.name == name || w.name.rsplit('.').next() == Some(name))
 }
 
-// Font-Pfad mit mehreren Quellen finden (ohne Admin-Rechte)
-fn find_font_file(font_name: &str, style: &str) -> Option<std::path::PathBuf> {
-    // Mehrere mögliche Font-Ordner (auch ohne Admin-Rechte)
-    let font_dirs = vec![
-        "C:\\Windows\\Fonts".to_string(),
-        format!("{}\\AppData\\Local\\Microsoft\\Windows\\Fonts", std::env::var("USERPROFILE").unwrap_or_default()),
-        format!("{}\\AppData\\Roaming\\Adobe\\CoreSync\\plugins\\livetype\\.r", std::env::var("USERPROFILE").unwrap_or_default()),
-        ".\\fonts".to_string(), // Lokaler fonts Ordner im Projekt
-    ];
-    
-    // Mögliche Dateinamen für den Font (viele Varianten)
-    let font_base = font_name.replace(" ", "").to_lowercase();
-    let style_lower = style.to_lowercase();
-    
-    let possible_names = vec![
-        // Standard-Benennungen
-        format!("{}.ttf", font_base),
-        format!("{}.otf", font_base), 
-        format!("{}_{}.ttf", font_base, style_lower),
-        format!("{}_{}.otf", font_base, style_lower),
-        format!("{}-{}.ttf", font_base, style_lower),
-        format!("{}-{}.otf", font_base, style_lower),
-        // Windows-spezifische Benennungen
-        format!("{}b.ttf", font_base), // Bold
-        format!("{}i.ttf", font_base), // Italic
-        format!("{}z.ttf", font_base), // Bold Italic
-        format!("{}bd.ttf", font_base), // Bold
-        format!("{}it.ttf", font_base), // Italic
-        // Vollständige Namen
-        format!("{}.ttf", font_name.replace(" ", "")),
-        format!("{}.otf", font_name.replace(" ", "")),
-        format!("{} {}.ttf", font_name, style),
-        format!("{} {}.otf", font_name, style),
-        // Spezielle Arial-Varianten
-        format!("arial{}.ttf", if style_lower.contains("bold") { "bd" } else if style_lower.contains("italic") { "i" } else { "" }),
-        // Calibri-Varianten  
-        format!("calibri{}.ttf", if style_lower.contains("bold") { "b" } else if style_lower.contains("italic") { "i" } else { "" }),
-        // Times New Roman-Varianten
-        format!("times{}.ttf", if style_lower.contains("bold") { "bd" } else if style_lower.contains("italic") { "i" } else { "" }),
-    ];
-    
-    // Alle Kombinationen durchprobieren
-    for dir in font_dirs {
-        for name in &possible_names {
-            let font_path = std::path::Path::new(&dir).join(name);
-            if font_path.exists() && font_path.is_file() {
-                debug_print_global(&format!("Font gefunden: {} -> {}", font_name, font_path.display()));
-                return Some(font_path);
-            }
+// QR-Elemente mit Formularfeld in Config-Koordinaten umrechnen: der Code wird zentriert und
+// quadratisch in jedes Widget-Rechteck gesetzt. Die Widgets werden ausgeblendet, damit ein
+// Feldhintergrund den Code nicht überdeckt. Ohne Formularfelder bleibt die Config unverändert.
+fn place_qr_in_form_fields(doc: &mut Document, config: &Config, qr_payloads: &[Option<String>], widgets: &[FormWidget]) -> Result<(Option<Config>, Vec<Option<String>>), String> {
+    if config.qr_codes().all(|qr| qr.form_field.is_empty()) {
+        return Ok((None, qr_payloads.to_vec()));
+    }
+    let page_numbers: std::collections::HashMap<lopdf::ObjectId, u32> = doc.get_pages().into_iter().map(|(number, id)| (id, number)).collect();
+    // Elemente neu aufbauen: QR-Elemente mit Formularfeld werden je Widget vervielfacht und
+    // behalten Name, Sichtbarkeit und Zeichenreihenfolge
+    let mut placed = Config { elements: Vec::with_capacity(config.elements.len()), ..config.clone() };
+    let mut payloads = Vec::new();
+    let mut hidden = Vec::new();
+    let mut qr_index = 0;
+    for element in &config.elements {
+        let ElementKind::Qr(qr) = &element.kind else {
+            placed.elements.push(element.clone());
+            continue;
+        };
+        let payload = qr_payloads.get(qr_index).cloned().flatten();
+        qr_index += 1;
+        if qr.form_field.is_empty() {
+            placed.elements.push(element.clone());
+            payloads.push(payload);
+            continue;
+        }
+        let targets: Vec<&FormWidget> = matching_form_widgets(widgets, &qr.form_field).collect();
+        if targets.is_empty() {
+            return Err(format!("Formularfeld '{}' nicht in der Vorlage gefunden", qr.form_field));
+        }
+        for widget in targets {
+            let Some(&page_number) = page_numbers.get(&widget.page_id) else { continue };
+            let (x, y, w, h) = PageGeometry::of_page(doc, widget.page_id).to_display_rect(widget.rect);
+            let size = w.min(h);
+            placed.elements.push(Element {
+                kind: ElementKind::Qr(QrCodeConfig {
+                    x: x + (w - size) / 2.0,
+                    y: y + (h - size) / 2.0,
+                    size,
+                    pages: vec![page_number],
+                    all_pages: false,
+                    ..qr.clone()
+                }),
+                ..element.clone()
+            });
+            payloads.push(payload.clone());
+            hidden.push(widget.widget_id);
         }
     }
-    
-    debug_print_global(&format!("Font NICHT gefunden: {} ({})", font_name, style));
-    None
+    for widget_id in hidden {
+        if let Ok(widget) = doc.get_object_mut(widget_id).and_then(Object::as_dict_mut) {
+            let flags = widget.get(b"F").and_then(Object::as_i64).unwrap_or(0);
+            widget.set("F", flags | 2); // Hidden
+        }
+    }
+    Ok((Some(placed), payloads))
 }
 
-fn modify_pdf_with_debug(template_path: &str, kundennr: &str, qr_code: &[u8], qr_width: usize, config: &Config, output_path: &std::path::Path, debug_enabled: bool) {
-    debug_print(&format!("Lade PDF-Template: {}", template_path), debug_enabled);
-    let mut doc = match Document::load(template_path) {
-        Ok(document) => {
-            debug_print("PDF-Template erfolgreich geladen", debug_enabled);
-            document
-        },
-        Err(e) => {
-            println!("ERROR: Konnte PDF-Template nicht laden: {} - {}", template_path, e);
-            return;
+// Textfeld ausfüllen: Wert (/V) setzen und je Widget ein Erscheinungsbild mit Schrift, Farbe,
+// Abständen und Ausrichtung des Vertreternummer-Elements erzeugen (vertikal zentriert)
+fn fill_text_field(doc: &mut Document, widgets: &[FormWidget], v: &VertreterConfig, text: &str, embedded: Option<&EmbeddedFont>, fallback: Option<&EmbeddedFont>) {
+    const PADDING: f32 = 2.0;
+    let font_size = v.font_size;
+    let mut fonts = lopdf::Dictionary::new();
+    let run = match embedded {
+        Some(font) => {
+            fonts.set("F1", font.font_id);
+            TextRun::embedded(text, "F1", font, font_size)
+        }
+        None => {
+            let pdf_font_name = standard_pdf_font(&v.font_name, &v.font_style);
+            fonts.set("F1", dictionary!{
+                "Type" => "Font",
+                "Subtype" => "Type1",
+                "BaseFont" => pdf_font_name,
+                "Encoding" => "WinAnsiEncoding"
+            });
+            if let Some(font) = fallback {
+                fonts.set("FU", font.font_id);
+            }
+            standard_text_run(text, "F1", pdf_font_name, font_size, fallback.map(|font| ("FU", font)))
         }
     };
-    
-    // QR-Code als XObject registrieren, falls vorhanden
-    let mut maybe_image_id: Option<lopdf::ObjectId> = None;
-    if qr_width > 0 && !qr_code.is_empty() {
-        let image_id = doc.add_object(lopdf::Object::Stream(lopdf::Stream::new(
-            dictionary! {
-                "Type" => "XObject",
-                "Subtype" => "Image",
-                "Width" => qr_width as i64,
-                "Height" => qr_width as i64,
-                "ColorSpace" => "DeviceGray",
-                "BitsPerComponent" => 8,
-            },
-            qr_code.to_vec(),
-        )));
-        maybe_image_id = Some(image_id);
-    }
-
-    // Alle Seiten des PDFs ermitteln
-    let all_pages: Vec<u32> = doc.get_pages().keys().cloned().collect();
-    debug_print(&format!("PDF hat {} Seiten: {:?}", all_pages.len(), all_pages), debug_enabled);
+    let width = spaced_text_width(v, text, run.width);
+    let [r, g, b] = v.color;
 
-    // Für jede Seite prüfen, welche Elemente darauf platziert werden sollen
-    for page_number in all_pages {
-        let page_id = doc.get_pages().get(&page_number).copied().unwrap();
-        
-        // QR-Codes für diese Seite sammeln
-        let qr_codes_for_page: Vec<&QrCodeConfig> = config.qr_codes.iter()
-            .filter(|qr| qr.all_pages || qr.pages.contains(&page_number))
-            .collect();
-            
-        // Vertreternummer-Positionen für diese Seite sammeln
-        let vertreter_for_page: Vec<&VertreterConfig> = config.vertreter.iter()
-            .filter(|v| v.all_pages || v.pages.contains(&page_number))
-            .collect();
-        
-        // Nur wenn Elemente auf dieser Seite platziert werden sollen
-        if !qr_codes_for_page.is_empty() || !vertreter_for_page.is_empty() {
-            debug_print(&format!("Bearbeite Seite {}: {} QR-Codes, {} Vertreternummern", 
-                page_number, qr_codes_for_page.len(), vertreter_for_page.len()), debug_enabled);
-                
-            process_page_elements(&mut doc, page_id, page_number, &qr_codes_for_page, &vertreter_for_page, 
-                                  kundennr, maybe_image_id, debug_enabled);
+    for field_id in widgets.iter().map(|w| w.field_id).collect::<std::collections::BTreeSet<_>>() {
+        if let Ok(field) = doc.get_object_mut(field_id).and_then(Object::as_dict_mut) {
+            field.set("V", pdf_text_string(text));
+        }
+    }
+    for widget in widgets {
+        let (w, h) = (widget.rect[2] - widget.rect[0], widget.rect[3] - widget.rect[1]);
+        let x = match v.align {
+            TextAlign::Left => PADDING,
+            TextAlign::Center => (w - width) / 2.0,
+            TextAlign::Right => w - PADDING - width,
+        };
+        let y = (h - (run.ascent - run.descent) * font_size / 1000.0) / 2.0 - run.descent * font_size / 1000.0;
+        let mut operations = vec![
+            Operation::new("BMC", vec![Object::Name(b"Tx".to_vec())]),
+            Operation::new("q", vec![]),
+            Operation::new("BT", vec![]),
+            Operation::new("rg", vec![(r as f32 / 255.0).into(), (g as f32 / 255.0).into(), (b as f32 / 255.0).into()]),
+            Operation::new("Tf", vec![Object::Name(run.font_key.clone().into_bytes()), font_size.into()]),
+            Operation::new("Tc", vec![v.char_spacing.into()]),
+            Operation::new("Tz", vec![v.horizontal_scaling.into()]),
+            Operation::new("Td", vec![x.into(), y.into()]),
+        ];
+        if run.single_byte {
+            operations.push(Operation::new("Tw", vec![v.word_spacing.into()]));
+            operations.push(Operation::new("Tj", vec![run.text.clone()]));
+        } else if let (true, Some(font)) = (v.word_spacing != 0.0, embedded.or(fallback)) {
+            operations.push(word_spaced_text(text, font, v.word_spacing, font_size));
         } else {
-            debug_print(&format!("Seite {} übersprungen - keine Elemente zu platzieren", page_number), debug_enabled);
+            operations.push(Operation::new("Tj", vec![run.text.clone()]));
+        }
+        operations.push(Operation::new("ET", vec![]));
+        operations.push(Operation::new("Q", vec![]));
+        operations.push(Operation::new("EMC", vec![]));
+        let content = Content { operations }.encode().expect("Konnte Erscheinungsbild nicht kodieren");
+        let appearance_id = doc.add_object(lopdf::Stream::new(dictionary!{
+            "Type" => "XObject",
+            "Subtype" => "Form",
+            "BBox" => vec![0.into(), 0.into(), w.into(), h.into()],
+            "Resources" => dictionary!{ "Font" => fonts.clone() },
+        }, content));
+        if let Ok(widget_dict) = doc.get_object_mut(widget.widget_id).and_then(Object::as_dict_mut) {
+            widget_dict.set("AP", dictionary!{ "N" => appearance_id });
         }
     }
-    
-    // Sicherstellen dass der Output-Ordner existiert
-    if let Some(parent) = output_path.parent() {
-        if !parent.exists() {
-            match std::fs::create_dir_all(parent) {
-                Ok(()) => debug_print(&format!("Output-Ordner erstellt: {}", parent.display()), debug_enabled),
-                Err(e) => {
-                    println!("ERROR: Konnte Output-Ordner nicht erstellen: {} - {}", parent.display(), e);
-                    return;
-                }
-            }
+
+    // Eigene Erscheinungsbilder nicht vom Viewer neu berechnen lassen; das AcroForm kann indirekt
+    // oder direkt im Katalog stehen
+    let Some(root_id) = doc.trailer.get(b"Root").and_then(Object::as_reference).ok() else { return };
+    let acroform_id = doc.get_dictionary(root_id).ok()
+        .and_then(|catalog| catalog.get(b"AcroForm").and_then(Object::as_reference).ok());
+    let acroform = match acroform_id {
+        Some(id) => doc.get_object_mut(id).and_then(Object::as_dict_mut).ok(),
+        None => doc.get_object_mut(root_id).and_then(Object::as_dict_mut).ok()
+            .and_then(|catalog| catalog.get_mut(b"AcroForm").and_then(Object::as_dict_mut).ok()),
+    };
+    if let Some(acroform) = acroform {
+        acroform.set("NeedAppearances", false);
+    }
+}
+
+// Einträge einer Seitenauswahl wie "3,1-2,4-": (erste Seite, letzte Seite; None = bis zum Ende).
+// Trennzeichen Komma oder Semikolon; absteigende Bereiche ("3-1") kehren die Reihenfolge um.
+fn page_selection_entries(spec: &str) -> Result<Vec<(u32, Option<u32>)>, String> {
+    let parse = |value: &str| -> Result<u32, String> {
+        match value.trim().parse::<u32>() {
+            Ok(n) if n >= 1 => Ok(n),
+            _ => Err(format!("ungültige Seitenzahl '{}'", value.trim())),
+        }
+    };
+    spec.split([',', ';'])
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('-') {
+            Some((from, to)) if to.trim().is_empty() => Ok((parse(from)?, None)),
+            Some((from, to)) => Ok((parse(from)?, Some(parse(to)?))),
+            None => parse(entry).map(|n| (n, Some(n))),
+        })
+        .collect()
+}
+
+// Seitenauswahl für eine Vorlage mit `page_count` Seiten in Seitennummern auflösen
+fn resolve_page_selection(spec: &str, page_count: u32) -> Result<Vec<u32>, String> {
+    let mut pages = Vec::new();
+    for (from, to) in page_selection_entries(spec)? {
+        let to = to.unwrap_or(page_count);
+        if from.max(to) > page_count {
+            return Err(format!("Seite {} gibt es nicht, die Vorlage hat {} Seiten", from.max(to), page_count));
+        }
+        if from <= to {
+            pages.extend(from..=to);
         } else {
-            debug_print(&format!("Output-Ordner existiert bereits: {}", parent.display()), debug_enabled);
+            pages.extend((to..=from).rev());
         }
     }
-    
-    // PDF in den angegebenen Pfad speichern
-    match doc.save(output_path) {
-        Ok(_file) => debug_print(&format!("PDF erfolgreich gespeichert: {}", output_path.display()), debug_enabled),
-        Err(e) => {
-            println!("ERROR: Konnte PDF nicht speichern: {} - {}", output_path.display(), e);
-            return;
-        }
+    if pages.is_empty() {
+        return Err("keine Seite ausgewählt".to_string());
     }
+    Ok(pages)
 }
 
-fn process_page_elements(doc: &mut Document, page_id: lopdf::ObjectId, _page_number: u32,
-                         qr_codes: &[&QrCodeConfig], vertreter_configs: &[&VertreterConfig], 
-                         kundennr: &str, maybe_image_id: Option<lopdf::ObjectId>, _debug_enabled: bool) {
-    
-    let content_stream = doc.get_page_content(page_id).expect("Konnte Seiteninhalt nicht laden");
-    let mut content = Content::decode(&content_stream).expect("Konnte Inhalt nicht dekodieren");
+// Seitenbaum nach der Auswahl neu aufbauen (weglassen, umsortieren, doppeln). Alle Seiten hängen
+// danach direkt am Wurzelknoten, geerbte Attribute stehen an den Seiten. Weitere Exemplare einer
+// Seite teilen Inhalt und Ressourcen; Formular-Widgets bleiben nur auf dem ersten Exemplar aktiv,
+// auf den weiteren wird ihr Erscheinungsbild in den Seiteninhalt gezeichnet.
+fn select_output_pages(doc: &mut Document, selection: &[u32]) -> Result<(), String> {
+    let pages = doc.get_pages();
+    let widgets = form_widgets(doc);
+    let pages_id = doc.trailer.get(b"Root").and_then(Object::as_reference).ok()
+        .and_then(|root| doc.get_dictionary(root).ok())
+        .and_then(|catalog| catalog.get(b"Pages").and_then(Object::as_reference).ok())
+        .ok_or_else(|| "Seitenbaum der Vorlage nicht gefunden".to_string())?;
+    let inherited_keys: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+    let mut kids = Vec::with_capacity(selection.len());
+    let mut used = std::collections::HashSet::new();
+    for &number in selection {
+        let page_id = *pages.get(&number)
+            .ok_or_else(|| format!("Seite {} gibt es nicht, die Vorlage hat {} Seiten", number, pages.len()))?;
+        let inherited: Vec<(&[u8], Object)> = inherited_keys
+            .into_iter()
+            .filter_map(|key| get_inherited_page_attr(doc, page_id, key).map(|value| (key, value)))
+            .collect();
+        let mut page = doc.get_dictionary(page_id).map_err(|e| format!("Seite {} nicht lesbar: {}", number, e))?.clone();
+        for (key, value) in inherited {
+            page.set(key.to_vec(), value);
+        }
+        page.set("Parent", pages_id);
 
-    // Alle QR-Codes platzieren
-    for (i, qr_config) in qr_codes.iter().enumerate() {
-        if let Some(_img_id) = maybe_image_id {
-            content.operations.push(Operation::new("q", vec![]));
-            content.operations.push(Operation::new("cm", vec![
-                qr_config.size.into(), 0.into(), 0.into(), qr_config.size.into(), 
-                qr_config.x.into(), qr_config.y.into()
-            ]));
-            content.operations.push(Operation::new("Do", vec![Object::Name(format!("Im{}", i + 1).into_bytes())]));
-            content.operations.push(Operation::new("Q", vec![]));
+        let duplicate = !used.insert(page_id);
+        let target = if !duplicate {
+            page_id
+        } else {
+            // Weiteres Exemplar: eigene Kopien der Annotationen (Links usw.), ohne Widgets
+            let copy_id = doc.new_object_id();
+            let annots: Vec<lopdf::Dictionary> = page.get(b"Annots").ok()
+                .and_then(|annots| resolve_object(doc, annots))
+                .and_then(|annots| annots.as_array().ok())
+                .map(|annots| annots.iter()
+                    .filter_map(|annot| resolve_object(doc, annot).and_then(|annot| annot.as_dict().ok()))
+                    .filter(|annot| annot.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"Widget".as_slice()))
+                    .cloned()
+                    .collect())
+                .unwrap_or_default();
+            let copies: Vec<Object> = annots.into_iter()
+                .map(|mut annot| {
+                    annot.set("P", copy_id);
+                    Object::Reference(doc.add_object(annot))
+                })
+                .collect();
+            if copies.is_empty() {
+                page.remove(b"Annots");
+            } else {
+                page.set("Annots", copies);
+            }
+            copy_id
+        };
+        doc.objects.insert(target, Object::Dictionary(page));
+        kids.push(Object::Reference(target));
+
+        let page_widgets: Vec<&FormWidget> = widgets.iter().filter(|w| w.page_id == page_id).collect();
+        if duplicate && !page_widgets.is_empty() {
+            let mut resources = effective_page_resources(doc, target);
+            let (content, _) = widget_appearance_content(doc, &page_widgets, &mut resources);
+            if !content.operations.is_empty() {
+                let encoded = content.encode().map_err(|e| format!("Formularfelder auf Seite {} nicht übertragbar: {}", number, e))?;
+                if let Ok(copy) = doc.get_object_mut(target).and_then(Object::as_dict_mut) {
+                    copy.set("Resources", resources);
+                }
+                append_overlay_content(doc, target, encoded);
+            }
         }
     }
 
-    // XObject und Font im Ressourcen-Dictionary der Seite eintragen (vor dem Content-Stream!)
-    let page_dict = doc.get_object_mut(page_id).expect("Konnte Seite nicht finden").as_dict_mut().expect("Konnte Seite nicht als Dict lesen");
-    
-    // Resources-Dict holen oder anlegen
-    if !page_dict.has(b"Resources") {
-        page_dict.set("Resources", dictionary!{});
+    // Formularfelder weggelassener Seiten aus dem Formular nehmen
+    let dropped: std::collections::HashSet<lopdf::ObjectId> = pages.values().filter(|id| !used.contains(id)).copied().collect();
+    if !dropped.is_empty() {
+        remove_fields_on_pages(doc, &dropped);
     }
-    let resources = page_dict.get_mut(b"Resources").expect("Konnte Resources nicht finden/anlegen");
-    let resources_dict = resources.as_dict_mut().expect("Konnte Resources nicht als Dict lesen");
-    
-    // XObject-Dict holen oder anlegen und QR-Code-Images registrieren
-    if let Some(img_id) = maybe_image_id {
-        if !qr_codes.is_empty() {
-            if !resources_dict.has(b"XObject") {
-                resources_dict.set("XObject", dictionary!{});
-            }
-            let xobjects = resources_dict.get_mut(b"XObject").expect("Konnte XObject nicht finden/anlegen");
-            let xobject_dict = xobjects.as_dict_mut().expect("Konnte XObject nicht als Dict lesen");
-            
-            for i in 0..qr_codes.len() {
-                xobject_dict.set(format!("Im{}", i + 1), img_id);
-            }
+
+    let count = kids.len() as i64;
+    let root = doc.get_object_mut(pages_id).and_then(Object::as_dict_mut).map_err(|e| format!("Seitenbaum nicht lesbar: {}", e))?;
+    root.set("Kids", kids);
+    root.set("Count", count);
+    Ok(())
+}
+
+// Felder aus /AcroForm /Fields entfernen, deren Widgets alle auf den angegebenen Seiten liegen
+fn remove_fields_on_pages(doc: &mut Document, pages: &std::collections::HashSet<lopdf::ObjectId>) {
+    fn on_pages(doc: &Document, field: &Object, pages: &std::collections::HashSet<lopdf::ObjectId>, depth: usize) -> bool {
+        let Some(dict) = resolve_object(doc, field).and_then(|f| f.as_dict().ok()) else { return false };
+        if let Ok(page) = dict.get(b"P").and_then(Object::as_reference) {
+            return pages.contains(&page);
+        }
+        let kids = dict.get(b"Kids").ok()
+            .and_then(|kids| resolve_object(doc, kids))
+            .and_then(|kids| kids.as_array().ok());
+        match kids {
+            Some(kids) if !kids.is_empty() && depth < 32 => kids.iter().all(|kid| on_pages(doc, kid, pages, depth + 1)),
+            _ => false,
         }
     }
 
-    // Font-Dict holen oder anlegen und verschiedene Fonts registrieren (nur wenn Vertreter-Positionen vorhanden)
-    if !vertreter_configs.is_empty() {
-        if !resources_dict.has(b"Font") {
-            resources_dict.set("Font", dictionary!{});
+    let Some(root) = doc.trailer.get(b"Root").and_then(Object::as_reference).ok() else { return };
+    let Some(acro_form) = doc.get_dictionary(root).ok().and_then(|catalog| catalog.get(b"AcroForm").ok()).cloned() else { return };
+    let Some(fields) = resolve_object(doc, &acro_form)
+        .and_then(|form| form.as_dict().ok())
+        .and_then(|form| form.get(b"Fields").ok())
+        .and_then(|fields| resolve_object(doc, fields))
+        .and_then(|fields| fields.as_array().ok())
+    else { return };
+    let kept: Vec<Object> = fields.iter().filter(|field| !on_pages(doc, field, pages, 0)).cloned().collect();
+
+    let form = match acro_form {
+        Object::Reference(id) => doc.get_object_mut(id).and_then(Object::as_dict_mut).ok(),
+        _ => doc.get_object_mut(root).and_then(Object::as_dict_mut).ok()
+            .and_then(|catalog| catalog.get_mut(b"AcroForm").and_then(Object::as_dict_mut).ok()),
+    };
+    if let Some(form) = form {
+        form.set("Fields", kept);
+    }
+}
+
+// Normal-Erscheinungsbilder sichtbarer Widgets als Zeichenbefehle im Benutzerraum der Seite;
+// die Formulare werden in `resources` eingetragen. Liefert Inhalt und Zahl der gezeichneten Widgets.
+fn widget_appearance_content(doc: &Document, widgets: &[&FormWidget], resources: &mut lopdf::Dictionary) -> (Content, usize) {
+    let mut content = Content { operations: Vec::new() };
+    let mut drawn = 0;
+    for widget in widgets {
+        let Ok(dict) = doc.get_dictionary(widget.widget_id) else { continue };
+        let hidden = dict.get(b"F").and_then(Object::as_i64).unwrap_or(0) & 2 != 0;
+        // Normal-Erscheinungsbild; bei Kontrollkästchen u.ä. der Zustand aus /AS
+        let normal = dict.get(b"AP").ok()
+            .and_then(|ap| resolve_object(doc, ap))
+            .and_then(|ap| ap.as_dict().ok())
+            .and_then(|ap| ap.get(b"N").ok());
+        let appearance_id = match normal {
+            Some(Object::Reference(id)) if matches!(doc.get_object(*id), Ok(Object::Stream(_))) => Some(*id),
+            Some(states) => resolve_object(doc, states)
+                .and_then(|states| states.as_dict().ok())
+                .zip(dict.get(b"AS").and_then(Object::as_name).ok())
+                .and_then(|(states, state)| states.get(state).and_then(Object::as_reference).ok()),
+            None => None,
+        };
+        if hidden {
+            continue;
         }
-        let fonts = resources_dict.get_mut(b"Font").expect("Konnte Font nicht finden/anlegen");
-        let font_dict = fonts.as_dict_mut().expect("Konnte Font nicht als Dict lesen");
-        
-        // Verschiedene Standard-Fonts definieren
-        let mut font_counter = 1;
-        let mut font_names = std::collections::HashMap::new();
-        
-        for vertreter_config in vertreter_configs {
-            // Erweiterte Font-Erkennung für mehr Schriftarten (inkl. Source Fonts)
-            let base_font = match vertreter_config.font_name.as_str() {
-                // Times Familie
-                "Times New Roman" | "Times" => "Times",
-                // Arial/Helvetica Familie
-                "Arial" | "Helvetica" => "Helvetica", 
-                // Courier Familie
-                "Courier New" | "Courier" => "Courier",
-                // Adobe/Source Fonts (falls installiert, fallback auf Helvetica)
-                name if name.contains("Adobe") || name.contains("Myriad") || name.contains("Minion") || name.contains("Source") => "Helvetica",
-                // Candara (fallback auf Helvetica)
-                "Candara" => "Helvetica",
-                // Calibri (fallback auf Helvetica)
-                "Calibri" => "Helvetica", 
-                // Weitere Windows-Fonts (fallback auf Helvetica)
-                "Verdana" | "Georgia" | "Trebuchet MS" | "Comic Sans MS" | "Impact" | "Lucida Console" | "Tahoma" => "Helvetica",
-                // Font-Namen mit Styles (extrahiere Basis-Font)
-                name if name.contains("Bold") || name.contains("Italic") || name.contains("Light") || name.contains("Medium") => {
-                    if name.contains("Times") {
-                        "Times"
-                    } else if name.contains("Arial") {
-                        "Helvetica"
-                    } else if name.contains("Courier") {
-                        "Courier"
-                    } else {
-                        "Helvetica"
-                    }
-                },
-                _ => "Helvetica" // Sicherer Fallback
-            };
-            
-            // Erweiterte Style-Zuordnung
-            let pdf_font_name = match (base_font, vertreter_config.font_style.as_str()) {
-                // Times Familie
-                ("Times", "Bold") => "Times-Bold",
-                ("Times", "Italic") => "Times-Italic", 
-                ("Times", "Bold Italic") | ("Times", "BoldItalic") => "Times-BoldItalic",
-                ("Times", _) => "Times-Roman",
-                // Helvetica Familie (Arial → Helvetica Mapping)
-                ("Helvetica", "Bold") => "Helvetica-Bold",
-                ("Helvetica", "Italic") => "Helvetica-Oblique",
-                ("Helvetica", "Bold Italic") | ("Helvetica", "BoldItalic") => "Helvetica-BoldOblique", 
-                ("Helvetica", "Light") => "Helvetica", // Light nicht verfügbar, fallback
-                ("Helvetica", "Medium") => "Helvetica-Bold", // Medium → Bold mapping
-                ("Helvetica", "Heavy") | ("Helvetica", "Black") => "Helvetica-Bold", // Heavy/Black → Bold mapping
-                ("Helvetica", "Thin") => "Helvetica", // Thin nicht verfügbar, fallback
-                ("Helvetica", _) => "Helvetica",
-                // Courier Familie
-                ("Courier", "Bold") => "Courier-Bold",
-                ("Courier", "Italic") => "Courier-Oblique",
-                ("Courier", "Bold Italic") | ("Courier", "BoldItalic") => "Courier-BoldOblique",
-                ("Courier", _) => "Courier",
-                // Sicherer Fallback
-                (_, _) => "Helvetica"
-            };
-            
-            let font_key = format!("F{}", font_counter);
-            if !font_names.contains_key(pdf_font_name) {
-                font_dict.set(font_key.as_bytes(), dictionary!{
-                    "Type" => "Font",
-                    "Subtype" => "Type1",
-                    "BaseFont" => pdf_font_name
-                });
-                font_names.insert(pdf_font_name, font_key.clone());
-                font_counter += 1;
-            }
+        let Some(appearance_id) = appearance_id else { continue };
+        // BBox des Erscheinungsbilds mit /Matrix transformieren und das umschließende Rechteck auf das
+        // Widget-Rechteck abbilden (PDF 32000-1, 12.5.5, Algorithmus 8.1); /Matrix wendet `Do` selbst an
+        let float_array = |key: &[u8]| -> Vec<f32> {
+            doc.get_object(appearance_id).and_then(Object::as_stream).ok()
+                .and_then(|stream| stream.dict.get(key).and_then(Object::as_array).ok())
+                .map(|values| values.iter().filter_map(|v| v.as_float().ok()).collect())
+                .unwrap_or_default()
+        };
+        let bbox = float_array(b"BBox");
+        if bbox.len() != 4 {
+            continue;
         }
+        let matrix = Some(float_array(b"Matrix")).filter(|m| m.len() == 6).unwrap_or_else(|| vec![1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        let corners = [(bbox[0], bbox[1]), (bbox[2], bbox[1]), (bbox[0], bbox[3]), (bbox[2], bbox[3])]
+            .map(|(x, y)| (matrix[0] * x + matrix[2] * y + matrix[4], matrix[1] * x + matrix[3] * y + matrix[5]));
+        let (x0, x1) = corners.iter().fold((f32::MAX, f32::MIN), |(lo, hi), &(x, _)| (lo.min(x), hi.max(x)));
+        let (y0, y1) = corners.iter().fold((f32::MAX, f32::MIN), |(lo, hi), &(_, y)| (lo.min(y), hi.max(y)));
+        let (bw, bh) = ((x1 - x0).max(0.001), (y1 - y0).max(0.001));
+        let (w, h) = (widget.rect[2] - widget.rect[0], widget.rect[3] - widget.rect[1]);
+        let (sx, sy) = (w / bw, h / bh);
+        let name = add_page_resource(resources, b"XObject", "Fm", appearance_id);
+        content.operations.push(Operation::new("q", vec![]));
+        content.operations.push(Operation::new("cm", vec![
+            sx.into(), 0.into(), 0.into(), sy.into(),
+            (widget.rect[0] - x0 * sx).into(), (widget.rect[1] - y0 * sy).into()
+        ]));
+        content.operations.push(Operation::new("Do", vec![Object::Name(name.into_bytes())]));
+        content.operations.push(Operation::new("Q", vec![]));
+        drawn += 1;
+    }
+    (content, drawn)
+}
 
-        // Jetzt den Content-Stream mit Text schreiben
-        for vertreter_config in vertreter_configs {
-            // NEUE LOGIK: Erst Custom TTF-Font versuchen, dann Fallback auf Standard-PDF-Fonts
-            
-            if let Some(font_path) = find_font_file(&vertreter_config.font_name, &vertreter_config.font_style) {
-                debug_print(&format!("Custom Font gefunden: {} -> {}", vertreter_config.font_name, font_path.display()), _debug_enabled);
-                
-                // Versuche TTF-Font zu laden und in PDF einzubetten
-                if let Ok(font_data) = std::fs::read(&font_path) {
-                    let _custom_font_name = format!("CustomFont_{}_{}", 
-                        vertreter_config.font_name.replace(" ", ""), 
-                        vertreter_config.font_style.replace(" ", ""));
-                    
-                    // TODO: TTF-Font-Einbettung (kompliziert in lopdf)
-                    // Für jetzt: Besseres Standard-Font-Mapping
-                    debug_print(&format!("TTF-Font-Daten geladen ({}kb), verwende verbessertes Mapping", font_data.len() / 1024), _debug_enabled);
-                } else {
-                    debug_print(&format!("Konnte TTF-Font-Datei nicht lesen: {}", font_path.display()), _debug_enabled);
-                }
+// Alle sichtbaren Widgets mit Erscheinungsbild in den Seiteninhalt zeichnen, die Widgets aus
+// den /Annots entfernen und das AcroForm löschen. Liefert die Zahl der übernommenen Widgets.
+fn flatten_form_fields(doc: &mut Document) -> usize {
+    let widgets = form_widgets(doc);
+    if widgets.is_empty() {
+        return 0;
+    }
+    let mut by_page: std::collections::BTreeMap<lopdf::ObjectId, Vec<&FormWidget>> = std::collections::BTreeMap::new();
+    for widget in &widgets {
+        by_page.entry(widget.page_id).or_default().push(widget);
+    }
+    let mut flattened = 0;
+    for (page_id, page_widgets) in by_page {
+        let mut resources = effective_page_resources(doc, page_id);
+        let (content, drawn) = widget_appearance_content(doc, &page_widgets, &mut resources);
+        flattened += drawn;
+
+        // Widgets aus den Annotationen der Seite entfernen
+        let removed: std::collections::HashSet<lopdf::ObjectId> = page_widgets.iter().map(|w| w.widget_id).collect();
+        let annots: Vec<Object> = doc.get_dictionary(page_id).ok()
+            .and_then(|page| page.get(b"Annots").ok())
+            .and_then(|annots| resolve_object(doc, annots))
+            .and_then(|annots| annots.as_array().ok())
+            .map(|annots| annots.iter().filter(|a| !a.as_reference().map(|id| removed.contains(&id)).unwrap_or(false)).cloned().collect())
+            .unwrap_or_default();
+        if let Ok(page) = doc.get_object_mut(page_id).and_then(Object::as_dict_mut) {
+            page.set("Resources", resources);
+            if annots.is_empty() {
+                page.remove(b"Annots");
             } else {
-                debug_print(&format!("Custom Font NICHT gefunden: {} ({}), verwende Standard-PDF-Font", 
-                    vertreter_config.font_name, vertreter_config.font_style), _debug_enabled);
+                page.set("Annots", annots);
             }
-            
-            // 2. Fallback: Verbessertes Standard-PDF-Font-Mapping
-            let base_font = match vertreter_config.font_name.as_str() {
-                // Times Familie - BESSERE ERKENNUNG
-                "Times New Roman" | "Times" | "TimesNewRoman" => "Times",
-                // Arial/Helvetica Familie - BESSERE ERKENNUNG  
-                "Arial" | "Helvetica" | "Arial Unicode MS" => "Helvetica",
-                // Courier Familie
-                "Courier New" | "Courier" | "CourierNew" => "Courier",
-                // Calibri -> Helvetica (ähnlichster Standard-Font)
-                "Calibri" => "Helvetica",
-                // Verdana -> Helvetica 
-                "Verdana" => "Helvetica",
-                // Georgia -> Times (Serif-Font)
-                "Georgia" => "Times",
-                // Adobe/Source Fonts -> Helvetica
-                name if name.contains("Adobe") || name.contains("Myriad") || name.contains("Minion") || name.contains("Source") => "Helvetica",
-                // Weitere Windows-Fonts
-                "Trebuchet MS" | "Comic Sans MS" | "Impact" | "Lucida Console" | "Tahoma" | "Candara" => "Helvetica",
-                // Font-Namen mit Styles (extrahiere Basis-Font)
-                name if name.contains("Bold") || name.contains("Italic") || name.contains("Light") || name.contains("Medium") => {
-                    if name.contains("Times") {
-                        "Times"
-                    } else if name.contains("Arial") {
-                        "Helvetica"
-                    } else if name.contains("Courier") {
-                        "Courier"
-                    } else {
-                        "Helvetica"
-                    }
-                },
-                _ => "Helvetica" // Sicherer Fallback
-            };
-            
-            // Erweiterte Style-Zuordnung (identisch mit oben)
-            let pdf_font_name = match (base_font, vertreter_config.font_style.as_str()) {
-                // Times Familie
-                ("Times", "Bold") => "Times-Bold",
-                ("Times", "Italic") => "Times-Italic", 
-                ("Times", "Bold Italic") | ("Times", "BoldItalic") => "Times-BoldItalic",
-                ("Times", _) => "Times-Roman",
-                // Helvetica Familie (Arial → Helvetica Mapping)
-                ("Helvetica", "Bold") => "Helvetica-Bold",
-                ("Helvetica", "Italic") => "Helvetica-Oblique",
-                ("Helvetica", "Bold Italic") | ("Helvetica", "BoldItalic") => "Helvetica-BoldOblique", 
-                ("Helvetica", "Light") => "Helvetica", // Light nicht verfügbar, fallback
-                ("Helvetica", "Medium") => "Helvetica-Bold", // Medium → Bold mapping
-                ("Helvetica", "Heavy") | ("Helvetica", "Black") => "Helvetica-Bold", // Heavy/Black → Bold mapping
-                ("Helvetica", "Thin") => "Helvetica", // Thin nicht verfügbar, fallback
-                ("Helvetica", _) => "Helvetica",
-                // Courier Familie
-                ("Courier", "Bold") => "Courier-Bold",
-                ("Courier", "Italic") => "Courier-Oblique",
-                ("Courier", "Bold Italic") | ("Courier", "BoldItalic") => "Courier-BoldOblique",
-                ("Courier", _) => "Courier",
-                // Sicherer Fallback
-                (_, _) => "Helvetica"
-            };
-            
-            let font_key = font_names.get(pdf_font_name).unwrap_or(&"F1".to_string()).clone();
-            
-            content.operations.push(Operation::new("BT", vec![]));
-            content.operations.push(Operation::new("Tf", vec![Object::Name(font_key.into_bytes()), vertreter_config.font_size.into()]));
-            content.operations.push(Operation::new("Td", vec![vertreter_config.x.into(), vertreter_config.y.into()]));
-            content.operations.push(Operation::new("Tj", vec![Object::string_literal(kundennr)]));
-            content.operations.push(Operation::new("ET", vec![]));
+        }
+        if !content.operations.is_empty() {
+            let encoded = content.encode().expect("Konnte Inhalt nicht kodieren");
+            append_overlay_content(doc, page_id, encoded);
         }
     }
 
-    let encoded_content = content.encode().expect("Konnte Inhalt nicht kodieren");
-    doc.change_page_content(page_id, encoded_content).expect("Konnte Seiteninhalt nicht ändern");
+    if let Some(catalog) = doc.trailer.get(b"Root").and_then(Object::as_reference).ok()
+        .and_then(|root| doc.get_object_mut(root).and_then(Object::as_dict_mut).ok()) {
+        catalog.remove(b"AcroForm");
+    }
+    flattened
+}
+
+// Vorlageninhalt in q/Q einklammern und das Overlay als eigenen Stream dahinter hängen:
+// Contents = [q, <Vorlage...>, Q + Overlay]. Eine in der Vorlage offen gelassene
+// Transformation, Clipping oder Farbe wirkt so nicht auf das Overlay, dessen Koordinaten
+// immer im Standard-Benutzerraum liegen.
+fn append_overlay_content(doc: &mut Document, page_id: lopdf::ObjectId, overlay: Vec<u8>) {
+    let existing: Vec<Object> = {
+        let page = doc.get_dictionary(page_id).expect("Konnte Seite nicht als Dict lesen");
+        match page.get(b"Contents") {
+            Ok(Object::Reference(id)) => match doc.get_object(*id) {
+                // Contents kann auch als Referenz auf ein Array vorliegen
+                Ok(Object::Array(streams)) => streams.clone(),
+                _ => vec![Object::Reference(*id)],
+            },
+            Ok(Object::Array(streams)) => streams.clone(),
+            _ => Vec::new(),
+        }
+    };
+
+    let save_id = doc.add_object(lopdf::Stream::new(dictionary!{}, b"q\n".to_vec()));
+    let mut restore_and_overlay = b"Q\n".to_vec();
+    restore_and_overlay.extend_from_slice(&overlay);
+    let overlay_id = doc.add_object(lopdf::Stream::new(dictionary!{}, restore_and_overlay));
+
+    let mut contents = Vec::with_capacity(existing.len() + 2);
+    contents.push(Object::Reference(save_id));
+    contents.extend(existing);
+    contents.push(Object::Reference(overlay_id));
+    doc.get_object_mut(page_id)
+        .and_then(Object::as_dict_mut)
+        .expect("Konnte Seite nicht als Dict lesen")
+        .set("Contents", contents);
+}
+
+// Sortierwerte vergleichen: numerisch, wenn beide Werte Zahlen sind, sonst als Text
+fn compare_sort_values(a: &str, b: &str) -> std::cmp::Ordering {
+    match (a.trim().replace(',', ".").parse::<f64>(), b.trim().replace(',', ".").parse::<f64>()) {
+        (Ok(x), Ok(y)) => x.partial_cmp(&y).unwrap_or(std::cmp::Ordering::Equal),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn generate_bestellscheine_resume(
     progress: Arc<Mutex<f32>>,
     stop_signal: Arc<Mutex<bool>>,
     start_from: usize,
     threads: usize,
-    vertreter: Arc<Vec<(String, String, String)>>,
+    vertreter: Arc<Vec<VertreterRecord>>,
     progress_counter: Arc<Mutex<usize>>,
     total: usize,
     data_dir: std::path::PathBuf,
@@ -5551,6 +11570,10 @@ fn generate_bestellscheine_resume(
     use_range: bool,
     range_start: usize,
     range_end: usize,
+    // Laufweite Einstellungen (Kampagne usw.)
+    run_options: RunOptions,
+    // Ergebnisse je Ausgabe (für Abschlussmeldung und Laufbericht)
+    run_report: Arc<Mutex<RunReport>>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Debug-Logging für Funktion
     if debug_mode {
@@ -5563,6 +11586,28 @@ fn generate_bestellscheine_resume(
     let progress_path = get_temp_file_path("progress.txt");
     let stop_status_path = get_temp_file_path("stop_status.txt");
     
+    // Vorlagen einmal je Lauf laden und vorbereiten; die Threads lesen sie nur
+    let run_started = std::time::Instant::now();
+    let selections = get_current_selections().unwrap_or_else(|| vec![
+        (data_dir.join("Vertreternummern.csv").to_string_lossy().to_string(),
+         templates_dir.join("Bestellschein-Endkunde-de_de.pdf").to_string_lossy().to_string(),
+         true)
+    ]);
+    debug_print_global(&format!("selections for generation (count={}): {:?}", selections.len(), selections));
+    let jobs = Arc::new(prepare_template_jobs(&selections, &language, use_custom_output, &custom_output_path, &run_options, debug_mode));
+    println!("⏱ {} Vorlage(n) vorbereitet in {} ms", jobs.len(), run_started.elapsed().as_millis());
+
+    // Existenz-Prüfung anhand der ersten Vorlage (Ausgabeordner und Dateiname einmal bestimmen)
+    let first_template = selections.first().map(|s| s.1.clone()).unwrap_or_default();
+    let existing_check = {
+        let (template_group, _template_language, template_is_messe) = infer_group_lang_from_template(&first_template);
+        let group_output_dir = get_configured_output_dir_with_debug(use_custom_output, &custom_output_path, &template_group, &language, template_is_messe, debug_mode);
+        debug_print_global(&format!("Output-Verzeichnis für Sprache '{}': {}", language, group_output_dir.display()));
+        let detected_for_check = detect_language_code(&language, Some(&first_template), None);
+        let stem = std::path::Path::new(&first_template).file_stem().unwrap_or_default().to_string_lossy().to_string();
+        (group_output_dir, format!("{}-{}", stem, detected_for_check))
+    };
+
     // PDF-Erstellung mit Threads
     let mut handles = Vec::new();
     for t in 0..threads {
@@ -5570,14 +11615,14 @@ fn generate_bestellscheine_resume(
         let progress_counter = Arc::clone(&progress_counter);
         let progress = Arc::clone(&progress);
         let stop_signal = Arc::clone(&stop_signal);
-        let data_dir = data_dir.clone();
-        let templates_dir = templates_dir.clone();
-        // Neue Parameter für Output-Konfiguration klonen
-        let use_custom_output = use_custom_output;
-        let custom_output_path = custom_output_path.clone();
+        let jobs = Arc::clone(&jobs);
+        let first_template = first_template.clone();
+        let existing_check = existing_check.clone();
         let group = group.clone();
         let language = language.clone();
         let _is_messe = is_messe;
+        let run_options = run_options.clone();
+        let run_report = Arc::clone(&run_report);
         // Pfade für Threads klonen
         let progress_path = progress_path.clone();
         let stop_status_path = stop_status_path.clone();
@@ -5607,111 +11652,97 @@ fn generate_bestellscheine_resume(
                     }
                 }
                 
-                let (kundennr, de_link, en_link) = &vertreter[i];
+                let record = &vertreter[i];
+                let kundennr = &record.nummer;
                 
-                // Wähle die richtige URL basierend auf der Sprache
-                let link = if language == "Englisch" || language.to_lowercase().contains("en") {
-                    debug_print_global(&format!("Verwende englische URL für Vertreter {}: {}", kundennr, en_link));
-                    en_link
+                // Wähle die richtige URL basierend auf der Sprache (leer = ggf. Link-Vorlage)
+                let csv_link = if language == "Englisch" || language.to_lowercase().contains("en") {
+                    debug_print_global(&format!("Verwende englische URL für Vertreter {}: {}", kundennr, record.en_link));
+                    &record.en_link
                 } else {
-                    debug_print_global(&format!("Verwende deutsche URL für Vertreter {}: {}", kundennr, de_link));
-                    de_link
+                    debug_print_global(&format!("Verwende deutsche URL für Vertreter {}: {}", kundennr, record.de_link));
+                    &record.de_link
                 };
                 
-                // Gruppenspezifischen Output-Pfad bestimmen (mit Benutzer-Konfiguration)
-                let selections = get_current_selections().unwrap_or_else(|| vec![ 
-                    (data_dir.join("Vertreternummern.csv").to_string_lossy().to_string(), 
-                     templates_dir.join("Bestellschein-Endkunde-de_de.pdf").to_string_lossy().to_string(), 
-                     true) 
-                ]);
-                let first_template = selections.get(0).map(|s| s.1.clone()).unwrap_or_default();
-                let (template_group, _template_language, template_is_messe) = infer_group_lang_from_template(&first_template);
-                let group_output_dir = get_configured_output_dir_with_debug(use_custom_output, &custom_output_path, &template_group, &language, template_is_messe, debug_mode);
-                
-                debug_print_global(&format!("Output-Verzeichnis für Sprache '{}': {}", language, group_output_dir.display()));
-                
                 // Prüfen ob PDF bereits existiert
-                // Versuche, eine Sprache zu detektieren basierend auf Auswahl/template (für Existenz-Check)
-                let detected_for_check = detect_language_code(&language, Some(&first_template), None);
-                let pdf_filename = format!("{}-{}-{}.pdf", 
-                    std::path::Path::new(&first_template)
-                        .file_stem()
-                        .unwrap_or_default()
-                        .to_string_lossy(),
-                    detected_for_check,
-                    kundennr.replace("\0", "")
-                );
-                let pdf_path = group_output_dir.join(&pdf_filename);
+                let (check_dir, check_prefix) = &existing_check;
+                let pdf_path = check_dir.join(format!("{}-{}.pdf", check_prefix, kundennr.replace("\0", "")));
                 
                 if !pdf_path.exists() {
                     println!("Erstelle PDF für Vertreter {}: {} -> {}/{}", i + 1, kundennr, group, language);
-                    // Für jede ausgewählte Template-Option erstellen (aber keine Duplikate)
-                    let selections = get_current_selections().unwrap_or_else(|| vec![ ("DATA/Vertreternummern.csv".to_string(), "VORLAGE/Bestellschein-Endkunde-de_de.pdf".to_string(), true) ]);
-                    // Debug: Liste der verwendeten Selections ausgeben, damit wir nachvollziehen können,
-                    // ob die UI-Auswahl oder der Fallback verwendet wird.
-                    debug_print_global(&format!("selections for generation (count={}): {:?}", selections.len(), selections));
-                    let mut created = Vec::new();
-                    for (csv_s, template_s, gen_qr) in selections.iter() {
-                        // Verhindere doppelte Ausgaben für dieselbe template/kundennr
-                        let out_name = format!("{}-{}", template_s, kundennr);
-                        if created.contains(&out_name) { continue; }
-                        created.push(out_name.clone());
-
-                        // Template-Pfad korrekt auflösen
-                        let resolved_template = resolve_template_path_with_debug(template_s, debug_mode);
-                        let resolved_template_str = resolved_template.to_string_lossy();
-
-                        debug_print(&format!("Verwende Template: {}", resolved_template_str), debug_mode);
-
-                        // Debug: Zeige welches template_s wir versuchen zu verwenden und ob die Datei existiert
-                        debug_print_global(&format!("template_s='{}' -> resolved='{}' (exists={})", template_s, resolved_template_str, resolved_template.exists()));
-
-                        // Prüfe ob Template existiert
-                        if !resolved_template.exists() {
-                            println!("ERROR: Template-Datei nicht gefunden: {}", resolved_template_str);
-                            continue;
-                        }
-
-                        // Bestimme kanonischen Sprachcode (de_de / en_us) anhand Template, CSV oder UI
-                        let detected_lang_code = detect_language_code(&language, Some(&resolved_template_str), Some(csv_s));
-                        let (group_name, _template_lang, tpl_is_messe) = infer_group_lang_from_template(&resolved_template_str);
-
-                        // Debug: Zeige den detektierten Sprachcode und den Pfad, wohin geschrieben werden soll
-                        let tpl_stem = resolved_template.file_stem().unwrap_or_default().to_string_lossy();
-                        let output_filename_preview = format!("{}-{}.pdf", tpl_stem, kundennr);
-                        let output_path_preview = get_configured_output_dir_with_debug(use_custom_output, &custom_output_path, &group_name, &detected_lang_code, tpl_is_messe, debug_mode).join(&output_filename_preview);
-                        debug_print_global(&format!("detected_lang_code='{}', preview_output_path='{}'", detected_lang_code, output_path_preview.display()));
-
-                        // Verwende aktuelle UI-Config falls verfügbar, sonst fallback zu group config (mit detektiertem Sprachcode)
-                        let tpl_config = {
-                            let _lock = CONFIG_MUTEX.lock().unwrap();
-                            unsafe {
-                                if let Some(ref current_config) = CURRENT_CONFIG {
-                                    println!("🎯 Verwende aktuelle UI-Config für PDF-Generierung: QR={:?}", current_config.qr_codes);
-                                    current_config.clone()
-                                } else {
-                                    println!("⚠️ Keine UI-Config verfügbar, lade Group-Config (detected lang: {})", detected_lang_code);
-                                    load_group_config(&group_name, &detected_lang_code, tpl_is_messe)
-                                }
+                    // Für jede ausgewählte (und vorbereitete) Vorlage erstellen
+                    for job in jobs.iter() {
+                        let template_s = job.template.as_str();
+                        let prepared = match &job.prepared {
+                            Ok(prepared) => prepared,
+                            Err(e) => {
+                                run_report.lock().unwrap().record(kundennr, template_s, RowStatus::Failed(e.clone()));
+                                continue;
                             }
                         };
-                        if *gen_qr {
-                            let (qr_img, qr_width) = generate_qr(link);
-                            // Output-Dateiname jetzt inklusive Sprachcode
-                            let tpl_stem = resolved_template.file_stem().unwrap_or_default().to_string_lossy();
-                            // Verwende nur Template-Stem + Kundennr als Dateiname
-                            let output_filename = format!("{}-{}.pdf", tpl_stem, kundennr);
-                            let output_path = get_configured_output_dir_with_debug(use_custom_output, &custom_output_path, &group_name, &detected_lang_code, tpl_is_messe, debug_mode).join(&output_filename);
-                            modify_pdf_with_debug(&resolved_template_str, kundennr, &qr_img, qr_width, &tpl_config, &output_path, debug_mode);
+                        let tpl_config = &job.config;
+                        // Verwende nur Template-Stem + Kundennr als Dateiname
+                        let output_path = job.output_dir.join(format!("{}-{}.pdf", job.stem, kundennr));
+                        // QR-Inhalte bestimmen: CSV-Link bzw. gefüllte Link-Vorlage oder vCard/mailto/EPC je Element
+                        let mut vars = link_template_vars(record, &job.lang_code, &job.group, &run_options);
+                        vars.insert("template".to_string(), job.stem.clone());
+                        let qr_payloads = if job.gen_qr {
+                            match build_row_qr_payloads(tpl_config, record, csv_link, &vars) {
+                                Ok(payloads) => payloads,
+                                Err(e) => {
+                                    println!("WARNUNG: QR-Inhalt für Vertreter {} konnte nicht erzeugt werden: {} - überspringe", kundennr, e);
+                                    run_report.lock().unwrap().record(kundennr, template_s, RowStatus::Failed(e));
+                                    continue;
+                                }
+                            }
                         } else {
-                            let tpl_stem = resolved_template.file_stem().unwrap_or_default().to_string_lossy();
-                            let output_filename = format!("{}-{}.pdf", tpl_stem, kundennr);
-                            let output_path = get_configured_output_dir_with_debug(use_custom_output, &custom_output_path, &group_name, &detected_lang_code, tpl_is_messe, debug_mode).join(&output_filename);
-                            modify_pdf_with_debug(&resolved_template_str, kundennr, &[], 0, &tpl_config, &output_path, debug_mode);
-                        }
+                            vec![None; tpl_config.qr_codes().count()]
+                        };
+                        debug_print_global(&format!("QR-Inhalte für Vertreter {}: {:?}", kundennr, qr_payloads));
+                        let barcode_contents = match build_row_barcode_contents(tpl_config, &vars) {
+                            Ok(contents) => contents,
+                            Err(e) => {
+                                println!("WARNUNG: Strichcode-Inhalt für Vertreter {} konnte nicht erzeugt werden: {} - überspringe", kundennr, e);
+                                run_report.lock().unwrap().record(kundennr, template_s, RowStatus::Failed(e));
+                                continue;
+                            }
+                        };
+                        let image_paths = match build_row_image_paths(tpl_config, &vars) {
+                            Ok(paths) => paths,
+                            Err(e) => {
+                                println!("WARNUNG: Bildpfad für Vertreter {} konnte nicht erzeugt werden: {} - überspringe", kundennr, e);
+                                run_report.lock().unwrap().record(kundennr, template_s, RowStatus::Failed(e));
+                                continue;
+                            }
+                        };
+                        let vertreter_texts = match build_row_vertreter_texts(tpl_config, &vars) {
+                            Ok(texts) => texts,
+                            Err(e) => {
+                                println!("WARNUNG: Vertreternummer-Text für Vertreter {} konnte nicht erzeugt werden: {} - überspringe", kundennr, e);
+                                run_report.lock().unwrap().record(kundennr, template_s, RowStatus::Failed(e));
+                                continue;
+                            }
+                        };
+                        let metadata = match build_row_metadata(tpl_config, &vars, &prepared.hash) {
+                            Ok(metadata) => metadata,
+                            Err(e) => {
+                                println!("WARNUNG: Metadaten für Vertreter {} konnten nicht erzeugt werden: {} - überspringe", kundennr, e);
+                                run_report.lock().unwrap().record(kundennr, template_s, RowStatus::Failed(e));
+                                continue;
+                            }
+                        };
+                        let status = match modify_pdf_with_debug(prepared, &vertreter_texts, &qr_payloads, &barcode_contents, &image_paths, &metadata, tpl_config, &output_path, run_options.verify_saved_qr, run_options.output_mode, debug_mode) {
+                            Ok((verification, notes)) => RowStatus::Created(verification, notes),
+                            Err(e) => {
+                                println!("ERROR: PDF für Vertreter {} fehlgeschlagen: {}", kundennr, e);
+                                RowStatus::Failed(e)
+                            }
+                        };
+                        run_report.lock().unwrap().record(kundennr, template_s, status);
                     }
                 } else {
                     println!("PDF für Vertreter {} bereits vorhanden, überspringe", kundennr);
+                    run_report.lock().unwrap().record(kundennr, &first_template, RowStatus::Skipped);
                 }
                 
                 // Progress aktualisieren
@@ -5761,6 +11792,97 @@ fn generate_bestellscheine_resume(
         }
     }
 
+    // Druckdatei und Druckbogen je Vorlage aus den vorhandenen Einzel-PDFs des Bereichs
+    if (run_options.print_file || run_options.imposition.enabled) && total > 0 && !*stop_signal.lock().unwrap() {
+        let (first, last) = if use_range {
+            (range_start.max(start_from), range_end.min(total - 1))
+        } else {
+            (start_from, total - 1)
+        };
+        let records = vertreter.get(first..=last.min(vertreter.len().saturating_sub(1))).unwrap_or(&[]);
+        let sort_column = run_options.print_file_sort_column.trim();
+        // Angeforderte Sammelausgaben; Überspringen und Fehler landen im Laufbericht
+        let outputs: Vec<&'static str> = [(run_options.print_file, "Druckdatei"), (run_options.imposition.enabled, "Druckbogen")]
+            .into_iter().filter(|(enabled, _)| *enabled).map(|(_, output)| output).collect();
+        let skip_all = |template: &str, reason: &str| {
+            let mut report = run_report.lock().unwrap();
+            for output in &outputs {
+                report.record_print_output(output, template, PrintOutputStatus::Skipped(reason.to_string()));
+            }
+        };
+        for job in jobs.iter() {
+            if job.config.encryption.enabled {
+                println!("WARNUNG: Keine Druckdatei/Druckbogen für {} - die Einzel-PDFs sind verschlüsselt", job.template);
+                skip_all(&job.template, "Einzel-PDFs sind verschlüsselt");
+                continue;
+            }
+            let mut rows: Vec<(&VertreterRecord, std::path::PathBuf)> = records.iter()
+                .map(|record| (record, job.output_dir.join(format!("{}-{}.pdf", job.stem, record.nummer))))
+                .filter(|(_, path)| path.exists())
+                .collect();
+            rows.sort_by(|(a, _), (b, _)| {
+                let key = |record: &VertreterRecord| if sort_column.is_empty() {
+                    record.nummer.clone()
+                } else {
+                    record.column(sort_column).unwrap_or_default().to_string()
+                };
+                compare_sort_values(&key(a), &key(b)).then_with(|| compare_sort_values(&a.nummer, &b.nummer))
+            });
+            let parts: Vec<(String, std::path::PathBuf)> = rows.into_iter().map(|(record, path)| (record.nummer.clone(), path)).collect();
+            if parts.is_empty() {
+                skip_all(&job.template, "keine Einzel-PDFs im Bereich");
+                continue;
+            }
+            if run_options.print_file {
+                let print_path = job.output_dir.join(format!("{}-Druckdatei.pdf", job.stem));
+                let status = match merge_print_file(&parts, &format!("{} Druckdatei", job.stem), run_options.print_file_duplex, run_options.output_mode, &print_path) {
+                    Ok(pages) => {
+                        println!("Druckdatei gespeichert: {} ({} Vertreter, {} Seiten)", print_path.display(), parts.len(), pages);
+                        PrintOutputStatus::Created(format!("{} ({} Vertreter, {} Seiten)", print_path.display(), parts.len(), pages))
+                    }
+                    Err(e) => {
+                        println!("WARNUNG: Druckdatei für {} fehlgeschlagen: {}", job.template, e);
+                        PrintOutputStatus::Failed(e)
+                    }
+                };
+                run_report.lock().unwrap().record_print_output("Druckdatei", &job.template, status);
+            }
+            if run_options.imposition.enabled {
+                let sheet_path = job.output_dir.join(format!("{}-Druckbogen.pdf", job.stem));
+                let status = match impose_print_sheets(&parts, &job.stem, &run_options.imposition, run_options.output_mode, &sheet_path) {
+                    Ok((sheets, pages)) => {
+                        println!("Druckbogen gespeichert: {} ({} Seiten auf {} Bogen)", sheet_path.display(), pages, sheets);
+                        PrintOutputStatus::Created(format!("{} ({} Seiten auf {} Bogen)", sheet_path.display(), pages, sheets))
+                    }
+                    Err(e) => {
+                        println!("WARNUNG: Ausschießen für {} fehlgeschlagen: {}", job.template, e);
+                        PrintOutputStatus::Failed(e)
+                    }
+                };
+                run_report.lock().unwrap().record_print_output("Druckbogen", &job.template, status);
+            }
+        }
+    }
+
+    // Laufbericht neben den Ausgaben ablegen
+    {
+        let report = run_report.lock().unwrap();
+        let report_dir = if use_custom_output && !custom_output_path.is_empty() {
+            std::path::PathBuf::from(&custom_output_path)
+        } else {
+            get_release_dirs().4
+        };
+        let report_path = report_dir.join(format!("Laufbericht-{}.csv", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+        match report.write_csv(&report_path) {
+            Ok(()) => println!("Laufbericht gespeichert: {}", report_path.display()),
+            Err(e) => println!("WARNUNG: Laufbericht konnte nicht gespeichert werden: {} - {}", report_path.display(), e),
+        }
+        let (created, verified, skipped, failed) = report.summary();
+        println!("Ergebnis: {} erstellt ({} mit geprüften QR-Codes), {} übersprungen, {} fehlgeschlagen", created, verified, skipped, failed);
+        let elapsed = run_started.elapsed();
+        println!("⏱ Laufzeit {:.1} s, {:.1} ms je erstellter PDF", elapsed.as_secs_f64(), elapsed.as_secs_f64() * 1000.0 / created.max(1) as f64);
+    }
+
     println!("Bestellscheine erfolgreich erstellt!");
     Ok(())
 }
@@ -5782,3 +11904,917 @@ fn main() {
         Box::new(|_cc| Box::new(MyApp::default())),
     ).unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_quote_round_trip() {
+        for value in ["", "Muster GmbH", "C:\\Logos\\\"neu\".png", "a, b = { c }", "\\\\server\\share"] {
+            let line = format!("{{ name = {}, x = 1 }}", toml_quote(value));
+            let parts = split_inline_table(&line);
+            assert_eq!(parts.len(), 2, "{}", line);
+            assert_eq!(toml_unquote(parts[0].strip_prefix("name =").unwrap()), value);
+        }
+        // Ältere Dateien ohne Maskierung
+        assert_eq!(toml_unquote("\"C:\\Logos\\neu.png\""), "C:\\Logos\\neu.png");
+    }
+
+    #[test]
+    fn element_strings_survive_save_and_load() {
+        let config = Config {
+            elements: vec![
+                Element { name: "Logo \"groß\"".to_string(), visible: true, z_order: 0,
+                    kind: ElementKind::Image(ImageConfig { path: "D:\\Bilder\\a, b.png".to_string(), ..Default::default() }) },
+                Element { name: String::new(), visible: true, z_order: 0,
+                    kind: ElementKind::Barcode(BarcodeConfig { content: "{kundennr}\"\\".to_string(), ..Default::default() }) },
+            ],
+            ..Default::default()
+        };
+        let loaded = parse_toml_to_config(&elements_toml_lines(&config));
+        assert_eq!(loaded.elements.len(), 2);
+        assert_eq!(loaded.elements[0].name, "Logo \"groß\"");
+        match (&loaded.elements[0].kind, &loaded.elements[1].kind) {
+            (ElementKind::Image(img), ElementKind::Barcode(b)) => {
+                assert_eq!(img.path, "D:\\Bilder\\a, b.png");
+                assert_eq!(b.content, "{kundennr}\"\\");
+            }
+            other => panic!("unerwartete Elemente: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn empty_element_list_stays_empty() {
+        let config = Config { elements: Vec::new(), ..Default::default() };
+        assert!(parse_toml_to_config(&elements_toml_lines(&config)).elements.is_empty());
+        // Dateien ganz ohne Elemente bekommen weiterhin die früheren Defaults
+        assert_eq!(parse_toml_to_config("# leer\n").elements.len(), 3);
+        // Eine Datei nur mit Strichcodes bekommt keine Default-QR-Codes/-Vertreternummern dazu
+        let loaded = parse_toml_to_config("[positions]\nbarcodes = [\n  { x = 10, y = 20 },\n]\n");
+        assert_eq!(loaded.elements.len(), 1);
+        assert!(matches!(loaded.elements[0].kind, ElementKind::Barcode(_)));
+    }
+
+    #[test]
+    fn payload_fields_survive_save_and_load() {
+        let mut qr = QrCodeConfig { payload: QrPayloadType::Epc, ..Default::default() };
+        qr.payload_fields.insert("name".to_string(), "Müller \"Bau\", Nord".to_string());
+        qr.payload_fields.insert("text".to_string(), "Rechnung {kundennr}\\2024".to_string());
+        let loaded = parse_qr_toml_line(&format!("{{ x = 1, y = 2{} }}", qr_optional_toml_fields(&qr)));
+        assert_eq!(loaded.payload, QrPayloadType::Epc);
+        assert_eq!(loaded.payload_fields, qr.payload_fields);
+    }
+
+    #[test]
+    fn metadata_survives_save_and_load() {
+        let mut config = Config::default();
+        config.metadata.title = "Bestellschein \"Frühjahr\", 2024".to_string();
+        config.metadata.author = "Vertrieb\\Nord".to_string();
+        config.metadata.custom.insert("kampagne".to_string(), "a, b = c".to_string());
+        config.metadata.custom.insert("Aktion \"Frühjahr\", a = b\\c".to_string(), "x".to_string());
+        config.metadata.custom.insert("mit Leerzeichen".to_string(), String::new());
+        let loaded = parse_toml_to_config(&metadata_toml_lines(&config));
+        assert_eq!(loaded.metadata, config.metadata);
+    }
+
+    #[test]
+    fn passwords_survive_save_and_load() {
+        let config = Config {
+            encryption: EncryptionConfig {
+                enabled: true,
+                owner_password: "a\"b, c = }\\".to_string(),
+                user_password: " \\\"x\" ".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let loaded = parse_toml_to_config(&encryption_toml_lines(&config));
+        assert_eq!(loaded.encryption.owner_password, config.encryption.owner_password);
+        assert_eq!(loaded.encryption.user_password, config.encryption.user_password);
+        assert_eq!(loaded.encryption.allow_print, config.encryption.allow_print);
+    }
+
+    #[test]
+    fn permission_flags_set_reserved_and_requested_bits() {
+        let none = EncryptionConfig { allow_print: false, allow_modify: false, allow_copy: false, allow_annotate: false, allow_fill_forms: false, ..Default::default() };
+        assert_eq!(none.permission_flags() as u32, 0xFFFF_F2C0);
+        let all = EncryptionConfig { allow_print: true, allow_modify: true, allow_copy: true, allow_annotate: true, allow_fill_forms: true, ..Default::default() };
+        assert_eq!(all.permission_flags() as u32, 0xFFFF_FFFC);
+        // Drucken setzt Bit 3 und 12 (hohe Qualität), Ändern Bit 4 und 11 (Seiten zusammenstellen)
+        let print = EncryptionConfig { allow_print: true, ..none.clone() };
+        assert_eq!(print.permission_flags() as u32 & !0xFFFF_F2C0, 0x804);
+        let modify = EncryptionConfig { allow_modify: true, ..none.clone() };
+        assert_eq!(modify.permission_flags() as u32 & !0xFFFF_F2C0, 0x408);
+        let fill = EncryptionConfig { allow_fill_forms: true, ..none };
+        assert_eq!(fill.permission_flags() as u32 & !0xFFFF_F2C0, 0x100);
+    }
+
+    fn single_page_document(content: &[u8]) -> Document {
+        let mut doc = Document::with_version("1.7");
+        let pages_id = doc.new_object_id();
+        let content_id = doc.add_object(lopdf::Stream::new(dictionary! {}, content.to_vec()));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page", "Parent" => pages_id, "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()], "Contents" => content_id,
+        });
+        doc.objects.insert(pages_id, Object::Dictionary(dictionary! { "Type" => "Pages", "Kids" => vec![page_id.into()], "Count" => 1 }));
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+        doc
+    }
+
+    // Ohne Benutzer-Passwort öffnet lopdf die Datei selbst (prüft U, UE und Perms) und entschlüsselt den Inhalt
+    #[test]
+    fn encrypted_document_round_trip() {
+        let content = b"BT /F1 12 Tf 72 720 Td (Kunde 4711) Tj ET".to_vec();
+        let mut doc = single_page_document(&content);
+        let encryption = EncryptionConfig { enabled: true, owner_password: "Besitzer".to_string(), ..Default::default() };
+        encrypt_document(&mut doc, &encryption).unwrap();
+        let mut saved = Vec::new();
+        doc.save_to(&mut saved).unwrap();
+        assert!(!saved.windows(content.len()).any(|w| w == content.as_slice()));
+
+        let loaded = Document::load_mem(&saved).unwrap();
+        assert!(loaded.authenticate_owner_password("Besitzer").is_ok());
+        let page = *loaded.get_pages().get(&1).unwrap();
+        assert_eq!(loaded.get_page_content(page).unwrap(), content);
+    }
+
+    // Mit Benutzer-Passwort: V5/R6 mit AESV3, /P aus der Config, Metadaten bleiben lesbar
+    #[test]
+    fn encrypted_document_decrypts_with_user_password() {
+        let content = b"BT /F1 12 Tf 72 720 Td (Kunde 4711) Tj ET".to_vec();
+        let xmp = b"<x:xmpmeta>Bestellschein</x:xmpmeta>".to_vec();
+        let mut doc = single_page_document(&content);
+        let metadata_id = doc.add_object(lopdf::Stream::new(dictionary! { "Type" => "Metadata", "Subtype" => "XML" }, xmp.clone()));
+        let catalog_id = doc.trailer.get(b"Root").and_then(Object::as_reference).unwrap();
+        doc.get_object_mut(catalog_id).and_then(Object::as_dict_mut).unwrap().set("Metadata", metadata_id);
+        let encryption = EncryptionConfig { enabled: true, owner_password: "Besitzer".to_string(), user_password: "Kunde".to_string(), allow_copy: false, ..Default::default() };
+        encrypt_document(&mut doc, &encryption).unwrap();
+        let mut saved = Vec::new();
+        doc.save_to(&mut saved).unwrap();
+        assert!(!saved.windows(content.len()).any(|w| w == content.as_slice()));
+        assert!(saved.windows(xmp.len()).any(|w| w == xmp.as_slice()));
+
+        // lopdf lädt Dateien mit Benutzer-Passwort nur bis zum Verschlüsselungs-Dictionary
+        let loaded = Document::load_mem(&saved).unwrap();
+        let dict = loaded.get_encrypted().unwrap().clone();
+        assert_eq!((dict.get(b"V").and_then(Object::as_i64).unwrap(), dict.get(b"R").and_then(Object::as_i64).unwrap()), (5, 6));
+        assert_eq!(dict.get(b"P").and_then(Object::as_i64).unwrap() as i32, encryption.permission_flags());
+        assert!(!dict.get(b"EncryptMetadata").and_then(Object::as_bool).unwrap());
+        let cfm = dict.get(b"CF").and_then(Object::as_dict).and_then(|cf| cf.get(b"StdCF")).and_then(Object::as_dict)
+            .and_then(|f| f.get(b"CFM")).and_then(Object::as_name).unwrap();
+        assert_eq!(cfm, b"AESV3");
+        assert!(loaded.authenticate_user_password("Kunde").is_ok());
+        assert!(loaded.authenticate_owner_password("Besitzer").is_ok());
+        assert!(loaded.authenticate_password("falsch").is_err());
+
+        doc.decrypt("Kunde").unwrap();
+        let page = *doc.get_pages().get(&1).unwrap();
+        assert_eq!(doc.get_page_content(page).unwrap(), content);
+    }
+
+    // Seite 100 × 200 pt mit /Rotate und einem Textfeld (Widget mit Erscheinungsbild)
+    fn rotated_form_document(rotate: i64) -> Document {
+        let mut doc = single_page_document(b"0 0 m 100 200 l S");
+        let page_id = *doc.get_pages().get(&1).unwrap();
+        let appearance_id = doc.add_object(lopdf::Stream::new(dictionary! {
+            "Type" => "XObject", "Subtype" => "Form", "BBox" => vec![0.into(), 0.into(), 40.into(), 20.into()],
+        }, b"/Tx BMC BT (4711) Tj ET EMC".to_vec()));
+        let widget_id = doc.add_object(dictionary! {
+            "Type" => "Annot", "Subtype" => "Widget", "FT" => "Tx", "T" => Object::string_literal("kundennr"),
+            "Rect" => vec![10.into(), 10.into(), 50.into(), 30.into()], "P" => page_id,
+            "AP" => dictionary! { "N" => appearance_id },
+        });
+        let page = doc.get_object_mut(page_id).and_then(Object::as_dict_mut).unwrap();
+        page.set("MediaBox", vec![0.into(), 0.into(), 100.into(), 200.into()]);
+        page.set("Rotate", rotate);
+        page.set("Annots", vec![widget_id.into()]);
+        let catalog_id = doc.trailer.get(b"Root").and_then(Object::as_reference).unwrap();
+        doc.get_object_mut(catalog_id).and_then(Object::as_dict_mut).unwrap()
+            .set("AcroForm", dictionary! { "Fields" => vec![widget_id.into()] });
+        doc
+    }
+
+    #[test]
+    fn imposition_places_rotated_pages_and_keeps_fields() {
+        let dir = std::env::temp_dir().join(format!("bestellschein-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let part_path = dir.join("gedreht.pdf");
+        rotated_form_document(90).save(&part_path).unwrap();
+        let sheet_path = dir.join("bogen.pdf");
+        // Bogen, auf den genau ein Nutzen im Querformat passt
+        let options = ImpositionOptions { enabled: true, sheet_width_mm: 80.0, sheet_height_mm: 40.0, bleed_mm: 0.0, crop_marks: false, slug_line: false, ..Default::default() };
+        let result = impose_print_sheets(&[("A".to_string(), part_path)], "Test", &options, OutputMode::Fast, &sheet_path);
+        let sheets = Document::load(&sheet_path);
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(result.unwrap(), (1, 1));
+        let sheets = sheets.unwrap();
+
+        // Die Seite liegt quer (200 × 100): Benutzerraum um 90 Grad im Uhrzeigersinn gedreht
+        let sheet_id = *sheets.get_pages().get(&1).unwrap();
+        let content = Content::decode(&sheets.get_page_content(sheet_id).unwrap()).unwrap();
+        let cm = content.operations.iter().find(|op| op.operator == "cm").unwrap();
+        let m: Vec<f32> = cm.operands.iter().map(|o| o.as_float().unwrap()).collect();
+        assert_eq!(&m[..4], &[0.0, -1.0, 1.0, 0.0]);
+        // Ecke (0,0) landet links oben, (100,200) rechts unten; das Querformat steht mittig auf dem Bogen
+        let sheet = page_box(&sheets, sheet_id, &[b"MediaBox"]).unwrap();
+        let (left, top) = (m[4], m[5]);
+        assert!((left - (sheet[2] - 200.0) / 2.0).abs() < 0.01, "{:?}", m);
+        assert!((top - (sheet[3] + 100.0) / 2.0).abs() < 0.01, "{:?}", m);
+
+        // Das Feld steckt als gezeichnetes Erscheinungsbild im Form-XObject der Seite
+        let xobjects = sheets.get_dictionary(sheet_id).unwrap().get(b"Resources").and_then(Object::as_dict).unwrap()
+            .get(b"XObject").and_then(Object::as_dict).unwrap();
+        let form_id = xobjects.get(b"N1").and_then(Object::as_reference).unwrap();
+        let mut form = sheets.get_object(form_id).and_then(Object::as_stream).unwrap().clone();
+        let _ = form.decompress();
+        let form_xobjects = form.dict.get(b"Resources").and_then(Object::as_dict).unwrap()
+            .get(b"XObject").and_then(Object::as_dict).unwrap();
+        let operations = Content::decode(&form.content).unwrap().operations;
+        let drawn = operations.iter().filter(|op| op.operator == "Do").filter_map(|op| op.operands[0].as_name().ok()).collect::<Vec<_>>();
+        assert_eq!(drawn.len(), 1);
+        assert!(form_xobjects.has(drawn[0]));
+    }
+
+    #[test]
+    fn flattened_appearance_honours_form_matrix() {
+        // Um 90 Grad gedrehtes Erscheinungsbild: BBox 20 × 40 ergibt nach /Matrix 40 × 20 (x von -40 bis 0)
+        let mut doc = rotated_form_document(0);
+        let widgets = form_widgets(&doc);
+        let appearance_id = doc.get_dictionary(widgets[0].widget_id).unwrap().get(b"AP").and_then(Object::as_dict).unwrap()
+            .get(b"N").and_then(Object::as_reference).unwrap();
+        let appearance = doc.get_object_mut(appearance_id).and_then(Object::as_stream_mut).unwrap();
+        appearance.dict.set("BBox", vec![0.into(), 0.into(), 20.into(), 40.into()]);
+        appearance.dict.set("Matrix", vec![0.into(), 1.into(), (-1).into(), 0.into(), 0.into(), 0.into()]);
+
+        let mut resources = lopdf::Dictionary::new();
+        let widget_refs: Vec<&FormWidget> = widgets.iter().collect();
+        let (content, drawn) = widget_appearance_content(&doc, &widget_refs, &mut resources);
+        assert_eq!(drawn, 1);
+        let cm = content.operations.iter().find(|op| op.operator == "cm").unwrap();
+        let m: Vec<f32> = cm.operands.iter().map(|o| o.as_float().unwrap()).collect();
+        // Rect [10 10 50 30]: ohne Verzerrung, nur um die transformierte BBox verschoben
+        assert_eq!(m, vec![1.0, 0.0, 0.0, 1.0, 50.0, 10.0]);
+    }
+
+    #[test]
+    fn fill_text_field_disables_need_appearances_in_direct_acroform() {
+        // rotated_form_document legt das AcroForm direkt im Katalog an
+        let mut doc = rotated_form_document(0);
+        let catalog_id = doc.trailer.get(b"Root").and_then(Object::as_reference).unwrap();
+        doc.get_object_mut(catalog_id).and_then(Object::as_dict_mut).unwrap()
+            .get_mut(b"AcroForm").and_then(Object::as_dict_mut).unwrap()
+            .set("NeedAppearances", true);
+        let widgets = form_widgets(&doc);
+        let v = VertreterConfig { word_spacing: 3.0, ..Default::default() };
+        fill_text_field(&mut doc, &widgets, &v, "12 34", None, None);
+
+        let acroform = doc.get_dictionary(catalog_id).unwrap().get(b"AcroForm").and_then(Object::as_dict).unwrap();
+        assert!(!acroform.get(b"NeedAppearances").and_then(Object::as_bool).unwrap());
+        let appearance_id = doc.get_dictionary(widgets[0].widget_id).unwrap().get(b"AP").and_then(Object::as_dict).unwrap()
+            .get(b"N").and_then(Object::as_reference).unwrap();
+        let appearance = doc.get_object(appearance_id).and_then(Object::as_stream).unwrap();
+        let operations = Content::decode(&appearance.content).unwrap().operations;
+        let tw = operations.iter().find(|op| op.operator == "Tw").unwrap();
+        assert_eq!(tw.operands[0].as_float().unwrap(), 3.0);
+    }
+
+    #[test]
+    fn page_selection_entries_parse_ranges() {
+        assert_eq!(page_selection_entries("1, 3-4; 4-").unwrap(), vec![(1, Some(1)), (3, Some(4)), (4, None)]);
+        assert_eq!(page_selection_entries("3-1").unwrap(), vec![(3, Some(1))]);
+        assert_eq!(page_selection_entries("").unwrap(), vec![]);
+        assert_eq!(page_selection_entries(" ;, ").unwrap(), vec![]);
+        assert!(page_selection_entries("0").unwrap_err().contains("'0'"));
+        assert!(page_selection_entries("1-0").is_err());
+        assert!(page_selection_entries("a-2").is_err());
+    }
+
+    #[test]
+    fn resolve_page_selection_expands_against_page_count() {
+        assert_eq!(resolve_page_selection("4-", 6).unwrap(), vec![4, 5, 6]);
+        assert_eq!(resolve_page_selection("3-1", 6).unwrap(), vec![3, 2, 1]);
+        assert_eq!(resolve_page_selection("1,1,2", 2).unwrap(), vec![1, 1, 2]);
+        assert!(resolve_page_selection("0", 6).is_err());
+        assert!(resolve_page_selection("7", 6).unwrap_err().contains("6 Seiten"));
+        assert!(resolve_page_selection("5-", 4).is_err());
+        assert!(resolve_page_selection("2-9", 4).is_err());
+        assert!(resolve_page_selection("", 4).unwrap_err().contains("keine Seite"));
+    }
+
+    #[test]
+    fn duplicated_pages_keep_field_appearances() {
+        let mut doc = rotated_form_document(0);
+        select_output_pages(&mut doc, &[1, 1]).unwrap();
+        let pages = doc.get_pages();
+        assert_eq!(pages.len(), 2);
+        let (first, copy) = (pages[&1], pages[&2]);
+        assert_ne!(first, copy);
+        // Das erste Exemplar behält das Widget, die Kopie zeichnet dessen Erscheinungsbild
+        assert!(doc.get_dictionary(first).unwrap().has(b"Annots"));
+        assert!(!doc.get_dictionary(copy).unwrap().has(b"Annots"));
+        let operations = Content::decode(&doc.get_page_content(copy).unwrap()).unwrap().operations;
+        let drawn: Vec<&[u8]> = operations.iter().filter(|op| op.operator == "Do").filter_map(|op| op.operands[0].as_name().ok()).collect();
+        assert_eq!(drawn.len(), 1);
+        let resources = effective_page_resources(&doc, copy);
+        assert!(resources.get(b"XObject").and_then(Object::as_dict).unwrap().has(drawn[0]));
+        // Das Original bleibt unverändert
+        assert!(!Content::decode(&doc.get_page_content(first).unwrap()).unwrap().operations.iter().any(|op| op.operator == "Do"));
+    }
+
+    #[test]
+    fn logo_path_survives_save_and_load() {
+        let qr = QrCodeConfig { logo_path: "\\\\server\\Logos\\\"Firma\", Nord.png".to_string(), ..Default::default() };
+        let loaded = parse_qr_toml_line(&format!("{{ x = 1, y = 2{} }}", qr_optional_toml_fields(&qr)));
+        assert_eq!(loaded.logo_path, qr.logo_path);
+        assert_eq!(loaded.logo_size, qr.logo_size);
+    }
+
+    fn fields(pairs: &[(&str, &str)]) -> std::collections::BTreeMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn fill_payload_fields_fills_trims_and_drops_empty() {
+        let mut qr = QrCodeConfig { payload: QrPayloadType::VCard, ..Default::default() };
+        qr.payload_fields.insert("Name".to_string(), " {vertreter} ".to_string());
+        qr.payload_fields.insert("url".to_string(), "https://example.com/?a=1&k={kundennr}".to_string());
+        qr.payload_fields.insert("note".to_string(), "{leer}".to_string());
+        let vars: std::collections::HashMap<String, String> = [("vertreter", "Hans Müller"), ("kundennr", "4711"), ("leer", "")]
+            .iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let filled = fill_payload_fields(&qr, &vars).unwrap();
+        assert_eq!(filled, fields(&[("name", "Hans Müller"), ("url", "https://example.com/?a=1&k=4711")]));
+
+        qr.payload_fields.insert("org".to_string(), "{firma}".to_string());
+        assert!(fill_payload_fields(&qr, &vars).unwrap_err().contains("{firma}"));
+    }
+
+    #[test]
+    fn vcard_and_mecard_escape_special_characters() {
+        let contact = fields(&[("name", "Hans Müller"), ("org", "A;B, C\\D"), ("note", "Zeile 1\nZeile 2")]);
+        let vcard = build_vcard(&contact).unwrap();
+        assert!(vcard.contains("\r\nN:Müller;Hans;;;\r\n"), "{}", vcard);
+        assert!(vcard.contains("\r\nORG:A\\;B\\, C\\\\D\r\n"), "{}", vcard);
+        assert!(vcard.contains("\r\nNOTE:Zeile 1\\nZeile 2\r\n"), "{}", vcard);
+        assert!(build_vcard(&fields(&[("org", "Firma")])).is_err());
+
+        let mecard = build_mecard(&fields(&[("name", "Hans Müller"), ("note", "a:b;c,\"d\"")])).unwrap();
+        assert_eq!(mecard, "MECARD:N:Müller,Hans;NOTE:a\\:b\\;c\\,\\\"d\\\";;");
+    }
+
+    #[test]
+    fn build_epc_checks_iban_bic_and_size() {
+        let base = [("name", "Muster GmbH"), ("iban", "de89 3704 0044 0532 0130 00"), ("bic", "COBADEFF"), ("amount", "12,50"), ("text", "Rechnung 4711")];
+        assert_eq!(build_epc(&fields(&base)).unwrap(),
+            "BCD\n002\n1\nSCT\nCOBADEFF\nMuster GmbH\nDE89370400440532013000\nEUR12.50\n\n\nRechnung 4711");
+
+        let with = |key: &str, value: &str| {
+            let mut f = fields(&base);
+            f.insert(key.to_string(), value.to_string());
+            build_epc(&f)
+        };
+        assert!(with("iban", "DE89-3704").unwrap_err().contains("IBAN"));
+        assert!(with("iban", "DE12").unwrap_err().contains("IBAN"));
+        assert!(with("bic", "COBADE").unwrap_err().contains("BIC"));
+        assert!(with("bic", "COBADEFFXXX").is_ok());
+        assert!(with("amount", "0").is_err());
+        assert!(with("name", "  ").unwrap_err().contains("Empfängernamen"));
+        assert!(with("text", " Rechnung 4711 ").unwrap().ends_with("\nRechnung 4711"));
+
+        // Strukturierte Referenz nur als gültige RF-Referenz (ISO 11649), freier Text wird abgelehnt
+        let with_reference = |reference: &str| {
+            let mut f = fields(&base);
+            f.remove("text");
+            f.insert("reference".to_string(), reference.to_string());
+            build_epc(&f)
+        };
+        assert!(with_reference("RF18 5390 0754 7034").unwrap().ends_with("\nRF18539007547034"));
+        assert!(with_reference("RF19 5390 0754 7034").unwrap_err().contains("RF-Referenz"));
+        assert!(with_reference("Vorkasse 4711").unwrap_err().contains("RF-Referenz"));
+
+        // Felder innerhalb ihrer Zeichengrenzen, zusammen aber über 331 Bytes (UTF-8)
+        let name = "ä".repeat(70);
+        let text = "ü".repeat(140);
+        let mut f = fields(&base);
+        f.insert("name".to_string(), name);
+        f.insert("text".to_string(), text);
+        assert!(build_epc(&f).unwrap_err().contains("331 Bytes"));
+    }
+
+    #[test]
+    fn build_qr_payload_link_requires_link() {
+        let qr = QrCodeConfig::default();
+        let vars = std::collections::HashMap::new();
+        assert_eq!(build_qr_payload(&qr, Some("https://example.com"), &vars).unwrap(), "https://example.com");
+        assert!(build_qr_payload(&qr, None, &vars).is_err());
+    }
+
+    #[test]
+    fn generate_qr_rejects_oversized_payload() {
+        assert!(generate_qr("https://example.com/?k=12345").is_ok());
+        let err = generate_qr(&"x".repeat(8000)).unwrap_err();
+        assert!(err.contains("8000 Bytes"), "{}", err);
+    }
+
+    // Zwei gleiche Bilder und eine Schriftkette (Datei -> Deskriptor -> Font), die erst über
+    // mehrere Durchgänge gleich wird; Dict-Einträge in unterschiedlicher Reihenfolge
+    #[test]
+    fn optimize_pdf_merges_duplicates_and_remaps_references() {
+        let mut doc = single_page_document(b"q /Im1 Do Q q /Im2 Do Q BT /F1 12 Tf /F2 12 Tf ET");
+        let page_id = *doc.get_pages().get(&1).unwrap();
+        let image = || lopdf::Stream::new(dictionary! {
+            "Type" => "XObject", "Subtype" => "Image", "Width" => 1, "Height" => 1, "ColorSpace" => "DeviceGray", "BitsPerComponent" => 8,
+        }, vec![0x80]);
+        let (image1, image2) = (doc.add_object(image()), doc.add_object(image()));
+        let font_chain = |doc: &mut Document, reversed: bool| {
+            let file = doc.add_object(lopdf::Stream::new(dictionary! { "Length1" => 4 }, b"glyf".to_vec()));
+            let mut descriptor = lopdf::Dictionary::new();
+            let mut font = lopdf::Dictionary::new();
+            let mut descriptor_entries: Vec<(&str, Object)> = vec![("Type", "FontDescriptor".into()), ("FontName", "ABCDEF+Test".into()), ("FontFile2", file.into())];
+            let mut font_entries: Vec<(&str, Object)> = vec![("Type", "Font".into()), ("Subtype", "TrueType".into()), ("BaseFont", "ABCDEF+Test".into())];
+            if reversed {
+                descriptor_entries.reverse();
+                font_entries.reverse();
+            }
+            descriptor_entries.into_iter().for_each(|(k, v)| descriptor.set(k, v));
+            let descriptor_id = doc.add_object(descriptor);
+            font_entries.push(("FontDescriptor", descriptor_id.into()));
+            font_entries.into_iter().for_each(|(k, v)| font.set(k, v));
+            (file, descriptor_id, doc.add_object(font))
+        };
+        let (file1, descriptor1, font1) = font_chain(&mut doc, false);
+        let (file2, descriptor2, font2) = font_chain(&mut doc, true);
+        doc.get_object_mut(page_id).and_then(Object::as_dict_mut).unwrap().set("Resources", dictionary! {
+            "XObject" => dictionary! { "Im1" => image1, "Im2" => image2 },
+            "Font" => dictionary! { "F1" => font1, "F2" => font2 },
+        });
+        // Verweis aus dem Trailer auf ein Duplikat
+        doc.trailer.set("Test", vec![Object::Reference(image2)]);
+
+        let merged = optimize_pdf(&mut doc);
+        let expected: std::collections::HashMap<_, _> = [(image2, image1), (file2, file1), (descriptor2, descriptor1), (font2, font1)].into_iter().collect();
+        assert_eq!(merged, expected);
+        for (duplicate, canonical) in &merged {
+            assert!(!doc.objects.contains_key(duplicate), "{:?} nicht entfernt", duplicate);
+            assert!(doc.objects.contains_key(canonical), "{:?} fehlt", canonical);
+        }
+
+        let resources = doc.get_dictionary(page_id).and_then(|page| page.get(b"Resources")).and_then(Object::as_dict).unwrap();
+        let reference = |kind: &[u8], name: &[u8]| resources.get(kind).and_then(Object::as_dict).and_then(|d| d.get(name)).and_then(Object::as_reference).unwrap();
+        assert_eq!((reference(b"XObject", b"Im1"), reference(b"XObject", b"Im2")), (image1, image1));
+        assert_eq!((reference(b"Font", b"F1"), reference(b"Font", b"F2")), (font1, font1));
+        let descriptor = doc.get_dictionary(font1).and_then(|f| f.get(b"FontDescriptor")).and_then(Object::as_reference).unwrap();
+        assert_eq!(descriptor, descriptor1);
+        assert_eq!(doc.trailer.get(b"Test").and_then(Object::as_array).unwrap()[0].as_reference().unwrap(), image1);
+    }
+
+    #[test]
+    fn srgb_icc_profile_header_and_tags() {
+        let profile = srgb_icc_profile();
+        let u32_at = |at: usize| u32::from_be_bytes(profile[at..at + 4].try_into().unwrap()) as usize;
+        assert_eq!(u32_at(0), profile.len());
+        assert_eq!(&profile[12..20], b"mntrRGB ");
+        assert_eq!(&profile[36..40], b"acsp");
+        let tag_count = u32_at(128);
+        assert_eq!(tag_count, 9);
+        for i in 0..tag_count {
+            let entry = 132 + i * 12;
+            let (offset, size) = (u32_at(entry + 4), u32_at(entry + 8));
+            assert!(offset >= 132 + tag_count * 12 && offset + size <= profile.len(), "Tag {} außerhalb", i);
+            assert_eq!(offset % 4, 0);
+        }
+    }
+
+    fn srgb_intent() -> OutputIntentProfile {
+        OutputIntentProfile { name: "sRGB IEC61966-2.1".to_string(), data: srgb_icc_profile(), components: 3 }
+    }
+
+    #[test]
+    fn pdfa_reports_non_embedded_type1_font() {
+        let mut doc = single_page_document(b"BT /F1 12 Tf (4711) Tj ET");
+        let page_id = *doc.get_pages().get(&1).unwrap();
+        let font_id = doc.add_object(dictionary! { "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Helvetica" });
+        doc.get_object_mut(page_id).and_then(Object::as_dict_mut).unwrap()
+            .set("Resources", dictionary! { "Font" => dictionary! { "F1" => font_id } });
+        let intent = add_output_intent(&mut doc, &srgb_intent());
+        assert!(intent.default_rgb.is_none());
+        let issues = make_pdfa_conformant(&mut doc, intent, "test");
+        assert!(issues.contains(&"Schrift Helvetica nicht eingebettet".to_string()), "{:?}", issues);
+    }
+
+    #[test]
+    fn pdfa_removes_additional_and_javascript_actions() {
+        let mut doc = single_page_document(b"");
+        let page_id = *doc.get_pages().get(&1).unwrap();
+        let script = doc.add_object(dictionary! { "S" => "JavaScript", "JS" => Object::string_literal("app.alert(1)") });
+        doc.get_object_mut(page_id).and_then(Object::as_dict_mut).unwrap().set("AA", dictionary! { "O" => script });
+        let catalog_id = doc.trailer.get(b"Root").and_then(Object::as_reference).unwrap();
+        doc.get_object_mut(catalog_id).and_then(Object::as_dict_mut).unwrap().set("OpenAction", script);
+        let intent = add_output_intent(&mut doc, &srgb_intent());
+        let issues = make_pdfa_conformant(&mut doc, intent, "test");
+        assert!(issues.is_empty(), "{:?}", issues);
+        assert!(!doc.get_dictionary(page_id).unwrap().has(b"AA"));
+        assert!(!doc.get_dictionary(catalog_id).unwrap().has(b"OpenAction"));
+        assert!(!doc.objects.contains_key(&script));
+    }
+
+    #[test]
+    fn pdfa_sets_default_rgb_for_cmyk_intent() {
+        let mut doc = single_page_document(b"1 0 0 rg 0 0 10 10 re f");
+        let page_id = *doc.get_pages().get(&1).unwrap();
+        let cmyk = OutputIntentProfile { name: "Coated FOGRA39".to_string(), data: vec![0; 128], components: 4 };
+        let intent = add_output_intent(&mut doc, &cmyk);
+        let default_rgb = intent.default_rgb.expect("CMYK-Ausgabebedingung braucht DefaultRGB");
+        let issues = make_pdfa_conformant(&mut doc, intent, "test");
+        assert!(issues.is_empty(), "{:?}", issues);
+        let spaces = doc.get_dictionary(page_id).and_then(|page| page.get(b"Resources")).and_then(Object::as_dict)
+            .and_then(|resources| resources.get(b"ColorSpace")).and_then(Object::as_dict).unwrap();
+        assert_eq!(spaces.get(b"DefaultRGB").and_then(Object::as_reference).unwrap(), default_rgb);
+        let space = doc.get_object(default_rgb).and_then(Object::as_array).unwrap();
+        assert_eq!(space[0].as_name().unwrap(), b"ICCBased");
+    }
+
+    #[test]
+    fn merge_print_file_pads_duplex_and_labels_sections() {
+        let dir = std::env::temp_dir().join(format!("bestellschein-druck-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let one_page = dir.join("a.pdf");
+        single_page_document(b"").save(&one_page).unwrap();
+        let two_pages = dir.join("b.pdf");
+        let mut doc = single_page_document(b"");
+        let pages_id = doc.catalog().and_then(|catalog| catalog.get(b"Pages")).and_then(Object::as_reference).unwrap();
+        let second = doc.add_object(dictionary! { "Type" => "Page", "Parent" => pages_id, "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()] });
+        let pages = doc.get_object_mut(pages_id).and_then(Object::as_dict_mut).unwrap();
+        pages.get_mut(b"Kids").and_then(Object::as_array_mut).unwrap().push(second.into());
+        pages.set("Count", 2);
+        doc.save(&two_pages).unwrap();
+        let merged_path = dir.join("druck.pdf");
+        let parts = [("Vertreter A".to_string(), one_page), ("Vertreter B".to_string(), two_pages)];
+        let result = merge_print_file(&parts, "Druck", true, OutputMode::Fast, &merged_path);
+        let merged = Document::load(&merged_path);
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(result.unwrap(), 4);
+        let merged = merged.unwrap();
+
+        // Leerseite nach dem einseitigen ersten Teil: ohne Inhalt
+        let pages = merged.get_pages();
+        assert_eq!(pages.len(), 4);
+        assert!(!merged.get_dictionary(pages[&2]).unwrap().has(b"Contents"));
+        assert!(merged.get_dictionary(pages[&1]).unwrap().has(b"Contents"));
+        assert!(merged.get_dictionary(pages[&3]).unwrap().has(b"Contents"));
+
+        let catalog = merged.catalog().unwrap();
+        let nums = catalog.get(b"PageLabels").and_then(Object::as_dict).and_then(|labels| labels.get(b"Nums")).and_then(Object::as_array).unwrap();
+        let starts: Vec<i64> = nums.iter().step_by(2).map(|n| n.as_i64().unwrap()).collect();
+        assert_eq!(starts, vec![0, 2]);
+
+        let outlines = catalog.get(b"Outlines").and_then(Object::as_reference).and_then(|id| merged.get_dictionary(id)).unwrap();
+        assert_eq!(outlines.get(b"Count").and_then(Object::as_i64).unwrap(), 2);
+        let first_id = outlines.get(b"First").and_then(Object::as_reference).unwrap();
+        let last_id = outlines.get(b"Last").and_then(Object::as_reference).unwrap();
+        let (first, last) = (merged.get_dictionary(first_id).unwrap(), merged.get_dictionary(last_id).unwrap());
+        assert_eq!(first.get(b"Next").and_then(Object::as_reference).unwrap(), last_id);
+        assert_eq!(last.get(b"Prev").and_then(Object::as_reference).unwrap(), first_id);
+        assert!(!first.has(b"Prev") && !last.has(b"Next"));
+        let dest_page = |item: &lopdf::Dictionary| item.get(b"Dest").and_then(Object::as_array).unwrap()[0].as_reference().unwrap();
+        assert_eq!((dest_page(first), dest_page(last)), (pages[&1], pages[&3]));
+    }
+
+    #[test]
+    fn compare_sort_values_numeric_then_text() {
+        use std::cmp::Ordering;
+        assert_eq!(compare_sort_values("9", "10"), Ordering::Less);
+        assert_eq!(compare_sort_values(" 2,5", "2.50"), Ordering::Equal);
+        assert_eq!(compare_sort_values("Berlin", "aachen"), Ordering::Greater);
+        assert_eq!(compare_sort_values("Köln", "KÖLN"), Ordering::Equal);
+        // Gemischt: kein Zahlenvergleich, sondern Text ohne Groß-/Kleinschreibung
+        assert_eq!(compare_sort_values("10", "9a"), Ordering::Less);
+    }
+
+    #[test]
+    fn encode_win_ansi_maps_latin1_and_cp1252() {
+        assert_eq!(encode_win_ansi("äß€"), Some(vec![0xE4, 0xDF, 0x80]));
+        assert_eq!(encode_win_ansi("Nr. 4711"), Some(b"Nr. 4711".to_vec()));
+        assert_eq!(encode_win_ansi("Łódź"), None);
+    }
+
+    fn test_embedded_font(text: &str) -> EmbeddedFont {
+        let glyphs: std::collections::HashMap<char, u16> = text.chars().enumerate().map(|(i, c)| (c, i as u16 + 1)).collect();
+        let widths = glyphs.values().map(|gid| (*gid, 500.0)).collect();
+        EmbeddedFont { font_id: (99, 0), glyphs, widths, ascent: 800.0, descent: -200.0 }
+    }
+
+    #[test]
+    fn standard_text_run_falls_back_or_replaces() {
+        // Ohne Ersatzschrift: nicht darstellbare Zeichen werden zu '?'
+        let run = standard_text_run("Łódź", "F1", "Helvetica", 10.0, None);
+        assert_eq!(run.font_key, "F1");
+        assert!(run.single_byte);
+        assert_eq!(run.text, Object::String(vec![b'?', 0xF3, b'd', b'?'], lopdf::StringFormat::Literal));
+
+        // Mit Ersatzschrift: ganzer Text über deren Ressourcennamen als Glyph-IDs
+        let font = test_embedded_font("Łódź");
+        let run = standard_text_run("Łódź", "F1", "Helvetica", 10.0, Some(("FU", &font)));
+        assert_eq!(run.font_key, "FU");
+        assert!(!run.single_byte);
+        assert_eq!(run.text, font.encode("Łódź"));
+        assert!((run.width - 20.0).abs() < 1e-4);
+
+        // WinAnsi-darstellbarer Text bleibt beim Standard-Font
+        assert_eq!(standard_text_run("Müller", "F1", "Helvetica", 10.0, Some(("FU", &font))).font_key, "F1");
+    }
+
+    // Operatoren des zuletzt angehängten Overlay-Streams einer Seite
+    fn overlay_operations(doc: &Document, page_id: lopdf::ObjectId) -> Vec<Operation> {
+        let contents = doc.get_dictionary(page_id).and_then(|page| page.get(b"Contents")).and_then(Object::as_array).unwrap();
+        let overlay_id = contents.last().and_then(|c| c.as_reference().ok()).unwrap();
+        let stream = doc.get_object(overlay_id).and_then(Object::as_stream).unwrap();
+        Content::decode(&stream.content).unwrap().operations
+    }
+
+    #[test]
+    fn overlay_fonts_do_not_replace_inherited_resources() {
+        let mut doc = single_page_document(b"BT /F1 12 Tf (Vorlage) Tj ET");
+        let page_id = *doc.get_pages().get(&1).unwrap();
+        let template_font = doc.add_object(dictionary! { "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Times-Roman" });
+        let fonts_id = doc.add_object(dictionary! { "F1" => template_font });
+        let pages_id = doc.catalog().and_then(|catalog| catalog.get(b"Pages")).and_then(Object::as_reference).unwrap();
+        doc.get_object_mut(pages_id).and_then(Object::as_dict_mut).unwrap()
+            .set("Resources", dictionary! { "Font" => fonts_id });
+
+        let v = VertreterConfig { x: 100.0, y: 100.0, font_name: "Arial".to_string(), font_size: 10.0, ..Default::default() };
+        process_page_elements(&mut doc, page_id, 1, &[PageItem::Text(&v, "4711", None)], None, false, false);
+
+        // Die Seite bekommt eine eigene Kopie: F1 bleibt die Schrift der Vorlage, das Overlay nimmt F2
+        let fonts = doc.get_dictionary(page_id).and_then(|page| page.get(b"Resources")).and_then(Object::as_dict)
+            .and_then(|resources| resources.get(b"Font")).and_then(Object::as_dict).unwrap();
+        assert_eq!(fonts.get(b"F1").and_then(Object::as_reference).unwrap(), template_font);
+        let overlay_font = fonts.get(b"F2").and_then(Object::as_dict).unwrap();
+        assert_eq!(overlay_font.get(b"BaseFont").and_then(Object::as_name).unwrap(), b"Helvetica");
+        let tf = overlay_operations(&doc, page_id).into_iter().find(|op| op.operator == "Tf").unwrap();
+        assert_eq!(tf.operands[0].as_name().unwrap(), b"F2");
+        // Das geerbte Dict der Vorlage bleibt unverändert
+        assert_eq!(doc.get_dictionary(fonts_id).unwrap().len(), 1);
+    }
+
+    // Box mit Ursprung (10, 20), 100 × 200 pt: Config -> Benutzerraum -> Config ergibt das Rechteck
+    #[test]
+    fn page_geometry_matrix_and_display_rect_are_inverse() {
+        let apply = |m: [f32; 6], x: f32, y: f32| (m[0] * x + m[2] * y + m[4], m[1] * x + m[3] * y + m[5]);
+        for rotate in [0, 90, 180, 270] {
+            let geometry = PageGeometry { llx: 10.0, lly: 20.0, width: 100.0, height: 200.0, rotate };
+            let matrix = geometry.overlay_matrix();
+            let (display_w, display_h) = geometry.displayed_size();
+
+            // Der sichtbare Bereich in Anzeigelage deckt genau die Box ab
+            let corners: Vec<(f32, f32)> = [(0.0, 0.0), (display_w, display_h)].iter().map(|&(x, y)| apply(matrix, x, y)).collect();
+            let (xs, ys) = ((corners[0].0.min(corners[1].0), corners[0].0.max(corners[1].0)), (corners[0].1.min(corners[1].1), corners[0].1.max(corners[1].1)));
+            assert_eq!((xs, ys), ((10.0, 110.0), (20.0, 220.0)), "Rotate {}", rotate);
+
+            let rect = [30.0, 50.0, 70.0, 60.0];
+            let (x, y, w, h) = geometry.to_display_rect(rect);
+            let (x1, y1) = apply(matrix, x, y);
+            let (x2, y2) = apply(matrix, x + w, y + h);
+            let back = [x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)];
+            assert_eq!(back, rect, "Rotate {}", rotate);
+            if rotate % 180 == 0 {
+                assert_eq!((w, h), (40.0, 10.0));
+            } else {
+                assert_eq!((w, h), (10.0, 40.0));
+            }
+        }
+        // Anzeigelage bei 90 Grad: links unten liegt die rechte untere Ecke der Box
+        let geometry = PageGeometry { llx: 10.0, lly: 20.0, width: 100.0, height: 200.0, rotate: 90 };
+        assert_eq!(apply(geometry.overlay_matrix(), 0.0, 0.0), (110.0, 20.0));
+    }
+
+    // Ende der Tinte aus den erzeugten Operatoren: Ursprung aus Tm/Td, Vorschub je Glyphe nach den
+    // AFM-Breiten von Helvetica (Ziffern 556/1000 em) plus Tc, ohne Tc nach der letzten Glyphe
+    #[test]
+    fn right_aligned_text_ends_at_anchor_with_char_spacing() {
+        let mut doc = single_page_document(b"");
+        let page_id = *doc.get_pages().get(&1).unwrap();
+        let v = VertreterConfig {
+            x: 200.0, y: 100.0, font_name: "Arial".to_string(), font_size: 10.0, align: TextAlign::Right,
+            char_spacing: 8.0, horizontal_scaling: 90.0, ..Default::default()
+        };
+        process_page_elements(&mut doc, page_id, 1, &[PageItem::Text(&v, "123", None)], None, false, false);
+        let operations = overlay_operations(&doc, page_id);
+        let operand = |operator: &str, i: usize| operations.iter().find(|op| op.operator == operator).unwrap().operands[i].as_float().unwrap();
+        let (tc, tz) = (operand("Tc", 0), operand("Tz", 0) / 100.0);
+        let start_x = operand("Tm", 4) + operand("Td", 0);
+        let tj = operations.iter().find(|op| op.operator == "Tj").unwrap();
+        assert_eq!(tj.operands[0].as_str().unwrap(), b"123");
+        let glyphs = 3.0;
+        let ink_end = start_x + (glyphs * 556.0 / 1000.0 * 10.0 + (glyphs - 1.0) * tc) * tz;
+        assert!((ink_end - 200.0).abs() < 1e-3, "Ende {} statt 200", ink_end);
+        assert_eq!(spaced_text_width(&v, "", 0.0), 0.0);
+    }
+
+    #[test]
+    fn ean13_check_digit_known_answer_and_rejections() {
+        assert_eq!(ean13_with_check_digit("400638133393").unwrap(), "4006381333931");
+        assert_eq!(ean13_with_check_digit("4006381 333931").unwrap(), "4006381333931");
+        assert!(ean13_with_check_digit("4006381333932").unwrap_err().contains("Prüfziffer"));
+        assert!(ean13_with_check_digit("40063813339").unwrap_err().contains("12 oder 13"));
+        assert!(ean13_with_check_digit("40063813339312").is_err());
+        assert!(ean13_with_check_digit("40063813339A").is_err());
+
+        let barcode = encode_barcode(BarcodeSymbology::Ean13, "400638133393").unwrap();
+        assert_eq!((barcode.text.as_str(), barcode.height), ("4006381333931", 1));
+        assert!(encode_barcode(BarcodeSymbology::Ean13, "4006381333932").is_err());
+    }
+
+    // Minimaler Header: SOI, APP14 "Adobe" (invertiertes CMYK), SOF0 mit 40 × 30 Pixeln und 4 Komponenten
+    #[test]
+    fn jpeg_header_info_reads_sof0_and_adobe_marker() {
+        let mut jpeg = vec![0xFF, 0xD8];
+        jpeg.extend_from_slice(&[0xFF, 0xEE, 0x00, 0x0E]);
+        jpeg.extend_from_slice(b"Adobe");
+        jpeg.extend_from_slice(&[0x00, 0x64, 0x00, 0x00, 0x00, 0x00, 0x02]);
+        jpeg.extend_from_slice(&[0xFF, 0xC0, 0x00, 0x14, 0x08, 0x00, 0x1E, 0x00, 0x28, 0x04]);
+        jpeg.extend_from_slice(&[1, 0x11, 0, 2, 0x11, 0, 3, 0x11, 0, 4, 0x11, 0]);
+        jpeg.extend_from_slice(&[0xFF, 0xD9]);
+        assert_eq!(jpeg_header_info(&jpeg), Some((40, 30, 4, true)));
+
+        // Ohne APP14 kein Adobe-Marker; kein JPEG ohne SOI
+        let without_adobe: Vec<u8> = [&jpeg[..2], &jpeg[18..]].concat();
+        assert_eq!(jpeg_header_info(&without_adobe), Some((40, 30, 4, false)));
+        assert_eq!(jpeg_header_info(b"\x89PNG\r\n"), None);
+    }
+
+    #[test]
+    fn image_fit_places_contain_cover_and_stretch() {
+        let frame = (10.0, 20.0, 100.0, 50.0);
+        // Bild 2:1 -> passt bei Contain genau hinein
+        assert_eq!(ImageFit::Contain.place(frame, (200.0, 100.0)), (10.0, 20.0, 100.0, 50.0));
+        // Hochformat 1:2: Contain mittig mit Höhe 50, Cover mit Breite 100 über den Rahmen hinaus
+        assert_eq!(ImageFit::Contain.place(frame, (30.0, 60.0)), (47.5, 20.0, 25.0, 50.0));
+        assert_eq!(ImageFit::Cover.place(frame, (30.0, 60.0)), (10.0, -55.0, 100.0, 200.0));
+        assert_eq!(ImageFit::Stretch.place(frame, (30.0, 60.0)), frame);
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> std::collections::HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn fill_link_template_encodes_values_and_rejects_unknown() {
+        let values = vars(&[("lang", "de"), ("kundennr", "47 11"), ("campaign", "Frühjahr&Sommer")]);
+        assert_eq!(fill_link_template("https://shop.natugena.de/{lang}/?ref={ KundenNr }&utm_campaign={campaign}", &values).unwrap(),
+            "https://shop.natugena.de/de/?ref=47%2011&utm_campaign=Fr%C3%BChjahr%26Sommer");
+        let err = fill_link_template("https://shop.natugena.de/?ref={kundenummer}&c={kampagne}", &values).unwrap_err();
+        assert!(err.contains("{kundenummer}, {kampagne}"), "{}", err);
+        assert!(fill_link_template("https://shop.natugena.de/?ref={kundennr", &values).unwrap_err().contains("Nicht geschlossener"));
+    }
+
+    #[test]
+    fn resolve_qr_link_prefers_csv_unless_template_is_forced() {
+        let values = vars(&[("kundennr", "4711")]);
+        let mut config = Config { link_template: "https://shop.natugena.de/?ref={kundennr}".to_string(), ..Default::default() };
+        // CSV-Link vorhanden: CSV gewinnt, leer: Vorlage als Ersatz
+        assert_eq!(resolve_qr_link(&config, " https://csv.example/a ", &values).unwrap().as_deref(), Some("https://csv.example/a"));
+        assert_eq!(resolve_qr_link(&config, "", &values).unwrap().as_deref(), Some("https://shop.natugena.de/?ref=4711"));
+        // Erzwungen: Vorlage trotz CSV-Link
+        config.force_link_template = true;
+        assert_eq!(resolve_qr_link(&config, "https://csv.example/a", &values).unwrap().as_deref(), Some("https://shop.natugena.de/?ref=4711"));
+        // Ohne Vorlage hat force keine Wirkung, ohne CSV-Link gibt es keinen Link
+        config.link_template.clear();
+        assert_eq!(resolve_qr_link(&config, "https://csv.example/a", &values).unwrap().as_deref(), Some("https://csv.example/a"));
+        assert_eq!(resolve_qr_link(&config, " ", &values).unwrap(), None);
+    }
+
+    #[test]
+    fn resolve_element_link_uses_each_elements_source() {
+        let record = VertreterRecord {
+            nummer: "4711".to_string(),
+            de_link: "https://shop.natugena.de/de/4711".to_string(),
+            en_link: "https://shop.natugena.de/en/4711".to_string(),
+            columns: vars(&[("messe_link", "https://messe.example/4711")]),
+        };
+        let values = vars(&[("kundennr", "4711"), ("lang", "de"), ("lang_code", "de_de")]);
+        let config = Config { link_template: "https://shop.natugena.de/{lang_code}/?ref={kundennr}".to_string(), ..Default::default() };
+        let link = |qr: QrCodeConfig| resolve_element_link(&config, &qr, &record, &record.de_link, &values);
+
+        assert_eq!(link(QrCodeConfig::default()).unwrap().as_deref(), Some("https://shop.natugena.de/de/4711"));
+        assert_eq!(link(QrCodeConfig { link_source: QrLinkSource::English, ..Default::default() }).unwrap().as_deref(), Some("https://shop.natugena.de/en/4711"));
+        let column = QrCodeConfig { link_source: QrLinkSource::Column, link_column: "Messe_Link".to_string(), ..Default::default() };
+        assert_eq!(link(column).unwrap().as_deref(), Some("https://messe.example/4711"));
+        let missing = QrCodeConfig { link_source: QrLinkSource::Column, link_column: "fehlt".to_string(), ..Default::default() };
+        assert!(link(missing).unwrap_err().contains("'fehlt'"));
+        // Eigene Vorlage des Elements vor der Vorlage der Config
+        let own = QrCodeConfig { link_source: QrLinkSource::Template, link_template: "https://kurz.example/{kundennr}".to_string(), ..Default::default() };
+        assert_eq!(link(own).unwrap().as_deref(), Some("https://kurz.example/4711"));
+        let shared = QrCodeConfig { link_source: QrLinkSource::Template, ..Default::default() };
+        assert_eq!(link(shared).unwrap().as_deref(), Some("https://shop.natugena.de/de_de/?ref=4711"));
+
+        // Englische Spalte leer: Ersatz-Vorlage mit der Sprache der Spalte
+        let record = VertreterRecord { en_link: String::new(), ..record.clone() };
+        let english = QrCodeConfig { link_source: QrLinkSource::English, ..Default::default() };
+        assert_eq!(resolve_element_link(&config, &english, &record, &record.de_link, &values).unwrap().as_deref(), Some("https://shop.natugena.de/en_us/?ref=4711"));
+    }
+
+    // Schriftdatei aus den mit egui ausgelieferten Standardschriften (TrueType)
+    #[test]
+    fn embedded_font_subset_maps_non_ascii_in_to_unicode() {
+        let fonts = egui::FontDefinitions::default();
+        let data = fonts.font_data.get("Ubuntu-Light").unwrap().font.to_vec();
+        let mut doc = Document::with_version("1.7");
+        let font = embed_font_subset(&mut doc, &data, "Müller").unwrap();
+        let gid = font.glyphs[&'ü'];
+        assert_ne!(gid, 0);
+        assert!(font.text_width("ü", 10.0) > 0.0);
+        assert_eq!(font.encode("ü"), Object::String(gid.to_be_bytes().to_vec(), lopdf::StringFormat::Hexadecimal));
+
+        let type0 = doc.get_dictionary(font.font_id).unwrap();
+        assert_eq!(type0.get(b"Encoding").and_then(Object::as_name).unwrap(), b"Identity-H");
+        let to_unicode = type0.get(b"ToUnicode").and_then(Object::as_reference).and_then(|id| doc.get_object(id)).and_then(Object::as_stream).unwrap();
+        let cmap = String::from_utf8(to_unicode.content.clone()).unwrap();
+        assert!(cmap.contains(&format!("<{:04X}> <00FC>", gid)), "{}", cmap);
+
+        // Subset-Präfix und eingebettete TrueType-Datei
+        let base_font = type0.get(b"BaseFont").and_then(Object::as_name).unwrap();
+        assert_eq!(base_font[6], b'+');
+        let cid_font = type0.get(b"DescendantFonts").and_then(Object::as_array).unwrap()[0].as_reference().and_then(|id| doc.get_dictionary(id)).unwrap();
+        let descriptor = cid_font.get(b"FontDescriptor").and_then(Object::as_reference).and_then(|id| doc.get_dictionary(id)).unwrap();
+        assert!(descriptor.has(b"FontFile2"));
+
+        assert!(embed_font_subset(&mut doc, &data, "中").err().unwrap().contains("fehlt"));
+    }
+
+    // Ober-/Unterlänge 700/-200 em/1000 bei 10 pt, Textbreite 40 pt
+    #[test]
+    fn text_anchor_offset_for_each_alignment_and_anchor() {
+        let expected_dx = [(TextAlign::Left, 0.0), (TextAlign::Center, -20.0), (TextAlign::Right, -40.0)];
+        let expected_dy = [(TextAnchor::Baseline, 0.0), (TextAnchor::Top, -7.0), (TextAnchor::Middle, -2.5)];
+        for (align, dx) in expected_dx {
+            for (anchor, dy) in expected_dy {
+                let (got_dx, got_dy) = text_anchor_offset(align, anchor, 40.0, 700.0, -200.0, 10.0);
+                assert!((got_dx - dx).abs() < 1e-4 && (got_dy - dy).abs() < 1e-4, "{:?}/{:?}: {:?}", align, anchor, (got_dx, got_dy));
+            }
+        }
+    }
+
+    #[test]
+    fn standard_pdf_font_keeps_previous_fallbacks() {
+        assert_eq!(standard_pdf_font("Times New Roman", "Bold"), "Times-Bold");
+        assert_eq!(standard_pdf_font("Arial", "Italic"), "Helvetica-Oblique");
+        assert_eq!(standard_pdf_font("Courier New", "Normal"), "Courier");
+        // Georgia war schon vor der gemeinsamen Zuordnung ein Helvetica-Fallback
+        assert_eq!(standard_pdf_font("Georgia", "Normal"), "Helvetica");
+        assert_eq!(standard_pdf_font("Georgia", "Bold"), "Helvetica-Bold");
+        assert_eq!(standard_pdf_font("Unbekannt", "Normal"), "Helvetica");
+    }
+
+    // Messung zum Vorlagen-Cache: je Zeile Vorlage neu laden (bisher) gegen einmal vorbereiten und klonen.
+    // Aufruf: cargo test --release -- --ignored template_cache_benchmark --nocapture
+    #[test]
+    #[ignore]
+    fn template_cache_benchmark() {
+        const ROWS: usize = 200;
+        let dir = std::env::temp_dir().join(format!("bestellschein-bench-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // Vorlage mit eingebetteter Schrift und längerem Inhaltsstrom, ähnlich einem echten Bestellschein
+        let content: String = (0..400).map(|i| format!("BT /F1 9 Tf 40 {} Td (Artikel {} Menge ____) Tj ET\n", 40 + i * 2, i)).collect();
+        let mut doc = single_page_document(content.as_bytes());
+        let font_data = egui::FontDefinitions::default().font_data["Ubuntu-Light"].font.to_vec();
+        doc.add_object(lopdf::Stream::new(dictionary! { "Length1" => font_data.len() as i64 }, font_data));
+        let template_path = dir.join("vorlage.pdf");
+        doc.save(&template_path).unwrap();
+        let template_s = template_path.to_string_lossy().to_string();
+        let template_kb = std::fs::metadata(&template_path).unwrap().len() / 1024;
+
+        let config = Config::default();
+        let qr_payloads = vec![None; config.qr_codes().count()];
+        let run = |prepared: &PreparedTemplate, row: usize| {
+            let record = VertreterRecord { nummer: (10000 + row).to_string(), de_link: String::new(), en_link: String::new(), columns: Default::default() };
+            let mut row_vars = link_template_vars(&record, "de_DE", "kunden", &RunOptions::default());
+            row_vars.insert("template".to_string(), "vorlage".to_string());
+            let texts = build_row_vertreter_texts(&config, &row_vars).unwrap();
+            let metadata = build_row_metadata(&config, &row_vars, &prepared.hash).unwrap();
+            let output_path = dir.join(format!("zeile-{}.pdf", row % 4));
+            modify_pdf_with_debug(prepared, &texts, &qr_payloads, &[], &[], &metadata, &config, &output_path, false, OutputMode::Fast, false).unwrap();
+        };
+
+        let start = std::time::Instant::now();
+        for row in 0..ROWS {
+            let prepared = prepare_template(&template_s, &config, false, None, false).unwrap();
+            run(&prepared, row);
+        }
+        let per_row_load = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let prepared = prepare_template(&template_s, &config, false, None, false).unwrap();
+        for row in 0..ROWS {
+            run(&prepared, row);
+        }
+        let cached = start.elapsed();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0 / ROWS as f64;
+        println!("{} Zeilen, Vorlage {} KB", ROWS, template_kb);
+        println!("je Zeile laden:       {:.2} ms/PDF", ms(per_row_load));
+        println!("einmal laden + klonen: {:.2} ms/PDF", ms(cached));
+    }
+}